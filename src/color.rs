@@ -1,5 +1,65 @@
-//! Color-related constants and helpers.
+//! Color-related constants and helpers: color spaces, palettes, lookup
+//! tables and colormaps.
+use image::Rgb;
+
+mod colormap;
+mod lut;
+mod palette;
+mod space;
+
+pub use colormap::{Colormap, COLORMAP_NAMES};
+pub use lut::{ColorLut, Lut1d, Lut3d, LutInterpolation};
+pub use palette::{ColorMetric, Palette, PaletteFormat, PALETTE_NAMES};
+pub use space::{
+    hsl_to_rgb, hsv_to_rgb, lab_to_lch, lab_to_xyz, lch_to_lab, linear_srgb_to_oklab,
+    linear_srgb_to_xyz, linear_to_srgb, oklab_to_linear_srgb, rgb_to_hsl, rgb_to_hsv, rgb_to_ycbcr,
+    srgb_to_linear, xyz_to_lab, xyz_to_linear_srgb, ycbcr_to_rgb, ColorModel, YCbCrMatrix,
+    D65_WHITE,
+};
+
 /// sRGB luminance weights used for RGB→grayscale conversion.
 pub const SRGB_LUMA_R: f64 = 0.2126;
 pub const SRGB_LUMA_G: f64 = 0.7152;
 pub const SRGB_LUMA_B: f64 = 0.0722;
+
+/// Unrounded sRGB luminance of a pixel (0.0..=255.0), weighted by the
+/// `SRGB_LUMA_*` constants.
+pub fn srgb_luma(p: &Rgb<u8>) -> f64 {
+    p[0] as f64 * SRGB_LUMA_R + p[1] as f64 * SRGB_LUMA_G + p[2] as f64 * SRGB_LUMA_B
+}
+
+/// Parse a hex color such as `#ff8800`, `ff8800` or `#f80` into an RGB pixel.
+///
+/// Returns `None` if the string is not a 3- or 6-digit hex color.
+pub fn parse_hex_rgb(s: &str) -> Option<Rgb<u8>> {
+    let hex = s.trim().trim_start_matches('#');
+    if !hex.is_ascii() {
+        return None;
+    }
+    match hex.len() {
+        6 => {
+            let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+            Some(Rgb([channel(0)?, channel(2)?, channel(4)?]))
+        }
+        3 => {
+            // Short form: each digit is repeated (`f80` -> `ff8800`).
+            let channel = |i: usize| u8::from_str_radix(&hex[i..i + 1], 16).ok().map(|v| v * 17);
+            Some(Rgb([channel(0)?, channel(1)?, channel(2)?]))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_hex_rgb() {
+        assert_eq!(parse_hex_rgb("#ff8800"), Some(Rgb([255, 136, 0])));
+        assert_eq!(parse_hex_rgb("0a0B0c"), Some(Rgb([10, 11, 12])));
+        assert_eq!(parse_hex_rgb("#f80"), Some(Rgb([255, 136, 0])));
+        assert_eq!(parse_hex_rgb("#ff88"), None);
+        assert_eq!(parse_hex_rgb("zzzzzz"), None);
+    }
+}
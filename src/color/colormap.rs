@@ -0,0 +1,254 @@
+//! Colormaps for visualizing grayscale data: perceptually uniform maps from
+//! matplotlib, turbo, the legacy jet/hot maps and custom CSV tables.
+use image::Rgb;
+
+/// Names accepted by [`Colormap::named`].
+pub const COLORMAP_NAMES: [&str; 8] = [
+    "viridis", "plasma", "magma", "inferno", "cividis", "turbo", "jet", "hot",
+];
+
+/// Degree-6 polynomial fits of the matplotlib maps (Matt Zucker, CC0):
+/// coefficients of `t^0..=t^6` for red, green and blue.
+const VIRIDIS: [[f32; 3]; 7] = [
+    [0.277_727_33, 0.005_407_344_5, 0.334_099_8],
+    [0.105_093_04, 1.404_613_5, 1.384_590_2],
+    [-0.330_861_83, 0.214_847_56, 0.095_095_16],
+    [-4.634_230_5, -5.799_101, -19.332_441],
+    [6.228_27, 14.179_933, 56.690_55],
+    [4.776_385, -13.745_145, -65.353_03],
+    [-5.435_456, 4.645_852_6, 26.312_435],
+];
+
+const PLASMA: [[f32; 3]; 7] = [
+    [0.058_732_344, 0.023_336_71, 0.543_340_2],
+    [2.176_514_6, 0.238_383_42, 0.753_960_46],
+    [-2.689_460_5, -7.455_851, 3.110_8],
+    [6.130_348, 42.346_19, -28.518_855],
+    [-11.107_436, -82.666_31, 60.139_85],
+    [10.023_066, 71.413_62, -54.072_19],
+    [-3.658_714, -22.931_535, 18.191_908],
+];
+
+const MAGMA: [[f32; 3]; 7] = [
+    [-0.002_136_485, -0.000_749_655, -0.005_386_128],
+    [0.251_660_54, 0.677_523_24, 2.494_026_6],
+    [8.353_717, -3.577_719_5, 0.314_467_9],
+    [-27.668_733, 14.264_731, -13.649_213],
+    [52.176_14, -27.943_606, 12.944_169],
+    [-50.768_524, 29.046_583, 4.234_153],
+    [18.655_705, -11.489_773, -5.601_961_5],
+];
+
+const INFERNO: [[f32; 3]; 7] = [
+    [0.000_218_940_37, 0.001_651_004_6, -0.019_480_898],
+    [0.106_513_42, 0.563_956_44, 3.932_712_3],
+    [11.602_493, -3.972_854, -15.942_394],
+    [-41.703_995, 17.436_4, 44.354_145],
+    [77.162_94, -33.402_36, -81.807_31],
+    [-71.319_43, 32.626_064, 73.209_52],
+    [25.131_126, -12.242_669, -23.070_325],
+];
+
+/// Polynomial approximation of turbo published with the map (Google,
+/// Apache-2.0): coefficients of `t^0..=t^5`.
+const TURBO: [[f32; 3]; 6] = [
+    [0.135_721_38, 0.091_402_61, 0.106_673_3],
+    [4.615_392_6, 2.194_188_4, 12.641_946],
+    [-42.660_324, 4.842_966_6, -60.582_05],
+    [132.131_08, -14.185_033, 110.362_77],
+    [-152.942_4, 4.277_298_5, -89.903_11],
+    [59.286_38, 2.829_566, 27.348_25],
+];
+
+/// Cividis sampled at 18 evenly spaced stops.
+const CIVIDIS: [[u8; 3]; 18] = [
+    [0, 32, 76],
+    [0, 42, 102],
+    [0, 52, 110],
+    [39, 63, 108],
+    [60, 74, 107],
+    [76, 85, 107],
+    [91, 95, 109],
+    [104, 106, 112],
+    [117, 117, 117],
+    [131, 129, 120],
+    [146, 140, 120],
+    [161, 152, 118],
+    [176, 165, 114],
+    [192, 177, 109],
+    [209, 191, 102],
+    [225, 204, 92],
+    [243, 219, 79],
+    [255, 233, 69],
+];
+
+/// A 256-entry table mapping gray levels to colors.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Colormap {
+    table: Vec<Rgb<u8>>,
+}
+
+fn to_u8(v: f32) -> u8 {
+    (v * 255.0).round().clamp(0.0, 255.0) as u8
+}
+
+fn polynomial<const N: usize>(coefficients: &[[f32; 3]; N], t: f32) -> Rgb<u8> {
+    Rgb(std::array::from_fn(|c| {
+        let v = coefficients.iter().rev().fold(0.0, |acc, k| acc * t + k[c]);
+        to_u8(v)
+    }))
+}
+
+impl Colormap {
+    /// Build a colormap from a function of `t` in `0.0..=1.0`.
+    pub fn from_fn(f: impl Fn(f32) -> Rgb<u8>) -> Self {
+        Self {
+            table: (0..256).map(|i| f(i as f32 / 255.0)).collect(),
+        }
+    }
+
+    /// Resample evenly spaced colors (at least 2) to 256 entries by linear
+    /// interpolation.
+    pub fn from_colors(colors: &[Rgb<u8>]) -> Option<Self> {
+        if colors.len() < 2 {
+            return None;
+        }
+        let last = (colors.len() - 1) as f32;
+        Some(Self::from_fn(|t| {
+            let pos = t * last;
+            let i = (pos.floor() as usize).min(colors.len() - 2);
+            let f = pos - i as f32;
+            let (a, b) = (colors[i], colors[i + 1]);
+            Rgb(std::array::from_fn(|c| {
+                (a[c] as f32 + (b[c] as f32 - a[c] as f32) * f).round() as u8
+            }))
+        }))
+    }
+
+    /// Look up a built-in colormap by name (see [`COLORMAP_NAMES`]).
+    pub fn named(name: &str) -> Option<Self> {
+        let ramp = |t: f32| t.clamp(0.0, 1.0);
+        Some(match name {
+            "viridis" => Self::from_fn(|t| polynomial(&VIRIDIS, t)),
+            "plasma" => Self::from_fn(|t| polynomial(&PLASMA, t)),
+            "magma" => Self::from_fn(|t| polynomial(&MAGMA, t)),
+            "inferno" => Self::from_fn(|t| polynomial(&INFERNO, t)),
+            "turbo" => Self::from_fn(|t| polynomial(&TURBO, t)),
+            "cividis" => Self::from_colors(&CIVIDIS.map(Rgb))?,
+            "jet" => Self::from_fn(|t| {
+                Rgb([3.0, 2.0, 1.0].map(|center| to_u8(ramp(1.5 - (4.0 * t - center).abs()))))
+            }),
+            "hot" => Self::from_fn(|t| {
+                Rgb([
+                    to_u8(ramp(t * 8.0 / 3.0)),
+                    to_u8(ramp((t - 3.0 / 8.0) * 8.0 / 3.0)),
+                    to_u8(ramp((t - 3.0 / 4.0) * 4.0)),
+                ])
+            }),
+            _ => return None,
+        })
+    }
+
+    /// Parse a CSV table with one `r,g,b` row per stop, evenly spaced from
+    /// black to white. Values are either all in `0.0..=1.0` or bytes in
+    /// `0..=255`; lines that do not start with a number (headers) and `#`
+    /// comments are skipped.
+    pub fn from_csv(text: &str) -> Result<Self, String> {
+        let mut rows: Vec<[f32; 3]> = Vec::new();
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if !line.starts_with(|c: char| c.is_ascii_digit() || c == '.') {
+                continue;
+            }
+            let values = line
+                .split([',', ';', '\t'])
+                .map(|v| v.trim().parse::<f32>())
+                .collect::<Result<Vec<f32>, _>>()
+                .map_err(|_| format!("line {}: invalid number", number + 1))?;
+            let [r, g, b] = values[..] else {
+                return Err(format!("line {}: expected r,g,b", number + 1));
+            };
+            rows.push([r, g, b]);
+        }
+        let fractions = rows.iter().flatten().all(|&v| v <= 1.0);
+        if rows.iter().flatten().any(|&v| !(0.0..=255.0).contains(&v)) {
+            return Err("colormap values must be in 0..1 or 0..255".into());
+        }
+        let colors: Vec<Rgb<u8>> = rows
+            .iter()
+            .map(|rgb| Rgb(rgb.map(|v| if fractions { to_u8(v) } else { v.round() as u8 })))
+            .collect();
+        Self::from_colors(&colors).ok_or_else(|| "a colormap needs at least 2 rows".into())
+    }
+
+    /// The same map running from white to black.
+    pub fn reversed(mut self) -> Self {
+        self.table.reverse();
+        self
+    }
+
+    /// Color of a gray level.
+    pub fn color(&self, level: u8) -> Rgb<u8> {
+        self.table[level as usize]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: Rgb<u8>, b: [u8; 3], tolerance: i32) -> bool {
+        a.0.iter()
+            .zip(b)
+            .all(|(&x, y)| (x as i32 - y as i32).abs() <= tolerance)
+    }
+
+    #[test]
+    fn test_builtin_maps_match_references() {
+        for name in COLORMAP_NAMES {
+            assert!(Colormap::named(name).is_some(), "{}", name);
+        }
+        assert!(Colormap::named("rainbow").is_none());
+        // The polynomial fits stay within a few levels of the matplotlib tables
+        let viridis = Colormap::named("viridis").unwrap();
+        assert!(close(viridis.color(0), [68, 1, 84], 6));
+        assert!(close(viridis.color(128), [33, 145, 140], 6));
+        assert!(close(viridis.color(255), [253, 231, 37], 6));
+        let magma = Colormap::named("magma").unwrap();
+        assert!(close(magma.color(0), [0, 0, 4], 6));
+        assert!(close(magma.color(255), [252, 253, 191], 6));
+        let inferno = Colormap::named("inferno").unwrap();
+        assert!(close(inferno.color(255), [252, 255, 164], 6));
+        let plasma = Colormap::named("plasma").unwrap();
+        assert!(close(plasma.color(0), [13, 8, 135], 6));
+        assert!(close(plasma.color(255), [240, 249, 33], 6));
+        let turbo = Colormap::named("turbo").unwrap();
+        let (low, mid, high) = (turbo.color(0), turbo.color(128), turbo.color(255));
+        assert!(low[0] < 64 && mid[1] > 200 && high[0] > 4 * high[2]);
+        let cividis = Colormap::named("cividis").unwrap();
+        assert_eq!(cividis.color(0), Rgb([0, 32, 76]));
+        assert_eq!(cividis.color(255), Rgb([255, 233, 69]));
+        let jet = Colormap::named("jet").unwrap();
+        assert_eq!(jet.color(0), Rgb([0, 0, 128]));
+        assert!(close(jet.color(128), [128, 255, 128], 3));
+        let hot = Colormap::named("hot").unwrap();
+        assert_eq!(
+            (hot.color(0), hot.color(255)),
+            (Rgb([0, 0, 0]), Rgb([255; 3]))
+        );
+    }
+
+    #[test]
+    fn test_csv_and_reverse() {
+        let map = Colormap::from_csv("r,g,b\n0,0,0\n# mid\n255,0,0\n255,255,255\n").unwrap();
+        assert_eq!(map.color(0), Rgb([0, 0, 0]));
+        assert_eq!(map.color(255), Rgb([255, 255, 255]));
+        assert_eq!(map.color(64), Rgb([128, 0, 0]));
+        let fractions = Colormap::from_csv("0.0, 0.0, 1.0\n1.0, 0.5, 0.0\n").unwrap();
+        assert_eq!(fractions.color(255), Rgb([255, 128, 0]));
+        assert_eq!(fractions.clone().reversed().color(0), Rgb([255, 128, 0]));
+        assert!(Colormap::from_csv("0,0,0\n").is_err());
+        assert!(Colormap::from_csv("0,0\n1,1\n").is_err());
+        assert!(Colormap::from_csv("0,0,0\n300,0,0\n").is_err());
+    }
+}
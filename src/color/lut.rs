@@ -0,0 +1,464 @@
+//! Color lookup tables: Adobe/Resolve `.cube` files and HaldCLUT images.
+//!
+//! A [`ColorLut`] holds an optional 1D table (applied first, per channel)
+//! and an optional 3D lattice. Values are stored as normalized `0.0..=1.0`
+//! floats, like in `.cube` files.
+use std::fmt::Write;
+use std::path::Path;
+use std::str::FromStr;
+
+use image::{ImageBuffer, Rgb};
+
+/// Largest 1D table allowed by the `.cube` specification.
+const MAX_1D_SIZE: usize = 65536;
+/// Largest 3D lattice allowed by the `.cube` specification.
+const MAX_3D_SIZE: usize = 256;
+
+/// How a 3D lattice is interpolated between its points.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum LutInterpolation {
+    /// Blend the 8 surrounding lattice points.
+    #[default]
+    Trilinear,
+    /// Blend the 4 points of the enclosing tetrahedron; keeps neutral grays
+    /// exactly on the gray axis and is the usual choice in grading tools.
+    Tetrahedral,
+}
+
+/// A 1D table with one `[r, g, b]` entry per step.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Lut1d {
+    /// Input mapped to the first entry, per channel.
+    pub domain_min: [f32; 3],
+    /// Input mapped to the last entry, per channel.
+    pub domain_max: [f32; 3],
+    pub table: Vec<[f32; 3]>,
+}
+
+/// A `size`³ lattice of `[r, g, b]` outputs, red varying fastest.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Lut3d {
+    /// Input mapped to the first lattice point, per channel.
+    pub domain_min: [f32; 3],
+    /// Input mapped to the last lattice point, per channel.
+    pub domain_max: [f32; 3],
+    pub size: usize,
+    pub table: Vec<[f32; 3]>,
+}
+
+/// A color lookup table made of an optional 1D shaper and an optional 3D lattice.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ColorLut {
+    pub title: Option<String>,
+    pub shaper: Option<Lut1d>,
+    pub cube: Option<Lut3d>,
+}
+
+/// Position of `v` in a table of `size` entries spanning `min..=max`.
+fn table_position(v: f32, min: f32, max: f32, size: usize) -> f32 {
+    let t = if max > min {
+        (v - min) / (max - min)
+    } else {
+        0.0
+    };
+    t.clamp(0.0, 1.0) * (size - 1) as f32
+}
+
+/// Lower index and fraction for a table position.
+fn split_position(pos: f32, size: usize) -> (usize, f32) {
+    let i = (pos.floor() as usize).min(size.saturating_sub(2));
+    (i, pos - i as f32)
+}
+
+impl Lut1d {
+    /// Look up one color, interpolating linearly per channel.
+    pub fn sample(&self, rgb: [f32; 3]) -> [f32; 3] {
+        let n = self.table.len();
+        std::array::from_fn(|c| {
+            let pos = table_position(rgb[c], self.domain_min[c], self.domain_max[c], n);
+            let (i, f) = split_position(pos, n);
+            let (a, b) = (self.table[i][c], self.table[(i + 1).min(n - 1)][c]);
+            a + (b - a) * f
+        })
+    }
+}
+
+impl Lut3d {
+    /// The identity lattice over `0.0..=1.0`.
+    pub fn identity(size: usize) -> Self {
+        let step = 1.0 / (size - 1) as f32;
+        let table = (0..size * size * size)
+            .map(|i| [i % size, i / size % size, i / (size * size)].map(|v| v as f32 * step))
+            .collect();
+        Self {
+            domain_min: [0.0; 3],
+            domain_max: [1.0; 3],
+            size,
+            table,
+        }
+    }
+
+    fn at(&self, r: usize, g: usize, b: usize) -> [f32; 3] {
+        self.table[r + self.size * (g + self.size * b)]
+    }
+
+    /// Look up one color.
+    pub fn sample(&self, rgb: [f32; 3], interpolation: LutInterpolation) -> [f32; 3] {
+        let n = self.size;
+        let [(r, fr), (g, fg), (b, fb)] = std::array::from_fn(|c| {
+            let pos = table_position(rgb[c], self.domain_min[c], self.domain_max[c], n);
+            split_position(pos, n)
+        });
+        let (r1, g1, b1) = ((r + 1).min(n - 1), (g + 1).min(n - 1), (b + 1).min(n - 1));
+        let c000 = self.at(r, g, b);
+        let c111 = self.at(r1, g1, b1);
+        match interpolation {
+            LutInterpolation::Trilinear => {
+                let lerp = |a: [f32; 3], b: [f32; 3], t: f32| -> [f32; 3] {
+                    std::array::from_fn(|c| a[c] + (b[c] - a[c]) * t)
+                };
+                let c00 = lerp(c000, self.at(r1, g, b), fr);
+                let c10 = lerp(self.at(r, g1, b), self.at(r1, g1, b), fr);
+                let c01 = lerp(self.at(r, g, b1), self.at(r1, g, b1), fr);
+                let c11 = lerp(self.at(r, g1, b1), c111, fr);
+                lerp(lerp(c00, c10, fg), lerp(c01, c11, fg), fb)
+            }
+            LutInterpolation::Tetrahedral => {
+                // Walk from c000 to c111 along the edges of the tetrahedron
+                // containing the point, largest fraction first.
+                let (first, second, weights) = if fr > fg {
+                    if fg > fb {
+                        (self.at(r1, g, b), self.at(r1, g1, b), [fr, fg, fb])
+                    } else if fr > fb {
+                        (self.at(r1, g, b), self.at(r1, g, b1), [fr, fb, fg])
+                    } else {
+                        (self.at(r, g, b1), self.at(r1, g, b1), [fb, fr, fg])
+                    }
+                } else if fb > fg {
+                    (self.at(r, g, b1), self.at(r, g1, b1), [fb, fg, fr])
+                } else if fb > fr {
+                    (self.at(r, g1, b), self.at(r, g1, b1), [fg, fb, fr])
+                } else {
+                    (self.at(r, g1, b), self.at(r1, g1, b), [fg, fr, fb])
+                };
+                let [w1, w2, w3] = weights;
+                std::array::from_fn(|c| {
+                    c000[c]
+                        + w1 * (first[c] - c000[c])
+                        + w2 * (second[c] - first[c])
+                        + w3 * (c111[c] - second[c])
+                })
+            }
+        }
+    }
+}
+
+impl ColorLut {
+    /// Look up one normalized color: the 1D shaper first, then the 3D lattice.
+    pub fn sample(&self, rgb: [f32; 3], interpolation: LutInterpolation) -> [f32; 3] {
+        let rgb = self.shaper.as_ref().map_or(rgb, |s| s.sample(rgb));
+        self.cube
+            .as_ref()
+            .map_or(rgb, |c| c.sample(rgb, interpolation))
+    }
+
+    /// Load a `.cube` file, or a HaldCLUT image for any other extension.
+    pub fn open(path: &Path) -> Result<Self, String> {
+        let is_cube = path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("cube"));
+        let error = |e: &dyn std::fmt::Display| format!("{}: {e}", path.display());
+        if is_cube {
+            let text = std::fs::read_to_string(path).map_err(|e| error(&e))?;
+            return text.parse().map_err(|e: String| error(&e));
+        }
+        let img = image::open(path).map_err(|e| error(&e))?;
+        Self::from_hald(&img.to_rgb8()).map_err(|e| error(&e))
+    }
+
+    /// Read a HaldCLUT image: a square of side `level³` holding a `level²`
+    /// lattice with red varying fastest.
+    pub fn from_hald(img: &ImageBuffer<Rgb<u8>, Vec<u8>>) -> Result<Self, String> {
+        let (width, height) = img.dimensions();
+        let level = (2..=16u32).find(|l| l * l * l == width);
+        let Some(level) = level.filter(|_| width == height) else {
+            return Err(format!(
+                "HaldCLUT images must be square with a side of level³ (8, 27, 64, ...), got {width}x{height}"
+            ));
+        };
+        let table = img
+            .pixels()
+            .map(|p| p.0.map(|v| v as f32 / 255.0))
+            .collect();
+        Ok(Self {
+            title: None,
+            shaper: None,
+            cube: Some(Lut3d {
+                domain_min: [0.0; 3],
+                domain_max: [1.0; 3],
+                size: (level * level) as usize,
+                table,
+            }),
+        })
+    }
+
+    /// Bake a color transform into a `size`³ lattice by running it over an
+    /// image that holds every lattice point once.
+    ///
+    /// The transform must work pixel by pixel; filters that look at
+    /// neighbours (blur, edges, dithering) do not produce meaningful LUTs.
+    /// Lattice inputs are rounded to 8 bits before the transform runs.
+    pub fn bake(
+        size: usize,
+        transform: impl Fn(&ImageBuffer<Rgb<u8>, Vec<u8>>) -> ImageBuffer<Rgb<u8>, Vec<u8>>,
+    ) -> Self {
+        let identity = Lut3d::identity(size);
+        let lattice: ImageBuffer<Rgb<u8>, Vec<u8>> =
+            ImageBuffer::from_fn((size * size) as u32, size as u32, |x, y| {
+                let rgb = identity.table[y as usize * size * size + x as usize];
+                Rgb(rgb.map(|v| (v * 255.0).round() as u8))
+            });
+        let graded = transform(&lattice);
+        let table = graded
+            .pixels()
+            .map(|p| p.0.map(|v| v as f32 / 255.0))
+            .collect();
+        Self {
+            title: None,
+            shaper: None,
+            cube: Some(Lut3d { table, ..identity }),
+        }
+    }
+
+    /// Serialize as a `.cube` file.
+    ///
+    /// A LUT with a single table is written with `DOMAIN_MIN`/`DOMAIN_MAX`.
+    /// A shaper and a lattice together use Resolve's `LUT_1D_INPUT_RANGE` and
+    /// `LUT_3D_INPUT_RANGE`, which hold one range for all channels, so the
+    /// red channel's domain is written for both.
+    pub fn to_cube(&self) -> String {
+        let mut out = String::new();
+        if let Some(title) = &self.title {
+            let _ = writeln!(out, "TITLE \"{title}\"");
+        }
+        let domain = |out: &mut String, min: [f32; 3], max: [f32; 3]| {
+            if min != [0.0; 3] || max != [1.0; 3] {
+                let _ = writeln!(out, "DOMAIN_MIN {} {} {}", min[0], min[1], min[2]);
+                let _ = writeln!(out, "DOMAIN_MAX {} {} {}", max[0], max[1], max[2]);
+            }
+        };
+        match (&self.shaper, &self.cube) {
+            (Some(s), Some(c)) => {
+                let _ = writeln!(out, "LUT_1D_SIZE {}", s.table.len());
+                let _ = writeln!(
+                    out,
+                    "LUT_1D_INPUT_RANGE {} {}",
+                    s.domain_min[0], s.domain_max[0]
+                );
+                let _ = writeln!(out, "LUT_3D_SIZE {}", c.size);
+                let _ = writeln!(
+                    out,
+                    "LUT_3D_INPUT_RANGE {} {}",
+                    c.domain_min[0], c.domain_max[0]
+                );
+            }
+            (Some(s), None) => {
+                let _ = writeln!(out, "LUT_1D_SIZE {}", s.table.len());
+                domain(&mut out, s.domain_min, s.domain_max);
+            }
+            (None, Some(c)) => {
+                let _ = writeln!(out, "LUT_3D_SIZE {}", c.size);
+                domain(&mut out, c.domain_min, c.domain_max);
+            }
+            (None, None) => {}
+        }
+        out.push('\n');
+        let tables = self.shaper.iter().map(|s| &s.table);
+        for rgb in tables.chain(self.cube.iter().map(|c| &c.table)).flatten() {
+            let _ = writeln!(out, "{:.6} {:.6} {:.6}", rgb[0], rgb[1], rgb[2]);
+        }
+        out
+    }
+}
+
+/// Parse the numbers after a `.cube` keyword.
+fn cube_numbers<const N: usize>(keyword: &str, rest: &[&str]) -> Result<[f32; N], String> {
+    let values = rest
+        .iter()
+        .map(|v| v.parse::<f32>())
+        .collect::<Result<Vec<f32>, _>>()
+        .ok()
+        .filter(|v| v.len() == N);
+    values
+        .and_then(|v| v.try_into().ok())
+        .ok_or_else(|| format!("{keyword} expects {N} numbers"))
+}
+
+impl FromStr for ColorLut {
+    type Err = String;
+
+    /// Parse an Adobe or Resolve `.cube` file.
+    ///
+    /// Supports `TITLE`, `LUT_1D_SIZE`, `LUT_3D_SIZE`, `DOMAIN_MIN`,
+    /// `DOMAIN_MAX`, `LUT_1D_INPUT_RANGE` and `LUT_3D_INPUT_RANGE`; other
+    /// keywords and `#` comments are ignored. When both sizes are given the
+    /// 1D entries come first.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut title = None;
+        let (mut size_1d, mut size_3d) = (None, None);
+        let (mut domain_min, mut domain_max) = ([0.0f32; 3], [1.0f32; 3]);
+        let (mut range_1d, mut range_3d) = (None, None);
+        let mut data: Vec<[f32; 3]> = Vec::new();
+        for (number, line) in s.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let tokens: Vec<&str> = line.split_whitespace().collect();
+            let (keyword, rest) = (tokens[0], &tokens[1..]);
+            let size = |max: usize| -> Result<usize, String> {
+                rest.first()
+                    .and_then(|v| v.parse::<usize>().ok())
+                    .filter(|n| (2..=max).contains(n))
+                    .ok_or_else(|| format!("{keyword} must be between 2 and {max}"))
+            };
+            match keyword {
+                "TITLE" => title = Some(line[5..].trim().trim_matches('"').to_string()),
+                "LUT_1D_SIZE" => size_1d = Some(size(MAX_1D_SIZE)?),
+                "LUT_3D_SIZE" => size_3d = Some(size(MAX_3D_SIZE)?),
+                "DOMAIN_MIN" => domain_min = cube_numbers(keyword, rest)?,
+                "DOMAIN_MAX" => domain_max = cube_numbers(keyword, rest)?,
+                "LUT_1D_INPUT_RANGE" => range_1d = Some(cube_numbers::<2>(keyword, rest)?),
+                "LUT_3D_INPUT_RANGE" => range_3d = Some(cube_numbers::<2>(keyword, rest)?),
+                _ if keyword.starts_with(|c: char| c.is_ascii_alphabetic()) => {}
+                _ => data.push(
+                    cube_numbers(keyword, &tokens)
+                        .map_err(|_| format!("line {}: expected 3 numbers", number + 1))?,
+                ),
+            }
+        }
+        let expected = size_1d.unwrap_or(0) + size_3d.map_or(0, |n| n * n * n);
+        if expected == 0 {
+            return Err("missing LUT_1D_SIZE or LUT_3D_SIZE".into());
+        }
+        if data.len() != expected {
+            return Err(format!(
+                "expected {expected} table entries, found {}",
+                data.len()
+            ));
+        }
+        let domain = |range: Option<[f32; 2]>| match range {
+            Some([min, max]) => ([min; 3], [max; 3]),
+            None => (domain_min, domain_max),
+        };
+        let cube_table = data.split_off(size_1d.unwrap_or(0));
+        let shaper = size_1d.map(|_| {
+            let (domain_min, domain_max) = domain(range_1d);
+            Lut1d {
+                domain_min,
+                domain_max,
+                table: data,
+            }
+        });
+        let cube = size_3d.map(|size| {
+            let (domain_min, domain_max) = domain(range_3d);
+            Lut3d {
+                domain_min,
+                domain_max,
+                size,
+                table: cube_table,
+            }
+        });
+        Ok(Self {
+            title,
+            shaper,
+            cube,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A 2³ lattice that swaps red and blue.
+    const SWAP_RB: &str = "# swap red and blue
+TITLE \"swap\"
+LUT_3D_SIZE 2
+
+0 0 0
+0 0 1
+0 1 0
+0 1 1
+1 0 0
+1 0 1
+1 1 0
+1 1 1
+";
+
+    #[test]
+    fn test_parse_and_sample_cube() {
+        let lut: ColorLut = SWAP_RB.parse().unwrap();
+        assert_eq!(lut.title.as_deref(), Some("swap"));
+        assert!(lut.shaper.is_none());
+        for interpolation in [LutInterpolation::Trilinear, LutInterpolation::Tetrahedral] {
+            let out = lut.sample([0.2, 0.5, 0.9], interpolation);
+            for (a, b) in out.iter().zip([0.9, 0.5, 0.2]) {
+                assert!((a - b).abs() < 1e-6, "{:?} {:?}", interpolation, out);
+            }
+        }
+        assert!("LUT_3D_SIZE 2\n0 0 0\n".parse::<ColorLut>().is_err());
+        assert!("0 0 0\n".parse::<ColorLut>().is_err());
+        assert!("LUT_3D_SIZE 2\nfoo 0 0\n".parse::<ColorLut>().is_err());
+    }
+
+    #[test]
+    fn test_shaper_and_domain() {
+        // A 1D inversion over 0..2 followed by an identity lattice
+        let mut text =
+            String::from("LUT_1D_SIZE 2\nLUT_1D_INPUT_RANGE 0 2\nLUT_3D_SIZE 2\n1 1 1\n0 0 0\n");
+        for [r, g, b] in Lut3d::identity(2).table {
+            text.push_str(&format!("{r} {g} {b}\n"));
+        }
+        let lut: ColorLut = text.parse().unwrap();
+        let shaper = lut.shaper.as_ref().unwrap();
+        assert_eq!((shaper.domain_min, shaper.domain_max), ([0.0; 3], [2.0; 3]));
+        let out = lut.sample([1.0, 0.0, 2.0], LutInterpolation::Trilinear);
+        assert_eq!(out, [0.5, 1.0, 0.0]);
+        let round_trip: ColorLut = lut.to_cube().parse().unwrap();
+        assert_eq!(round_trip, lut);
+    }
+
+    #[test]
+    fn test_tetrahedral_keeps_grays_neutral() {
+        // A lattice where only the pure-red corner is tinted
+        let mut lut = Lut3d::identity(2);
+        lut.table[1] = [1.0, 0.3, 0.3];
+        for v in [0.1, 0.5, 0.8] {
+            let out = lut.sample([v; 3], LutInterpolation::Tetrahedral);
+            assert!(out.iter().all(|&c| (c - v).abs() < 1e-6), "{:?}", out);
+        }
+        let out = lut.sample([0.5; 3], LutInterpolation::Trilinear);
+        assert!(out[1] > 0.5 + 1e-3, "{:?}", out);
+    }
+
+    #[test]
+    fn test_hald_and_bake() {
+        // A level-2 HaldCLUT (8x8, 4³ lattice) baked from an inversion
+        let baked = ColorLut::bake(4, |img| {
+            ImageBuffer::from_fn(img.width(), img.height(), |x, y| {
+                Rgb(img.get_pixel(x, y).0.map(|v| 255 - v))
+            })
+        });
+        let cube = baked.cube.as_ref().unwrap();
+        let hald: ImageBuffer<Rgb<u8>, Vec<u8>> = ImageBuffer::from_fn(8, 8, |x, y| {
+            Rgb(cube.table[(y * 8 + x) as usize].map(|v| (v * 255.0).round() as u8))
+        });
+        let lut = ColorLut::from_hald(&hald).unwrap();
+        assert_eq!(lut, baked);
+        let out = lut.sample([0.0, 1.0, 0.2], LutInterpolation::Tetrahedral);
+        assert_eq!(out.map(|v| (v * 255.0).round() as u8), [255, 0, 204]);
+        let bad: ImageBuffer<Rgb<u8>, Vec<u8>> = ImageBuffer::new(10, 10);
+        assert!(ColorLut::from_hald(&bad).is_err());
+    }
+}
@@ -0,0 +1,376 @@
+//! Palettes: built-in retro palettes, palette file formats and nearest-color lookup.
+use image::Rgb;
+
+use super::{linear_srgb_to_oklab, parse_hex_rgb, srgb_to_linear};
+
+/// A list of RGB colors that images can be reduced or snapped to.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Palette {
+    colors: Vec<Rgb<u8>>,
+}
+
+/// Distance used to decide which palette color is "nearest".
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ColorMetric {
+    /// Plain Euclidean distance on sRGB values.
+    Rgb,
+    /// "Redmean" weighted RGB distance, a cheap perceptual approximation.
+    Redmean,
+    /// Euclidean distance in OKLab (perceptually uniform).
+    Oklab,
+}
+
+/// Supported palette file formats.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PaletteFormat {
+    /// GIMP palette (`.gpl`).
+    Gpl,
+    /// Adobe Color Table (`.act`): 256 binary RGB triplets plus an optional count.
+    Act,
+    /// JASC / Paint Shop Pro palette (`.pal`).
+    Pal,
+    /// One hex color per line (`.hex`, `.txt`).
+    Hex,
+}
+
+/// Names accepted by [`Palette::named`].
+pub const PALETTE_NAMES: [&str; 8] = [
+    "gameboy",
+    "pico8",
+    "cga",
+    "ega",
+    "nes",
+    "c64",
+    "zx-spectrum",
+    "web-safe",
+];
+
+const GAMEBOY: [u32; 4] = [0x0f380f, 0x306230, 0x8bac0f, 0x9bbc0f];
+
+const PICO8: [u32; 16] = [
+    0x000000, 0x1d2b53, 0x7e2553, 0x008751, 0xab5236, 0x5f574f, 0xc2c3c7, 0xfff1e8, 0xff004d,
+    0xffa300, 0xffec27, 0x00e436, 0x29adff, 0x83769c, 0xff77a8, 0xffccaa,
+];
+
+/// The 16 RGBI colors of CGA text mode (dark yellow rendered as brown).
+const CGA: [u32; 16] = [
+    0x000000, 0x0000aa, 0x00aa00, 0x00aaaa, 0xaa0000, 0xaa00aa, 0xaa5500, 0xaaaaaa, 0x555555,
+    0x5555ff, 0x55ff55, 0x55ffff, 0xff5555, 0xff55ff, 0xffff55, 0xffffff,
+];
+
+/// NES 2C02 palette with the duplicate blacks removed.
+const NES: [u32; 54] = [
+    0x7c7c7c, 0x0000fc, 0x0000bc, 0x4428bc, 0x940084, 0xa80020, 0xa81000, 0x881400, 0x503000,
+    0x007800, 0x006800, 0x005800, 0x004058, 0x000000, 0xbcbcbc, 0x0078f8, 0x0058f8, 0x6844fc,
+    0xd800cc, 0xe40058, 0xf83800, 0xe45c10, 0xac7c00, 0x00b800, 0x00a800, 0x00a844, 0x008888,
+    0xf8f8f8, 0x3cbcfc, 0x6888fc, 0x9878f8, 0xf878f8, 0xf85898, 0xf87858, 0xfca044, 0xf8b800,
+    0xb8f818, 0x58d854, 0x58f898, 0x00e8d8, 0x787878, 0xfcfcfc, 0xa4e4fc, 0xb8b8f8, 0xd8b8f8,
+    0xf8b8f8, 0xf8a4c0, 0xf0d0b0, 0xfce0a8, 0xf8d878, 0xd8f878, 0xb8f8b8, 0xb8f8d8, 0x00fcfc,
+];
+
+/// Commodore 64 palette (Pepto's measurements).
+const C64: [u32; 16] = [
+    0x000000, 0xffffff, 0x68372b, 0x70a4b2, 0x6f3d86, 0x588d43, 0x352879, 0xb8c76f, 0x6f4f25,
+    0x433900, 0x9a6759, 0x444444, 0x6c6c6c, 0x9ad284, 0x6c5eb5, 0x959595,
+];
+
+/// ZX Spectrum normal and bright colors (bright black is the same as black).
+const ZX_SPECTRUM: [u32; 15] = [
+    0x000000, 0x0000d7, 0xd70000, 0xd700d7, 0x00d700, 0x00d7d7, 0xd7d700, 0xd7d7d7, 0x0000ff,
+    0xff0000, 0xff00ff, 0x00ff00, 0x00ffff, 0xffff00, 0xffffff,
+];
+
+fn from_hex_values(values: &[u32]) -> Vec<Rgb<u8>> {
+    values
+        .iter()
+        .map(|&v| Rgb([(v >> 16) as u8, (v >> 8) as u8, v as u8]))
+        .collect()
+}
+
+/// Every combination of `levels` evenly spaced values per channel.
+fn channel_cube(levels: &[u8]) -> Vec<Rgb<u8>> {
+    let mut colors = Vec::with_capacity(levels.len().pow(3));
+    for &r in levels {
+        for &g in levels {
+            for &b in levels {
+                colors.push(Rgb([r, g, b]));
+            }
+        }
+    }
+    colors
+}
+
+impl Palette {
+    /// Create a palette from the given colors (must not be empty).
+    pub fn new(colors: Vec<Rgb<u8>>) -> Self {
+        assert!(
+            !colors.is_empty(),
+            "Palette must contain at least one color"
+        );
+        Self { colors }
+    }
+
+    /// Look up a built-in palette by name (see [`PALETTE_NAMES`]).
+    pub fn named(name: &str) -> Option<Self> {
+        let colors = match name.to_ascii_lowercase().as_str() {
+            "gameboy" | "dmg" => from_hex_values(&GAMEBOY),
+            "pico8" | "pico-8" => from_hex_values(&PICO8),
+            "cga" => from_hex_values(&CGA),
+            // All 64 colors of the EGA 6-bit (rgbRGB) palette.
+            "ega" => channel_cube(&[0x00, 0x55, 0xaa, 0xff]),
+            "nes" => from_hex_values(&NES),
+            "c64" => from_hex_values(&C64),
+            "zx-spectrum" | "zx" => from_hex_values(&ZX_SPECTRUM),
+            "web-safe" | "websafe" => channel_cube(&[0x00, 0x33, 0x66, 0x99, 0xcc, 0xff]),
+            _ => return None,
+        };
+        Some(Self::new(colors))
+    }
+
+    /// Parse palette file contents in the given format.
+    pub fn parse(bytes: &[u8], format: PaletteFormat) -> Result<Self, String> {
+        let colors = match format {
+            PaletteFormat::Act => parse_act(bytes)?,
+            text_format => {
+                let text = std::str::from_utf8(bytes)
+                    .map_err(|_| "palette file is not valid UTF-8 text".to_string())?;
+                match text_format {
+                    PaletteFormat::Gpl => parse_gpl(text)?,
+                    PaletteFormat::Pal => parse_pal(text)?,
+                    _ => parse_hex_list(text)?,
+                }
+            }
+        };
+        if colors.is_empty() {
+            return Err("palette file contains no colors".into());
+        }
+        Ok(Self::new(colors))
+    }
+
+    /// Colors in palette order.
+    pub fn colors(&self) -> &[Rgb<u8>] {
+        &self.colors
+    }
+
+    /// Number of colors in the palette.
+    pub fn len(&self) -> usize {
+        self.colors.len()
+    }
+
+    /// Whether the palette has no colors. The constructors reject empty
+    /// lists, so this is `false` for every palette.
+    pub fn is_empty(&self) -> bool {
+        self.colors.is_empty()
+    }
+
+    /// Index of the palette color closest to `color` (Euclidean distance in RGB).
+    pub fn nearest(&self, color: Rgb<u8>) -> usize {
+        self.nearest_by(color, ColorMetric::Rgb)
+    }
+
+    /// Index of the palette color closest to `color` under `metric`.
+    pub fn nearest_by(&self, color: Rgb<u8>, metric: ColorMetric) -> usize {
+        let target = metric.coordinates(color);
+        self.colors
+            .iter()
+            .map(|&c| metric.distance(target, metric.coordinates(c)))
+            .enumerate()
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map_or(0, |(i, _)| i)
+    }
+}
+
+impl ColorMetric {
+    /// Map a color into the space where this metric is a (weighted) Euclidean
+    /// distance.
+    pub fn coordinates(self, color: Rgb<u8>) -> [f32; 3] {
+        let v = color.0.map(|c| c as f32);
+        match self {
+            ColorMetric::Rgb | ColorMetric::Redmean => v,
+            ColorMetric::Oklab => linear_srgb_to_oklab(v.map(|c| srgb_to_linear(c / 255.0))),
+        }
+    }
+
+    /// Squared distance between two colors given as [`Self::coordinates`].
+    pub fn distance(self, a: [f32; 3], b: [f32; 3]) -> f32 {
+        let d = [a[0] - b[0], a[1] - b[1], a[2] - b[2]];
+        match self {
+            ColorMetric::Rgb | ColorMetric::Oklab => d[0] * d[0] + d[1] * d[1] + d[2] * d[2],
+            ColorMetric::Redmean => {
+                let rmean = (a[0] + b[0]) / 2.0;
+                (2.0 + rmean / 256.0) * d[0] * d[0]
+                    + 4.0 * d[1] * d[1]
+                    + (2.0 + (255.0 - rmean) / 256.0) * d[2] * d[2]
+            }
+        }
+    }
+}
+
+impl PaletteFormat {
+    /// Guess the format from a file extension (`gpl`, `act`, `pal`, `hex`, `txt`).
+    pub fn from_extension(ext: &str) -> Option<Self> {
+        match ext.to_ascii_lowercase().as_str() {
+            "gpl" => Some(Self::Gpl),
+            "act" => Some(Self::Act),
+            "pal" => Some(Self::Pal),
+            "hex" | "txt" => Some(Self::Hex),
+            _ => None,
+        }
+    }
+
+    /// Guess the format from file contents.
+    pub fn detect(bytes: &[u8]) -> Self {
+        if bytes.starts_with(b"GIMP Palette") {
+            Self::Gpl
+        } else if bytes.starts_with(b"JASC-PAL") {
+            Self::Pal
+        } else if (bytes.len() == 768 || bytes.len() == 772)
+            && bytes.iter().any(|&b| b == 0 || b >= 0x80)
+        {
+            Self::Act
+        } else {
+            Self::Hex
+        }
+    }
+}
+
+/// Parse a whitespace-separated `R G B` triplet at the start of a line.
+fn parse_triplet(line: &str) -> Result<Rgb<u8>, String> {
+    let values: Vec<u8> = line
+        .split_whitespace()
+        .take(3)
+        .map(|v| {
+            v.parse::<u8>()
+                .map_err(|_| format!("invalid color component: {v}"))
+        })
+        .collect::<Result<_, _>>()?;
+    match values[..] {
+        [r, g, b] => Ok(Rgb([r, g, b])),
+        _ => Err(format!("expected three color components: {line}")),
+    }
+}
+
+fn parse_gpl(text: &str) -> Result<Vec<Rgb<u8>>, String> {
+    let mut lines = text.lines();
+    if lines.next().map(str::trim) != Some("GIMP Palette") {
+        return Err("missing 'GIMP Palette' header".into());
+    }
+    lines
+        .map(str::trim)
+        .filter(|l| !l.is_empty() && !l.starts_with('#'))
+        // Header fields such as `Name:` and `Columns:`.
+        .filter(|l| !l.starts_with(|c: char| c.is_ascii_alphabetic()))
+        .map(parse_triplet)
+        .collect()
+}
+
+fn parse_pal(text: &str) -> Result<Vec<Rgb<u8>>, String> {
+    let mut lines = text.lines().map(str::trim).filter(|l| !l.is_empty());
+    if lines.next() != Some("JASC-PAL") {
+        return Err("missing 'JASC-PAL' header".into());
+    }
+    lines.next().ok_or("missing JASC-PAL version")?;
+    let count: usize = lines
+        .next()
+        .and_then(|l| l.parse().ok())
+        .ok_or("missing JASC-PAL color count")?;
+    let colors: Vec<Rgb<u8>> = lines
+        .take(count)
+        .map(parse_triplet)
+        .collect::<Result<_, _>>()?;
+    if colors.len() != count {
+        return Err(format!("expected {count} colors, found {}", colors.len()));
+    }
+    Ok(colors)
+}
+
+fn parse_act(bytes: &[u8]) -> Result<Vec<Rgb<u8>>, String> {
+    if bytes.len() != 768 && bytes.len() != 772 {
+        return Err("ACT palette must be 768 or 772 bytes long".into());
+    }
+    // The optional trailer holds the used color count (big-endian u16).
+    let count = if bytes.len() == 772 {
+        (u16::from_be_bytes([bytes[768], bytes[769]]) as usize).clamp(1, 256)
+    } else {
+        256
+    };
+    Ok(bytes[..count * 3]
+        .chunks_exact(3)
+        .map(|c| Rgb([c[0], c[1], c[2]]))
+        .collect())
+}
+
+fn parse_hex_list(text: &str) -> Result<Vec<Rgb<u8>>, String> {
+    text.lines()
+        .map(|l| l.split(';').next().unwrap_or(""))
+        .flat_map(|l| l.split([',', ' ', '\t']))
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .map(|v| parse_hex_rgb(v).ok_or_else(|| format!("invalid hex color: {v}")))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_named_palettes() {
+        let sizes = [4, 16, 16, 64, 54, 16, 15, 216];
+        for (name, size) in PALETTE_NAMES.iter().zip(sizes) {
+            let palette = Palette::named(name).unwrap();
+            assert_eq!(palette.len(), size, "{name}");
+        }
+        assert_eq!(Palette::named("PICO-8"), Palette::named("pico8"));
+        assert!(Palette::named("unknown").is_none());
+    }
+
+    #[test]
+    fn test_parse_text_formats() {
+        let expected = Palette::new(vec![Rgb([0, 0, 0]), Rgb([255, 128, 1])]);
+        let gpl =
+            "GIMP Palette\nName: Test\nColumns: 2\n#\n  0   0   0 Black\n255 128   1\tOrange\n";
+        assert_eq!(
+            Palette::parse(gpl.as_bytes(), PaletteFormat::Gpl),
+            Ok(expected.clone())
+        );
+        let pal = "JASC-PAL\r\n0100\r\n2\r\n0 0 0\r\n255 128 1\r\n";
+        assert_eq!(
+            Palette::parse(pal.as_bytes(), PaletteFormat::Pal),
+            Ok(expected.clone())
+        );
+        let hex = "000000\n#ff8001 ; orange\n";
+        assert_eq!(
+            Palette::parse(hex.as_bytes(), PaletteFormat::Hex),
+            Ok(expected.clone())
+        );
+        assert_eq!(PaletteFormat::detect(gpl.as_bytes()), PaletteFormat::Gpl);
+        assert_eq!(PaletteFormat::detect(pal.as_bytes()), PaletteFormat::Pal);
+        assert!(Palette::parse(b"JASC-PAL\n0100\n3\n0 0 0\n", PaletteFormat::Pal).is_err());
+    }
+
+    #[test]
+    fn test_parse_act() {
+        let mut bytes = vec![0u8; 772];
+        bytes[3..6].copy_from_slice(&[255, 128, 1]);
+        bytes[768..770].copy_from_slice(&2u16.to_be_bytes());
+        let palette = Palette::parse(&bytes, PaletteFormat::Act).unwrap();
+        assert_eq!(palette.colors(), &[Rgb([0, 0, 0]), Rgb([255, 128, 1])]);
+        assert_eq!(
+            Palette::parse(&bytes[..768], PaletteFormat::Act)
+                .unwrap()
+                .len(),
+            256
+        );
+        assert_eq!(PaletteFormat::detect(&bytes), PaletteFormat::Act);
+    }
+
+    #[test]
+    fn test_nearest_by_metric() {
+        let palette = Palette::named("gameboy").unwrap();
+        for metric in [ColorMetric::Rgb, ColorMetric::Redmean, ColorMetric::Oklab] {
+            assert_eq!(palette.nearest_by(Rgb([0, 0, 0]), metric), 0);
+            assert_eq!(palette.nearest_by(Rgb([255, 255, 255]), metric), 3);
+        }
+    }
+}
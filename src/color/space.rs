@@ -0,0 +1,459 @@
+//! Conversions between color spaces.
+//!
+//! All functions work on `[f32; 3]` triples. "RGB" means gamma-encoded sRGB
+//! with channels in `0.0..=1.0`; "linear" means the same primaries in linear
+//! light. XYZ and CIE Lab use the D65 white point, with `Y = 1` for white
+//! and Lab `L` in `0..=100`. Hues are in degrees.
+
+/// sRGB (linear) to XYZ, D65 (IEC 61966-2-1).
+const SRGB_TO_XYZ: [[f32; 3]; 3] = [
+    [0.412_456_4, 0.357_576_1, 0.180_437_5],
+    [0.212_672_9, 0.715_152_2, 0.072_175],
+    [0.019_333_9, 0.119_192, 0.950_304_1],
+];
+
+/// XYZ, D65 to sRGB (linear).
+const XYZ_TO_SRGB: [[f32; 3]; 3] = [
+    [3.240_454_2, -1.537_138_5, -0.498_531_4],
+    [-0.969_266, 1.876_010_8, 0.041_556],
+    [0.055_643_4, -0.204_025_9, 1.057_225_2],
+];
+
+/// D65 reference white in XYZ.
+pub const D65_WHITE: [f32; 3] = [0.950_47, 1.0, 1.088_83];
+
+/// CIE Lab constants `216 / 24389` and `24389 / 27`.
+const LAB_EPSILON: f32 = 216.0 / 24389.0;
+const LAB_KAPPA: f32 = 24389.0 / 27.0;
+
+fn mul(m: &[[f32; 3]; 3], v: [f32; 3]) -> [f32; 3] {
+    m.map(|row| row[0] * v[0] + row[1] * v[1] + row[2] * v[2])
+}
+
+/// Convert an sRGB-encoded channel in `0..=1` to linear light.
+pub fn srgb_to_linear(v: f32) -> f32 {
+    if v <= 0.04045 {
+        v / 12.92
+    } else {
+        ((v + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// Convert a linear-light channel in `0..=1` to sRGB encoding.
+pub fn linear_to_srgb(v: f32) -> f32 {
+    if v <= 0.0031308 {
+        v * 12.92
+    } else {
+        1.055 * v.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+/// Convert linear sRGB to CIE XYZ (D65).
+pub fn linear_srgb_to_xyz(rgb: [f32; 3]) -> [f32; 3] {
+    mul(&SRGB_TO_XYZ, rgb)
+}
+
+/// Convert CIE XYZ (D65) to linear sRGB.
+pub fn xyz_to_linear_srgb(xyz: [f32; 3]) -> [f32; 3] {
+    mul(&XYZ_TO_SRGB, xyz)
+}
+
+/// Convert CIE XYZ to CIE Lab relative to the D65 white.
+pub fn xyz_to_lab(xyz: [f32; 3]) -> [f32; 3] {
+    let [fx, fy, fz] = std::array::from_fn(|c| {
+        let t = xyz[c] / D65_WHITE[c];
+        if t > LAB_EPSILON {
+            t.cbrt()
+        } else {
+            (LAB_KAPPA * t + 16.0) / 116.0
+        }
+    });
+    [116.0 * fy - 16.0, 500.0 * (fx - fy), 200.0 * (fy - fz)]
+}
+
+/// Convert CIE Lab (D65 white) back to XYZ.
+pub fn lab_to_xyz([l, a, b]: [f32; 3]) -> [f32; 3] {
+    let fy = (l + 16.0) / 116.0;
+    let fx = fy + a / 500.0;
+    let fz = fy - b / 200.0;
+    let inverse = |f: f32| {
+        let f3 = f * f * f;
+        if f3 > LAB_EPSILON {
+            f3
+        } else {
+            (116.0 * f - 16.0) / LAB_KAPPA
+        }
+    };
+    let y = if l > LAB_KAPPA * LAB_EPSILON {
+        fy * fy * fy
+    } else {
+        l / LAB_KAPPA
+    };
+    [
+        inverse(fx) * D65_WHITE[0],
+        y * D65_WHITE[1],
+        inverse(fz) * D65_WHITE[2],
+    ]
+}
+
+/// Convert a Lab-like triple (CIE Lab or OKLab) to lightness, chroma and hue
+/// in degrees (`0..360`, 0 for neutral colors).
+pub fn lab_to_lch([l, a, b]: [f32; 3]) -> [f32; 3] {
+    let chroma = a.hypot(b);
+    let hue = if chroma < 1e-6 {
+        0.0
+    } else {
+        b.atan2(a).to_degrees().rem_euclid(360.0)
+    };
+    [l, chroma, hue]
+}
+
+/// Convert lightness, chroma and hue in degrees back to a Lab-like triple.
+pub fn lch_to_lab([l, c, h]: [f32; 3]) -> [f32; 3] {
+    let (sin, cos) = h.to_radians().sin_cos();
+    [l, c * cos, c * sin]
+}
+
+/// Convert linear sRGB to OKLab (`L` in `0..=1`, `a`/`b` roughly `-0.4..=0.4`).
+pub fn linear_srgb_to_oklab([r, g, b]: [f32; 3]) -> [f32; 3] {
+    let l = 0.412_221_46 * r + 0.536_332_55 * g + 0.051_445_995 * b;
+    let m = 0.211_903_5 * r + 0.680_699_5 * g + 0.107_396_96 * b;
+    let s = 0.088_302_46 * r + 0.281_718_85 * g + 0.629_978_7 * b;
+    let (l, m, s) = (l.cbrt(), m.cbrt(), s.cbrt());
+    [
+        0.210_454_26 * l + 0.793_617_8 * m - 0.004_072_047 * s,
+        1.977_998_5 * l - 2.428_592_2 * m + 0.450_593_7 * s,
+        0.025_904_037 * l + 0.782_771_77 * m - 0.808_675_77 * s,
+    ]
+}
+
+/// Convert OKLab back to linear sRGB.
+pub fn oklab_to_linear_srgb([l, a, b]: [f32; 3]) -> [f32; 3] {
+    let l_ = l + 0.396_337_78 * a + 0.215_803_76 * b;
+    let m_ = l - 0.105_561_346 * a - 0.063_854_17 * b;
+    let s_ = l - 0.089_484_18 * a - 1.291_485_5 * b;
+    let (l, m, s) = (l_ * l_ * l_, m_ * m_ * m_, s_ * s_ * s_);
+    [
+        4.076_741_7 * l - 3.307_711_6 * m + 0.230_969_94 * s,
+        -1.268_438 * l + 2.609_757_4 * m - 0.341_319_38 * s,
+        -0.004_196_086_3 * l - 0.703_418_6 * m + 1.707_614_7 * s,
+    ]
+}
+
+/// Hue in degrees, the largest channel and the chroma (max - min) of RGB.
+fn hue_max_chroma([r, g, b]: [f32; 3]) -> (f32, f32, f32) {
+    let max = r.max(g).max(b);
+    let chroma = max - r.min(g).min(b);
+    let hue = if chroma == 0.0 {
+        0.0
+    } else if max == r {
+        60.0 * ((g - b) / chroma).rem_euclid(6.0)
+    } else if max == g {
+        60.0 * ((b - r) / chroma + 2.0)
+    } else {
+        60.0 * ((r - g) / chroma + 4.0)
+    };
+    (hue, max, chroma)
+}
+
+/// RGB from a hue in degrees, a chroma and the amount added to every channel.
+fn hue_chroma_to_rgb(h: f32, c: f32, m: f32) -> [f32; 3] {
+    let h = h.rem_euclid(360.0) / 60.0;
+    let x = c * (1.0 - (h % 2.0 - 1.0).abs());
+    let (r, g, b) = match h as u32 {
+        0 => (c, x, 0.0),
+        1 => (x, c, 0.0),
+        2 => (0.0, c, x),
+        3 => (0.0, x, c),
+        4 => (x, 0.0, c),
+        _ => (c, 0.0, x),
+    };
+    [r + m, g + m, b + m]
+}
+
+/// Convert RGB in `0..=1` to HSV (`h` in degrees, `s` and `v` in `0..=1`).
+pub fn rgb_to_hsv(rgb: [f32; 3]) -> [f32; 3] {
+    let (hue, max, chroma) = hue_max_chroma(rgb);
+    let saturation = if max == 0.0 { 0.0 } else { chroma / max };
+    [hue, saturation, max]
+}
+
+/// Convert HSV (`h` in degrees, `s` and `v` in `0..=1`) to RGB in `0..=1`.
+pub fn hsv_to_rgb([h, s, v]: [f32; 3]) -> [f32; 3] {
+    let c = v * s;
+    hue_chroma_to_rgb(h, c, v - c)
+}
+
+/// Convert RGB in `0..=1` to HSL (`h` in degrees, `s` and `l` in `0..=1`).
+pub fn rgb_to_hsl(rgb: [f32; 3]) -> [f32; 3] {
+    let (hue, max, chroma) = hue_max_chroma(rgb);
+    let lightness = max - chroma / 2.0;
+    let saturation = if lightness <= 0.0 || lightness >= 1.0 {
+        0.0
+    } else {
+        chroma / (1.0 - (2.0 * lightness - 1.0).abs())
+    };
+    [hue, saturation, lightness]
+}
+
+/// Convert HSL (`h` in degrees, `s` and `l` in `0..=1`) to RGB in `0..=1`.
+pub fn hsl_to_rgb([h, s, l]: [f32; 3]) -> [f32; 3] {
+    let c = (1.0 - (2.0 * l - 1.0).abs()) * s;
+    hue_chroma_to_rgb(h, c, l - c / 2.0)
+}
+
+/// Luma coefficients of a YCbCr encoding.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum YCbCrMatrix {
+    /// ITU-R BT.601 (SD video, JPEG).
+    #[default]
+    Bt601,
+    /// ITU-R BT.709 (HD video).
+    Bt709,
+}
+
+impl YCbCrMatrix {
+    /// Red and blue luma weights `(Kr, Kb)`.
+    pub fn weights(self) -> (f32, f32) {
+        match self {
+            YCbCrMatrix::Bt601 => (0.299, 0.114),
+            YCbCrMatrix::Bt709 => (0.2126, 0.0722),
+        }
+    }
+}
+
+/// Convert RGB in `0..=1` to full-range YCbCr (`Y` in `0..=1`, `Cb`/`Cr` in
+/// `-0.5..=0.5`; add 0.5 and scale by 255 for JPEG-style bytes).
+pub fn rgb_to_ycbcr([r, g, b]: [f32; 3], matrix: YCbCrMatrix) -> [f32; 3] {
+    let (kr, kb) = matrix.weights();
+    let y = kr * r + (1.0 - kr - kb) * g + kb * b;
+    [
+        y,
+        (b - y) / (2.0 * (1.0 - kb)),
+        (r - y) / (2.0 * (1.0 - kr)),
+    ]
+}
+
+/// Convert full-range YCbCr back to RGB in `0..=1`.
+pub fn ycbcr_to_rgb([y, cb, cr]: [f32; 3], matrix: YCbCrMatrix) -> [f32; 3] {
+    let (kr, kb) = matrix.weights();
+    let r = y + 2.0 * (1.0 - kr) * cr;
+    let b = y + 2.0 * (1.0 - kb) * cb;
+    let g = (y - kr * r - kb * b) / (1.0 - kr - kb);
+    [r, g, b]
+}
+
+/// A color space reachable from sRGB, for converting between any two.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ColorModel {
+    /// Gamma-encoded sRGB in `0..=1`.
+    Srgb,
+    /// Linear-light sRGB in `0..=1`.
+    LinearSrgb,
+    /// CIE XYZ, D65.
+    Xyz,
+    /// CIE Lab, D65.
+    Lab,
+    /// CIE LCh (cylindrical Lab).
+    Lch,
+    /// OKLab.
+    Oklab,
+    /// OKLCh (cylindrical OKLab).
+    Oklch,
+    /// Hue, saturation, value.
+    Hsv,
+    /// Hue, saturation, lightness.
+    Hsl,
+    /// Full-range YCbCr with the given matrix.
+    YCbCr(YCbCrMatrix),
+}
+
+impl ColorModel {
+    /// Convert gamma-encoded sRGB in `0..=1` to this model.
+    pub fn from_srgb(self, rgb: [f32; 3]) -> [f32; 3] {
+        let linear = || rgb.map(srgb_to_linear);
+        match self {
+            ColorModel::Srgb => rgb,
+            ColorModel::LinearSrgb => linear(),
+            ColorModel::Xyz => linear_srgb_to_xyz(linear()),
+            ColorModel::Lab => xyz_to_lab(linear_srgb_to_xyz(linear())),
+            ColorModel::Lch => lab_to_lch(xyz_to_lab(linear_srgb_to_xyz(linear()))),
+            ColorModel::Oklab => linear_srgb_to_oklab(linear()),
+            ColorModel::Oklch => lab_to_lch(linear_srgb_to_oklab(linear())),
+            ColorModel::Hsv => rgb_to_hsv(rgb),
+            ColorModel::Hsl => rgb_to_hsl(rgb),
+            ColorModel::YCbCr(matrix) => rgb_to_ycbcr(rgb, matrix),
+        }
+    }
+
+    /// Convert a value of this model to gamma-encoded sRGB. Out-of-gamut
+    /// colors are not clamped.
+    pub fn to_srgb(self, value: [f32; 3]) -> [f32; 3] {
+        let encode = |linear: [f32; 3]| linear.map(linear_to_srgb);
+        match self {
+            ColorModel::Srgb => value,
+            ColorModel::LinearSrgb => encode(value),
+            ColorModel::Xyz => encode(xyz_to_linear_srgb(value)),
+            ColorModel::Lab => encode(xyz_to_linear_srgb(lab_to_xyz(value))),
+            ColorModel::Lch => encode(xyz_to_linear_srgb(lab_to_xyz(lch_to_lab(value)))),
+            ColorModel::Oklab => encode(oklab_to_linear_srgb(value)),
+            ColorModel::Oklch => encode(oklab_to_linear_srgb(lch_to_lab(value))),
+            ColorModel::Hsv => hsv_to_rgb(value),
+            ColorModel::Hsl => hsl_to_rgb(value),
+            ColorModel::YCbCr(matrix) => ycbcr_to_rgb(value, matrix),
+        }
+    }
+
+    /// Convert `value` from model `from` to model `to` (through sRGB).
+    pub fn convert(value: [f32; 3], from: ColorModel, to: ColorModel) -> [f32; 3] {
+        if from == to {
+            return value;
+        }
+        to.from_srgb(from.to_srgb(value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MODELS: [ColorModel; 11] = [
+        ColorModel::Srgb,
+        ColorModel::LinearSrgb,
+        ColorModel::Xyz,
+        ColorModel::Lab,
+        ColorModel::Lch,
+        ColorModel::Oklab,
+        ColorModel::Oklch,
+        ColorModel::Hsv,
+        ColorModel::Hsl,
+        ColorModel::YCbCr(YCbCrMatrix::Bt601),
+        ColorModel::YCbCr(YCbCrMatrix::Bt709),
+    ];
+
+    fn assert_close(actual: [f32; 3], expected: [f32; 3], tolerance: f32) {
+        for c in 0..3 {
+            assert!(
+                (actual[c] - expected[c]).abs() <= tolerance,
+                "{:?} != {:?}",
+                actual,
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn test_reference_values() {
+        let red = [1.0, 0.0, 0.0];
+        // Bruce Lindbloom's calculator, sRGB / D65
+        assert_close(
+            ColorModel::Xyz.from_srgb(red),
+            [0.412_456_4, 0.212_672_9, 0.019_333_9],
+            1e-6,
+        );
+        assert_close(
+            ColorModel::Lab.from_srgb(red),
+            [53.2408, 80.0925, 67.2032],
+            1e-2,
+        );
+        assert_close(
+            ColorModel::Lch.from_srgb(red),
+            [53.2408, 104.5518, 39.9990],
+            1e-2,
+        );
+        assert_close(ColorModel::Lab.from_srgb([1.0; 3]), [100.0, 0.0, 0.0], 1e-3);
+        assert_close(
+            ColorModel::Lab.from_srgb([0.5; 3]),
+            [53.3890, 0.0, 0.0],
+            1e-2,
+        );
+        // Björn Ottosson's OKLab reference for sRGB red
+        assert_close(
+            ColorModel::Oklab.from_srgb(red),
+            [0.627_955, 0.224_863, 0.125_846],
+            1e-4,
+        );
+        assert_close(
+            ColorModel::Oklch.from_srgb(red),
+            [0.627_955, 0.257_683, 29.2339],
+            1e-3,
+        );
+        // HSV/HSL of orange and a desaturated teal
+        assert_close(
+            ColorModel::Hsv.from_srgb([1.0, 0.5, 0.0]),
+            [30.0, 1.0, 1.0],
+            1e-5,
+        );
+        assert_close(
+            ColorModel::Hsl.from_srgb([1.0, 0.5, 0.0]),
+            [30.0, 1.0, 0.5],
+            1e-5,
+        );
+        assert_close(
+            ColorModel::Hsl.from_srgb([0.25, 0.75, 0.75]),
+            [180.0, 0.5, 0.5],
+            1e-5,
+        );
+        // JPEG red: Y = 76.245, Cb = 84.97, Cr = 255.5 as bytes
+        assert_close(
+            ColorModel::YCbCr(YCbCrMatrix::Bt601).from_srgb(red),
+            [0.299, -0.168_736, 0.5],
+            1e-5,
+        );
+        assert_close(
+            ColorModel::YCbCr(YCbCrMatrix::Bt709).from_srgb(red),
+            [0.2126, -0.114_572, 0.5],
+            1e-5,
+        );
+    }
+
+    #[test]
+    fn test_round_trips() {
+        let mut samples = vec![[0.0; 3], [1.0; 3], [0.5; 3], [0.02, 0.01, 0.03]];
+        for r in 0..5 {
+            for g in 0..5 {
+                for b in 0..5 {
+                    samples.push([r as f32 / 4.0, g as f32 / 4.0, b as f32 / 4.0]);
+                }
+            }
+        }
+        for model in MODELS {
+            for &rgb in &samples {
+                let back = model.to_srgb(model.from_srgb(rgb));
+                assert_close(back, rgb, 1e-4);
+            }
+        }
+        let v = linear_to_srgb(srgb_to_linear(0.3));
+        assert!((v - 0.3).abs() < 1e-6);
+        let lab = [50.0, 20.0, -30.0];
+        let converted = ColorModel::convert(lab, ColorModel::Lab, ColorModel::Oklch);
+        assert_close(
+            ColorModel::convert(converted, ColorModel::Oklch, ColorModel::Lab),
+            lab,
+            1e-2,
+        );
+    }
+
+    #[test]
+    fn test_hsv_to_rgb() {
+        assert_eq!(hsv_to_rgb([0.0, 1.0, 1.0]), [1.0, 0.0, 0.0]);
+        assert_eq!(hsv_to_rgb([120.0, 1.0, 1.0]), [0.0, 1.0, 0.0]);
+        assert_eq!(hsv_to_rgb([-120.0, 1.0, 0.5]), [0.0, 0.0, 0.5]);
+        assert_eq!(hsv_to_rgb([300.0, 0.0, 0.25]), [0.25, 0.25, 0.25]);
+    }
+
+    #[test]
+    fn test_oklab_reference_and_round_trip() {
+        // Reference: sRGB white is L = 1, a = b = 0 in OKLab
+        let white = linear_srgb_to_oklab([1.0, 1.0, 1.0]);
+        assert!((white[0] - 1.0).abs() < 1e-3);
+        assert!(white[1].abs() < 1e-3 && white[2].abs() < 1e-3);
+        let rgb = [0.2, 0.5, 0.8];
+        let back = oklab_to_linear_srgb(linear_srgb_to_oklab(rgb));
+        for n in 0..3 {
+            assert!((back[n] - rgb[n]).abs() < 1e-4);
+            let v = rgb[n];
+            assert!((linear_to_srgb(srgb_to_linear(v)) - v).abs() < 1e-5);
+        }
+    }
+}
@@ -3,7 +3,10 @@
 //! Each filter implements the generic [`crate::filters::Filter`] trait with concrete input and
 //! output image types. Filters consume an input by reference and return a new
 //! image buffer without mutating the original.
-use crate::color::{SRGB_LUMA_B, SRGB_LUMA_G, SRGB_LUMA_R};
+use crate::color::{
+    ColorLut, ColorMetric, Colormap, LutInterpolation, Palette, SRGB_LUMA_B, SRGB_LUMA_G,
+    SRGB_LUMA_R,
+};
 use image::{ImageBuffer, Luma, Rgb};
 
 /// Generic trait for applying image filters.
@@ -15,12 +18,51 @@ pub trait Filter<I, O> {
     fn apply(&self, input: &I) -> O;
 }
 
+mod blur;
+mod canny;
+mod color_dither;
+mod color_lut;
+mod colormap;
 mod convolution;
+mod convolve;
+mod curves;
 mod dither;
+mod edge;
+mod equalize;
 mod gamma;
+mod gradient;
 mod grayscale;
 mod invert;
+mod laplacian;
+mod mosaic;
+mod ordered;
+mod palette_map;
+mod quantize;
+mod rng;
 mod sobel;
+mod threshold;
+mod tone;
+mod toning;
+
+pub use blur::BlurMethod;
+pub use canny::CannyThresholds;
+pub use color_dither::{DitherSpace, DitherTarget};
+pub use convolution::{BorderMode, Kernel, MagnitudeScale, Normalization};
+pub use convolve::ConvolveChannels;
+pub use curves::{Curve, CurveInterpolation};
+pub use dither::DitherMethod;
+pub use edge::EdgeOperator;
+pub use equalize::EqualizeMethod;
+pub use gradient::{
+    raw_u16_scale, ChannelCombine, EdgeSpace, Gradient, GradientOperator, RAW_U16_OFFSET,
+};
+pub use laplacian::SecondDerivative;
+pub use mosaic::BlockColor;
+pub use ordered::ThresholdMap;
+pub use quantize::QuantizeMethod;
+pub use threshold::ThresholdMethod;
+pub use tone::{Levels, ToneCurve8};
+pub use toning::GradientMap;
 
 /// Convert an RGB image to grayscale using weighted channel luminance.
 pub struct GrayscaleFilter {
@@ -42,15 +84,412 @@ impl Filter<ImageBuffer<Rgb<u8>, Vec<u8>>, ImageBuffer<Luma<u8>, Vec<u8>>> for G
     }
 }
 
-/// Ordered-dither (Bayer matrix) halftone on luminance; outputs black and white.
-pub struct HalftoneFilter;
+/// Halftone on luminance using ordered or error-diffusion dithering; outputs
+/// black and white.
+pub struct HalftoneFilter {
+    /// Dithering algorithm (default: ordered dither).
+    pub method: DitherMethod,
+    /// Threshold matrix used by ordered dithering (default: 4x4 Bayer).
+    pub matrix: ThresholdMap,
+    /// Alternate scan direction on every other row (error diffusion only).
+    pub serpentine: bool,
+    /// Fraction of the quantization error to propagate (error diffusion only).
+    pub strength: f32,
+}
+
+impl HalftoneFilter {
+    /// Create a new halftone filter using the given dithering method.
+    pub fn new(method: DitherMethod) -> Self {
+        Self {
+            method,
+            matrix: ThresholdMap::default(),
+            serpentine: false,
+            strength: 1.0,
+        }
+    }
+
+    /// Use the given threshold matrix for ordered dithering.
+    pub fn with_matrix(mut self, matrix: ThresholdMap) -> Self {
+        self.matrix = matrix;
+        self
+    }
+
+    /// Enable or disable serpentine (boustrophedon) scanning.
+    pub fn with_serpentine(mut self, serpentine: bool) -> Self {
+        self.serpentine = serpentine;
+        self
+    }
+
+    /// Set the error-bleed strength (1.0 propagates the full error).
+    pub fn with_strength(mut self, strength: f32) -> Self {
+        self.strength = strength;
+        self
+    }
+}
+
+impl Default for HalftoneFilter {
+    fn default() -> Self {
+        Self::new(DitherMethod::Ordered)
+    }
+}
 
 impl Filter<ImageBuffer<Rgb<u8>, Vec<u8>>, ImageBuffer<Luma<u8>, Vec<u8>>> for HalftoneFilter {
     fn apply(&self, img: &ImageBuffer<Rgb<u8>, Vec<u8>>) -> ImageBuffer<Luma<u8>, Vec<u8>> {
         // Convert to grayscale before applying halftoning
         let gray: ImageBuffer<Luma<u8>, Vec<u8>> =
             grayscale::grayscale(img, SRGB_LUMA_R, SRGB_LUMA_G, SRGB_LUMA_B);
-        dither::halftoning(&gray)
+        dither::dither(
+            &gray,
+            self.method,
+            &self.matrix,
+            self.serpentine,
+            self.strength,
+        )
+    }
+}
+
+/// Binarize the luminance with a global or adaptive threshold; outputs black
+/// and white.
+pub struct ThresholdFilter {
+    /// How the threshold is chosen.
+    pub method: ThresholdMethod,
+    /// Output white for dark pixels and black for bright ones.
+    pub invert: bool,
+    /// How pixels outside the image are synthesized (adaptive methods).
+    pub border: BorderMode,
+}
+
+impl ThresholdFilter {
+    /// Create a new threshold filter with the default (reflect-101) border.
+    pub fn new(method: ThresholdMethod) -> Self {
+        Self {
+            method,
+            invert: false,
+            border: BorderMode::default(),
+        }
+    }
+
+    /// Swap black and white in the output.
+    pub fn with_invert(mut self, invert: bool) -> Self {
+        self.invert = invert;
+        self
+    }
+
+    /// Use a different border mode.
+    pub fn with_border(mut self, border: BorderMode) -> Self {
+        self.border = border;
+        self
+    }
+}
+
+impl Default for ThresholdFilter {
+    fn default() -> Self {
+        Self::new(ThresholdMethod::Otsu)
+    }
+}
+
+impl Filter<ImageBuffer<Rgb<u8>, Vec<u8>>, ImageBuffer<Luma<u8>, Vec<u8>>> for ThresholdFilter {
+    fn apply(&self, img: &ImageBuffer<Rgb<u8>, Vec<u8>>) -> ImageBuffer<Luma<u8>, Vec<u8>> {
+        let gray: ImageBuffer<Luma<u8>, Vec<u8>> =
+            grayscale::grayscale(img, SRGB_LUMA_R, SRGB_LUMA_G, SRGB_LUMA_B);
+        threshold::threshold(&gray, self.method, self.invert, self.border)
+    }
+}
+
+/// Multi-level or palette dithering for RGB images; outputs RGB.
+pub struct DitherFilter {
+    /// Gray levels, per-channel levels or palette to quantize to.
+    pub target: DitherTarget,
+    /// Working space for palette matching and error diffusion.
+    pub space: DitherSpace,
+    /// Dithering algorithm (default: ordered dither).
+    pub method: DitherMethod,
+    /// Threshold matrix used by ordered dithering (default: 4x4 Bayer).
+    pub matrix: ThresholdMap,
+    /// Alternate scan direction on every other row (error diffusion only).
+    pub serpentine: bool,
+    /// Fraction of the quantization error or ordered noise to apply.
+    pub strength: f32,
+}
+
+impl DitherFilter {
+    /// Create a new dither filter quantizing to the given target.
+    pub fn new(target: DitherTarget) -> Self {
+        Self {
+            target,
+            space: DitherSpace::Rgb,
+            method: DitherMethod::Ordered,
+            matrix: ThresholdMap::default(),
+            serpentine: false,
+            strength: 1.0,
+        }
+    }
+
+    /// Dither to `levels` evenly spaced gray levels.
+    pub fn gray(levels: u32) -> Self {
+        Self::new(DitherTarget::Gray(levels))
+    }
+
+    /// Dither to the nearest colors of `palette`.
+    pub fn palette(palette: Palette) -> Self {
+        Self::new(DitherTarget::Palette(palette))
+    }
+
+    /// Set the working color space.
+    pub fn with_space(mut self, space: DitherSpace) -> Self {
+        self.space = space;
+        self
+    }
+
+    /// Set the dithering algorithm.
+    pub fn with_method(mut self, method: DitherMethod) -> Self {
+        self.method = method;
+        self
+    }
+
+    /// Use the given threshold matrix for ordered dithering.
+    pub fn with_matrix(mut self, matrix: ThresholdMap) -> Self {
+        self.matrix = matrix;
+        self
+    }
+
+    /// Enable or disable serpentine (boustrophedon) scanning.
+    pub fn with_serpentine(mut self, serpentine: bool) -> Self {
+        self.serpentine = serpentine;
+        self
+    }
+
+    /// Set the error-bleed / noise strength (1.0 is the full amount).
+    pub fn with_strength(mut self, strength: f32) -> Self {
+        self.strength = strength;
+        self
+    }
+}
+
+impl Filter<ImageBuffer<Rgb<u8>, Vec<u8>>, ImageBuffer<Rgb<u8>, Vec<u8>>> for DitherFilter {
+    fn apply(&self, img: &ImageBuffer<Rgb<u8>, Vec<u8>>) -> ImageBuffer<Rgb<u8>, Vec<u8>> {
+        color_dither::color_dither(
+            img,
+            &self.target,
+            self.space,
+            self.method,
+            &self.matrix,
+            self.serpentine,
+            self.strength,
+        )
+    }
+}
+
+/// Reduce an RGB image to an optimized palette of at most `colors` entries.
+pub struct QuantizeFilter {
+    /// Maximum number of palette colors (>= 1).
+    pub colors: usize,
+    /// Palette construction algorithm.
+    pub method: QuantizeMethod,
+    /// Random seed for k-means initialization.
+    pub seed: u64,
+}
+
+/// Result of palette quantization.
+pub struct Quantized {
+    /// The generated palette.
+    pub palette: Palette,
+    /// The input image with every pixel replaced by its nearest palette color.
+    pub image: ImageBuffer<Rgb<u8>, Vec<u8>>,
+}
+
+impl QuantizeFilter {
+    /// Create a new quantize filter producing up to `colors` colors.
+    pub fn new(colors: usize, method: QuantizeMethod) -> Self {
+        Self {
+            colors,
+            method,
+            seed: 0,
+        }
+    }
+
+    /// Set the k-means seed; the same seed always yields the same palette.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Build the palette for `img` without remapping it.
+    pub fn palette(&self, img: &ImageBuffer<Rgb<u8>, Vec<u8>>) -> Palette {
+        quantize::build_palette(img, self.colors, self.method, self.seed)
+    }
+
+    /// Build the palette and remap the image to it.
+    pub fn quantize(&self, img: &ImageBuffer<Rgb<u8>, Vec<u8>>) -> Quantized {
+        let palette = self.palette(img);
+        let image = palette_map::palette_map(img, &palette, ColorMetric::Rgb);
+        Quantized { palette, image }
+    }
+}
+
+impl Filter<ImageBuffer<Rgb<u8>, Vec<u8>>, ImageBuffer<Rgb<u8>, Vec<u8>>> for QuantizeFilter {
+    fn apply(&self, img: &ImageBuffer<Rgb<u8>, Vec<u8>>) -> ImageBuffer<Rgb<u8>, Vec<u8>> {
+        self.quantize(img).image
+    }
+}
+
+/// Snap every pixel to the nearest color of a fixed palette (no dithering).
+pub struct PaletteMapFilter {
+    /// Target palette.
+    pub palette: Palette,
+    /// Distance metric used to find the nearest color.
+    pub metric: ColorMetric,
+}
+
+impl PaletteMapFilter {
+    /// Create a new palette-mapping filter with the given metric.
+    pub fn new(palette: Palette, metric: ColorMetric) -> Self {
+        Self { palette, metric }
+    }
+}
+
+impl Filter<ImageBuffer<Rgb<u8>, Vec<u8>>, ImageBuffer<Rgb<u8>, Vec<u8>>> for PaletteMapFilter {
+    fn apply(&self, img: &ImageBuffer<Rgb<u8>, Vec<u8>>) -> ImageBuffer<Rgb<u8>, Vec<u8>> {
+        palette_map::palette_map(img, &self.palette, self.metric)
+    }
+}
+
+/// Gaussian or box blur for RGB images, applied per channel with separable passes.
+pub struct BlurFilter {
+    /// Blur algorithm and size.
+    pub method: BlurMethod,
+    /// How pixels outside the image are synthesized.
+    pub border: BorderMode,
+}
+
+impl BlurFilter {
+    /// Create a new blur filter with the given method and the default
+    /// (reflect-101) border.
+    pub fn new(method: BlurMethod) -> Self {
+        Self {
+            method,
+            border: BorderMode::default(),
+        }
+    }
+
+    /// Gaussian blur with standard deviation `sigma`.
+    pub fn gaussian(sigma: f32) -> Self {
+        Self::new(BlurMethod::Gaussian { sigma })
+    }
+
+    /// Box blur with the given radius.
+    pub fn box_blur(radius: u32) -> Self {
+        Self::new(BlurMethod::Box { radius })
+    }
+
+    /// Use a different border mode.
+    pub fn with_border(mut self, border: BorderMode) -> Self {
+        self.border = border;
+        self
+    }
+}
+
+impl Filter<ImageBuffer<Rgb<u8>, Vec<u8>>, ImageBuffer<Rgb<u8>, Vec<u8>>> for BlurFilter {
+    fn apply(&self, img: &ImageBuffer<Rgb<u8>, Vec<u8>>) -> ImageBuffer<Rgb<u8>, Vec<u8>> {
+        blur::blur(img, self.method, self.border)
+    }
+}
+
+/// Convolution with a user-supplied kernel for RGB images.
+pub struct ConvolveFilter {
+    /// Kernel weights and anchor.
+    pub kernel: Kernel,
+    /// Value the weighted sum is divided by (non-zero).
+    pub divisor: f32,
+    /// Offset added after dividing.
+    pub bias: f32,
+    /// Convolve every RGB channel or luminance only.
+    pub channels: ConvolveChannels,
+    /// How pixels outside the image are synthesized.
+    pub border: BorderMode,
+}
+
+impl ConvolveFilter {
+    /// Create a new filter applying `kernel` to every RGB channel.
+    ///
+    /// The divisor defaults to the kernel sum (or 1 if the weights sum to zero),
+    /// so blur kernels keep the overall brightness.
+    pub fn new(kernel: Kernel) -> Self {
+        let sum = kernel.sum();
+        Self {
+            divisor: if sum.abs() > f32::EPSILON { sum } else { 1.0 },
+            kernel,
+            bias: 0.0,
+            channels: ConvolveChannels::Rgb,
+            border: BorderMode::default(),
+        }
+    }
+
+    /// Load a filter from JSON such as
+    /// `{"kernel": [[0, -1, 0], [-1, 5, -1], [0, -1, 0]], "divisor": 1, "bias": 0, "anchor": [1, 1]}`.
+    ///
+    /// The kernel and anchor are read by [`Kernel::from_json`]; `divisor` and
+    /// `bias` are optional and keep the defaults of [`ConvolveFilter::new`].
+    pub fn from_json(text: &str) -> Result<Self, String> {
+        let value: serde_json::Value =
+            serde_json::from_str(text).map_err(|e| format!("invalid kernel JSON: {e}"))?;
+        let mut filter = Self::new(Kernel::from_json(&value)?);
+        let number = |key: &str| -> Result<Option<f32>, String> {
+            match value.get(key) {
+                None => Ok(None),
+                Some(v) => v
+                    .as_f64()
+                    .map(|v| Some(v as f32))
+                    .ok_or(format!("{key} must be a number")),
+            }
+        };
+        if let Some(divisor) = number("divisor")? {
+            if divisor == 0.0 {
+                return Err("divisor must not be 0".into());
+            }
+            filter.divisor = divisor;
+        }
+        if let Some(bias) = number("bias")? {
+            filter.bias = bias;
+        }
+        Ok(filter)
+    }
+
+    /// Use an explicit divisor.
+    pub fn with_divisor(mut self, divisor: f32) -> Self {
+        self.divisor = divisor;
+        self
+    }
+
+    /// Add a constant offset to every output value.
+    pub fn with_bias(mut self, bias: f32) -> Self {
+        self.bias = bias;
+        self
+    }
+
+    /// Choose which channels are convolved.
+    pub fn with_channels(mut self, channels: ConvolveChannels) -> Self {
+        self.channels = channels;
+        self
+    }
+
+    /// Use a different border mode.
+    pub fn with_border(mut self, border: BorderMode) -> Self {
+        self.border = border;
+        self
+    }
+}
+
+impl Filter<ImageBuffer<Rgb<u8>, Vec<u8>>, ImageBuffer<Rgb<u8>, Vec<u8>>> for ConvolveFilter {
+    fn apply(&self, img: &ImageBuffer<Rgb<u8>, Vec<u8>>) -> ImageBuffer<Rgb<u8>, Vec<u8>> {
+        convolve::convolve(
+            img,
+            &self.kernel,
+            self.divisor,
+            self.bias,
+            self.channels,
+            self.border,
+        )
     }
 }
 
@@ -72,6 +511,360 @@ impl Filter<ImageBuffer<Rgb<u8>, Vec<u8>>, ImageBuffer<Rgb<u8>, Vec<u8>>> for Ga
     }
 }
 
+/// Levels adjustment (black/white points, midtone gamma, output range) for
+/// RGB images, with optional auto-levels.
+pub struct LevelsFilter {
+    /// Points applied to every channel; with `auto_clip` set, `black` and
+    /// `white` are replaced by values measured from the image.
+    pub levels: Levels,
+    /// Percentage of pixels clipped at each end by auto-levels.
+    pub auto_clip: Option<f64>,
+    /// Measure auto-levels per channel (removes color casts).
+    pub per_channel: bool,
+}
+
+impl LevelsFilter {
+    /// Create a new levels filter.
+    pub fn new(levels: Levels) -> Self {
+        Self {
+            levels,
+            auto_clip: None,
+            per_channel: false,
+        }
+    }
+
+    /// Derive the black and white points from the histogram, clipping
+    /// `clip` percent of the pixels at each end.
+    pub fn with_auto(mut self, clip: f64) -> Self {
+        self.auto_clip = Some(clip);
+        self
+    }
+
+    /// Measure auto-levels on each channel separately.
+    pub fn with_per_channel(mut self, per_channel: bool) -> Self {
+        self.per_channel = per_channel;
+        self
+    }
+}
+
+impl Default for LevelsFilter {
+    fn default() -> Self {
+        Self::new(Levels::default())
+    }
+}
+
+impl Filter<ImageBuffer<Rgb<u8>, Vec<u8>>, ImageBuffer<Rgb<u8>, Vec<u8>>> for LevelsFilter {
+    fn apply(&self, img: &ImageBuffer<Rgb<u8>, Vec<u8>>) -> ImageBuffer<Rgb<u8>, Vec<u8>> {
+        let luts = match self.auto_clip {
+            None => [self.levels.lut(); 3],
+            Some(clip) => tone::auto_levels(img, clip, self.per_channel).map(|(black, white)| {
+                Levels {
+                    black,
+                    white,
+                    ..self.levels
+                }
+                .lut()
+            }),
+        };
+        tone::apply_luts(img, &luts)
+    }
+}
+
+/// Brightness and contrast adjustment for RGB images.
+pub struct BrightnessContrastFilter {
+    /// Gray levels added to every channel (may be negative).
+    pub brightness: f64,
+    /// Multiplier for the distance from mid-gray (1.0 = unchanged, >= 0.0).
+    pub contrast: f64,
+}
+
+impl BrightnessContrastFilter {
+    /// Create a new brightness/contrast filter.
+    pub fn new(brightness: f64, contrast: f64) -> Self {
+        Self {
+            brightness,
+            contrast,
+        }
+    }
+}
+
+impl Filter<ImageBuffer<Rgb<u8>, Vec<u8>>, ImageBuffer<Rgb<u8>, Vec<u8>>>
+    for BrightnessContrastFilter
+{
+    fn apply(&self, img: &ImageBuffer<Rgb<u8>, Vec<u8>>) -> ImageBuffer<Rgb<u8>, Vec<u8>> {
+        let lut = tone::brightness_contrast_lut(self.brightness, self.contrast);
+        tone::apply_luts(img, &[lut; 3])
+    }
+}
+
+/// Exposure adjustment in photographic stops, applied in linear light.
+pub struct ExposureFilter {
+    /// Stops to add (each stop doubles the light; negative values darken).
+    pub stops: f64,
+}
+
+impl ExposureFilter {
+    /// Create a new exposure filter.
+    pub fn new(stops: f64) -> Self {
+        Self { stops }
+    }
+}
+
+impl Filter<ImageBuffer<Rgb<u8>, Vec<u8>>, ImageBuffer<Rgb<u8>, Vec<u8>>> for ExposureFilter {
+    fn apply(&self, img: &ImageBuffer<Rgb<u8>, Vec<u8>>) -> ImageBuffer<Rgb<u8>, Vec<u8>> {
+        tone::apply_luts(img, &[tone::exposure_lut(self.stops); 3])
+    }
+}
+
+/// Tone curves for RGB images: a master curve plus one curve per channel.
+///
+/// Each channel goes through its own curve first and then through the master
+/// curve. [`GammaFilter`] is the special case of a single power-law curve.
+pub struct CurvesFilter {
+    /// Curve applied to all channels.
+    pub master: Curve,
+    pub red: Curve,
+    pub green: Curve,
+    pub blue: Curve,
+    /// Interpolation between control points.
+    pub interpolation: CurveInterpolation,
+}
+
+impl CurvesFilter {
+    /// Create a new curves filter with a master curve and identity channel curves.
+    pub fn new(master: Curve) -> Self {
+        Self {
+            master,
+            red: Curve::identity(),
+            green: Curve::identity(),
+            blue: Curve::identity(),
+            interpolation: CurveInterpolation::default(),
+        }
+    }
+
+    /// Set the red channel curve.
+    pub fn with_red(mut self, curve: Curve) -> Self {
+        self.red = curve;
+        self
+    }
+
+    /// Set the green channel curve.
+    pub fn with_green(mut self, curve: Curve) -> Self {
+        self.green = curve;
+        self
+    }
+
+    /// Set the blue channel curve.
+    pub fn with_blue(mut self, curve: Curve) -> Self {
+        self.blue = curve;
+        self
+    }
+
+    /// Set the interpolation between control points.
+    pub fn with_interpolation(mut self, interpolation: CurveInterpolation) -> Self {
+        self.interpolation = interpolation;
+        self
+    }
+
+    /// Load curves from JSON such as
+    /// `{"interpolation": "catmull-rom", "master": [[0, 0], [128, 150], [255, 255]], "blue": "0,20 255,235"}`.
+    ///
+    /// Each curve is parsed by [`Curve::from_json`]; curves missing from the
+    /// object are identity and the interpolation defaults to monotone.
+    pub fn from_json(text: &str) -> Result<Self, String> {
+        let value: serde_json::Value =
+            serde_json::from_str(text).map_err(|e| format!("invalid curves JSON: {e}"))?;
+        if !value.is_object() {
+            return Err("curves JSON must be an object".into());
+        }
+        let curve = |key: &str| -> Result<Curve, String> {
+            value.get(key).map_or(Ok(Curve::identity()), |v| {
+                Curve::from_json(v).map_err(|e| format!("{key}: {e}"))
+            })
+        };
+        let interpolation = match value.get("interpolation").map(|v| v.as_str()) {
+            None | Some(Some("monotone")) => CurveInterpolation::MonotoneCubic,
+            Some(Some("catmull-rom")) => CurveInterpolation::CatmullRom,
+            Some(_) => return Err("interpolation must be \"monotone\" or \"catmull-rom\"".into()),
+        };
+        Ok(Self::new(curve("master")?)
+            .with_red(curve("red")?)
+            .with_green(curve("green")?)
+            .with_blue(curve("blue")?)
+            .with_interpolation(interpolation))
+    }
+
+    /// The compiled per-channel lookup tables (R, G, B).
+    pub fn luts(&self) -> [ToneCurve8; 3] {
+        let master = self.master.lut(self.interpolation);
+        [&self.red, &self.green, &self.blue].map(|curve| {
+            let channel = curve.lut(self.interpolation);
+            channel.map(|v| master[v as usize])
+        })
+    }
+}
+
+impl Default for CurvesFilter {
+    fn default() -> Self {
+        Self::new(Curve::identity())
+    }
+}
+
+impl Filter<ImageBuffer<Rgb<u8>, Vec<u8>>, ImageBuffer<Rgb<u8>, Vec<u8>>> for CurvesFilter {
+    fn apply(&self, img: &ImageBuffer<Rgb<u8>, Vec<u8>>) -> ImageBuffer<Rgb<u8>, Vec<u8>> {
+        tone::apply_luts(img, &self.luts())
+    }
+}
+
+/// Grade RGB images through a `.cube` or HaldCLUT color lookup table.
+pub struct LutFilter {
+    /// The lookup table.
+    pub lut: ColorLut,
+    /// How the 3D lattice is interpolated.
+    pub interpolation: LutInterpolation,
+}
+
+impl LutFilter {
+    /// Create a new LUT filter with trilinear interpolation.
+    pub fn new(lut: ColorLut) -> Self {
+        Self {
+            lut,
+            interpolation: LutInterpolation::default(),
+        }
+    }
+
+    /// Set the lattice interpolation.
+    pub fn with_interpolation(mut self, interpolation: LutInterpolation) -> Self {
+        self.interpolation = interpolation;
+        self
+    }
+}
+
+impl Filter<ImageBuffer<Rgb<u8>, Vec<u8>>, ImageBuffer<Rgb<u8>, Vec<u8>>> for LutFilter {
+    fn apply(&self, img: &ImageBuffer<Rgb<u8>, Vec<u8>>) -> ImageBuffer<Rgb<u8>, Vec<u8>> {
+        color_lut::apply_color_lut(img, &self.lut, self.interpolation)
+    }
+}
+
+/// Sepia toning with the classic sepia matrix.
+pub struct SepiaFilter {
+    /// Blend with the original: 0.0 keeps the colors, 1.0 is full sepia.
+    pub strength: f64,
+}
+
+impl SepiaFilter {
+    /// Create a new sepia filter.
+    pub fn new(strength: f64) -> Self {
+        Self { strength }
+    }
+}
+
+impl Default for SepiaFilter {
+    fn default() -> Self {
+        Self::new(1.0)
+    }
+}
+
+impl Filter<ImageBuffer<Rgb<u8>, Vec<u8>>, ImageBuffer<Rgb<u8>, Vec<u8>>> for SepiaFilter {
+    fn apply(&self, img: &ImageBuffer<Rgb<u8>, Vec<u8>>) -> ImageBuffer<Rgb<u8>, Vec<u8>> {
+        toning::sepia(img, self.strength as f32)
+    }
+}
+
+/// Color the luminance through a gradient of color stops; duotone and
+/// tritone are gradients of two or three evenly spaced colors.
+pub struct GradientMapFilter {
+    /// Colors along the luminance axis.
+    pub gradient: GradientMap,
+    /// Luminance weights (default: Rec. 709, as in [`GrayscaleFilter`]).
+    pub luma: GrayscaleFilter,
+}
+
+impl GradientMapFilter {
+    /// Create a new gradient map filter.
+    pub fn new(gradient: GradientMap) -> Self {
+        Self {
+            gradient,
+            luma: GrayscaleFilter::new(SRGB_LUMA_R, SRGB_LUMA_G, SRGB_LUMA_B),
+        }
+    }
+
+    /// Duotone: shadows blend into highlights.
+    pub fn duotone(shadows: Rgb<u8>, highlights: Rgb<u8>) -> Self {
+        Self::new(GradientMap::evenly(&[shadows, highlights]))
+    }
+
+    /// Tritone: shadows, midtones and highlights.
+    pub fn tritone(shadows: Rgb<u8>, midtones: Rgb<u8>, highlights: Rgb<u8>) -> Self {
+        Self::new(GradientMap::evenly(&[shadows, midtones, highlights]))
+    }
+
+    /// Use different luminance weights.
+    pub fn with_luma(mut self, luma: GrayscaleFilter) -> Self {
+        self.luma = luma;
+        self
+    }
+}
+
+impl Filter<ImageBuffer<Rgb<u8>, Vec<u8>>, ImageBuffer<Rgb<u8>, Vec<u8>>> for GradientMapFilter {
+    fn apply(&self, img: &ImageBuffer<Rgb<u8>, Vec<u8>>) -> ImageBuffer<Rgb<u8>, Vec<u8>> {
+        toning::gradient_map(&self.luma.apply(img), &self.gradient)
+    }
+}
+
+/// Pseudo-color a grayscale image (edge magnitudes, depth maps, heat maps)
+/// through a [`Colormap`] such as viridis or turbo.
+pub struct ColormapFilter {
+    /// Colors for gray levels 0..=255.
+    pub colormap: Colormap,
+}
+
+impl ColormapFilter {
+    /// Create a new colormap filter.
+    pub fn new(colormap: Colormap) -> Self {
+        Self { colormap }
+    }
+}
+
+impl Filter<ImageBuffer<Luma<u8>, Vec<u8>>, ImageBuffer<Rgb<u8>, Vec<u8>>> for ColormapFilter {
+    fn apply(&self, img: &ImageBuffer<Luma<u8>, Vec<u8>>) -> ImageBuffer<Rgb<u8>, Vec<u8>> {
+        colormap::apply_colormap(img, &self.colormap)
+    }
+}
+
+/// Histogram equalization (global or CLAHE) of the luminance.
+///
+/// RGB images keep their hue; grayscale images are equalized directly.
+pub struct EqualizeFilter {
+    /// Global equalization or CLAHE.
+    pub method: EqualizeMethod,
+}
+
+impl EqualizeFilter {
+    /// Create a new equalization filter.
+    pub fn new(method: EqualizeMethod) -> Self {
+        Self { method }
+    }
+}
+
+impl Default for EqualizeFilter {
+    fn default() -> Self {
+        Self::new(EqualizeMethod::Global)
+    }
+}
+
+impl Filter<ImageBuffer<Rgb<u8>, Vec<u8>>, ImageBuffer<Rgb<u8>, Vec<u8>>> for EqualizeFilter {
+    fn apply(&self, img: &ImageBuffer<Rgb<u8>, Vec<u8>>) -> ImageBuffer<Rgb<u8>, Vec<u8>> {
+        equalize::equalize(img, self.method)
+    }
+}
+
+impl Filter<ImageBuffer<Luma<u8>, Vec<u8>>, ImageBuffer<Luma<u8>, Vec<u8>>> for EqualizeFilter {
+    fn apply(&self, img: &ImageBuffer<Luma<u8>, Vec<u8>>) -> ImageBuffer<Luma<u8>, Vec<u8>> {
+        equalize::equalize_gray(img, self.method)
+    }
+}
+
 /// Per-channel inversion (negative) for RGB images.
 pub struct InvertFilter;
 
@@ -83,20 +876,337 @@ impl Filter<ImageBuffer<Rgb<u8>, Vec<u8>>, ImageBuffer<Rgb<u8>, Vec<u8>>> for In
 
 /// Sobel edge detection (magnitude of gradient) for RGB images.
 pub struct SobelFilter {
-    /// Intensity multiplier applied after normalization (>= 0.0).
-    pub intensity: f64,
+    /// Normalization, intensity and optional binary threshold of the magnitude.
+    pub scale: MagnitudeScale,
+    /// How pixels outside the image are synthesized.
+    pub border: BorderMode,
 }
 
 impl SobelFilter {
-    /// Create a new Sobel filter with the given intensity multiplier.
+    /// Create a new Sobel filter with the given intensity multiplier and the
+    /// default (reflect-101) border.
     pub fn new(intensity: f64) -> Self {
-        Self { intensity }
+        Self {
+            scale: MagnitudeScale::new(intensity as f32),
+            border: BorderMode::default(),
+        }
+    }
+
+    /// Map the magnitude to output levels with `scale`.
+    pub fn with_scale(mut self, scale: MagnitudeScale) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    /// Use a different border mode.
+    pub fn with_border(mut self, border: BorderMode) -> Self {
+        self.border = border;
+        self
     }
 }
 
 impl Filter<ImageBuffer<Rgb<u8>, Vec<u8>>, ImageBuffer<Luma<u8>, Vec<u8>>> for SobelFilter {
     fn apply(&self, img: &ImageBuffer<Rgb<u8>, Vec<u8>>) -> ImageBuffer<Luma<u8>, Vec<u8>> {
-        sobel::sobel_edges(img, self.intensity as f32)
+        sobel::sobel_edges(img, self.scale, self.border)
+    }
+}
+
+/// Canny edge detection for RGB images, producing a binary edge map.
+pub struct CannyFilter {
+    /// Standard deviation of the Gaussian pre-smoothing (0.0 disables it).
+    pub sigma: f32,
+    /// Hysteresis thresholds on the gradient magnitude.
+    pub thresholds: CannyThresholds,
+    /// How pixels outside the image are synthesized.
+    pub border: BorderMode,
+}
+
+impl CannyFilter {
+    /// Create a new Canny filter with automatic (gradient-based) thresholds and
+    /// the default (reflect-101) border.
+    pub fn new(sigma: f32) -> Self {
+        Self {
+            sigma,
+            thresholds: CannyThresholds::Auto,
+            border: BorderMode::default(),
+        }
+    }
+
+    /// Use fixed `low` / `high` thresholds on the Sobel gradient magnitude.
+    pub fn with_thresholds(mut self, low: f32, high: f32) -> Self {
+        self.thresholds = CannyThresholds::Manual { low, high };
+        self
+    }
+
+    /// Use a different border mode.
+    pub fn with_border(mut self, border: BorderMode) -> Self {
+        self.border = border;
+        self
+    }
+}
+
+impl Default for CannyFilter {
+    fn default() -> Self {
+        Self::new(1.4)
+    }
+}
+
+impl Filter<ImageBuffer<Rgb<u8>, Vec<u8>>, ImageBuffer<Luma<u8>, Vec<u8>>> for CannyFilter {
+    fn apply(&self, img: &ImageBuffer<Rgb<u8>, Vec<u8>>) -> ImageBuffer<Luma<u8>, Vec<u8>> {
+        canny::canny(img, self.sigma, self.thresholds, self.border)
+    }
+}
+
+/// Computes the raw image [`Gradient`] (derivatives, magnitude and direction)
+/// of an RGB image.
+pub struct GradientFilter {
+    /// Derivative masks.
+    pub operator: GradientOperator,
+    /// `None` differentiates the luminance; `Some` differentiates each channel
+    /// of `space` and merges the results.
+    pub combine: Option<ChannelCombine>,
+    /// Color space used when `combine` is set.
+    pub space: EdgeSpace,
+    /// How pixels outside the image are synthesized.
+    pub border: BorderMode,
+}
+
+impl GradientFilter {
+    /// Create a new luminance gradient filter with the default (reflect-101) border.
+    pub fn new(operator: GradientOperator) -> Self {
+        Self {
+            operator,
+            combine: None,
+            space: EdgeSpace::Rgb,
+            border: BorderMode::default(),
+        }
+    }
+
+    /// Differentiate every channel of `space` and merge them with `combine`,
+    /// which also catches edges between colors of equal luminance.
+    pub fn with_color(mut self, combine: ChannelCombine, space: EdgeSpace) -> Self {
+        self.combine = Some(combine);
+        self.space = space;
+        self
+    }
+
+    /// Use a different border mode.
+    pub fn with_border(mut self, border: BorderMode) -> Self {
+        self.border = border;
+        self
+    }
+
+    /// Scale for [`Gradient::to_raw_u16`] that fits the strongest response
+    /// this filter can produce, so no derivative is clamped.
+    pub fn raw_u16_scale(&self) -> f32 {
+        let gain = match self.combine {
+            None | Some(ChannelCombine::Max) => 1.0,
+            Some(ChannelCombine::DiZenzo) => 3f32.sqrt(),
+            Some(ChannelCombine::Sum) => 3.0,
+        };
+        raw_u16_scale(self.operator.max_response() * gain)
+    }
+}
+
+impl Filter<ImageBuffer<Rgb<u8>, Vec<u8>>, Gradient> for GradientFilter {
+    fn apply(&self, img: &ImageBuffer<Rgb<u8>, Vec<u8>>) -> Gradient {
+        match self.combine {
+            None => Gradient::compute(img, self.operator, self.border),
+            Some(combine) => {
+                let channels =
+                    Gradient::compute_channels(img, self.operator, self.space, self.border);
+                Gradient::combine(&channels, combine)
+            }
+        }
+    }
+}
+
+/// Colored edge image: each output channel holds the gradient magnitude of
+/// the matching input channel.
+pub struct ChannelEdgeFilter {
+    /// Derivative masks.
+    pub operator: GradientOperator,
+    /// Color space whose channels are differentiated.
+    pub space: EdgeSpace,
+    /// Normalization, intensity and optional binary threshold of the magnitude.
+    pub scale: MagnitudeScale,
+    /// How pixels outside the image are synthesized.
+    pub border: BorderMode,
+}
+
+impl ChannelEdgeFilter {
+    /// Create a new RGB channel edge filter with the default (reflect-101) border.
+    pub fn new(operator: GradientOperator, intensity: f64) -> Self {
+        Self {
+            operator,
+            space: EdgeSpace::Rgb,
+            scale: MagnitudeScale::new(intensity as f32),
+            border: BorderMode::default(),
+        }
+    }
+
+    /// Differentiate the channels of another color space.
+    pub fn with_space(mut self, space: EdgeSpace) -> Self {
+        self.space = space;
+        self
+    }
+
+    /// Map the magnitude to output levels with `scale`.
+    pub fn with_scale(mut self, scale: MagnitudeScale) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    /// Use a different border mode.
+    pub fn with_border(mut self, border: BorderMode) -> Self {
+        self.border = border;
+        self
+    }
+}
+
+impl Filter<ImageBuffer<Rgb<u8>, Vec<u8>>, ImageBuffer<Rgb<u8>, Vec<u8>>> for ChannelEdgeFilter {
+    fn apply(&self, img: &ImageBuffer<Rgb<u8>, Vec<u8>>) -> ImageBuffer<Rgb<u8>, Vec<u8>> {
+        let channels = Gradient::compute_channels(img, self.operator, self.space, self.border);
+        gradient::channels_to_rgb(&channels, self.scale)
+    }
+}
+
+/// Edge detection with any [`EdgeOperator`] (Sobel, Prewitt, Scharr,
+/// Roberts, Kirsch or Frei-Chen) for RGB images.
+pub struct GradientEdgeFilter {
+    /// Masks and how their responses are combined.
+    pub operator: EdgeOperator,
+    /// Normalization, intensity and optional binary threshold of the magnitude.
+    pub scale: MagnitudeScale,
+    /// How pixels outside the image are synthesized.
+    pub border: BorderMode,
+}
+
+impl GradientEdgeFilter {
+    /// Create a new edge filter with the given operator, intensity multiplier
+    /// and the default (reflect-101) border.
+    pub fn new(operator: EdgeOperator, intensity: f64) -> Self {
+        Self {
+            operator,
+            scale: MagnitudeScale::new(intensity as f32),
+            border: BorderMode::default(),
+        }
+    }
+
+    /// Map the magnitude to output levels with `scale`.
+    pub fn with_scale(mut self, scale: MagnitudeScale) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    /// Use a different border mode.
+    pub fn with_border(mut self, border: BorderMode) -> Self {
+        self.border = border;
+        self
+    }
+}
+
+impl Filter<ImageBuffer<Rgb<u8>, Vec<u8>>, ImageBuffer<Luma<u8>, Vec<u8>>> for GradientEdgeFilter {
+    fn apply(&self, img: &ImageBuffer<Rgb<u8>, Vec<u8>>) -> ImageBuffer<Luma<u8>, Vec<u8>> {
+        edge::operator_edges(img, self.operator, self.scale, self.border)
+    }
+}
+
+/// Second-derivative edge detection (Laplacian, LoG, DoG) for RGB images.
+pub struct LaplacianFilter {
+    /// Operator and its scale.
+    pub operator: SecondDerivative,
+    /// Normalization, intensity and optional binary threshold of the magnitude.
+    pub scale: MagnitudeScale,
+    /// `Some(threshold)` outputs a binary map of zero crossings whose step is at
+    /// least `threshold` times the strongest response.
+    pub zero_crossing: Option<f32>,
+    /// How pixels outside the image are synthesized.
+    pub border: BorderMode,
+}
+
+impl LaplacianFilter {
+    /// Create a new filter showing the magnitude of `operator`, with the
+    /// default (reflect-101) border.
+    pub fn new(operator: SecondDerivative, intensity: f64) -> Self {
+        Self {
+            operator,
+            scale: MagnitudeScale::new(intensity as f32),
+            zero_crossing: None,
+            border: BorderMode::default(),
+        }
+    }
+
+    /// Output zero crossings instead of the magnitude.
+    pub fn with_zero_crossings(mut self, threshold: f32) -> Self {
+        self.zero_crossing = Some(threshold);
+        self
+    }
+
+    /// Map the magnitude to output levels with `scale`.
+    pub fn with_scale(mut self, scale: MagnitudeScale) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    /// Use a different border mode.
+    pub fn with_border(mut self, border: BorderMode) -> Self {
+        self.border = border;
+        self
+    }
+}
+
+impl Filter<ImageBuffer<Rgb<u8>, Vec<u8>>, ImageBuffer<Luma<u8>, Vec<u8>>> for LaplacianFilter {
+    fn apply(&self, img: &ImageBuffer<Rgb<u8>, Vec<u8>>) -> ImageBuffer<Luma<u8>, Vec<u8>> {
+        laplacian::second_derivative_edges(
+            img,
+            self.operator,
+            self.scale,
+            self.zero_crossing,
+            self.border,
+        )
+    }
+}
+
+/// Mosaic ("pixelate") filter that fills fixed-size blocks with a single color.
+pub struct PixelateFilter {
+    /// Block width in pixels (>= 1).
+    pub block_width: u32,
+    /// Block height in pixels (>= 1).
+    pub block_height: u32,
+    /// How each block's fill color is chosen.
+    pub mode: BlockColor,
+    /// Optional color of 1px grid lines drawn between blocks.
+    pub grid: Option<Rgb<u8>>,
+}
+
+impl PixelateFilter {
+    /// Create a new pixelate filter with the given block size and color mode.
+    pub fn new(block_width: u32, block_height: u32, mode: BlockColor) -> Self {
+        Self {
+            block_width,
+            block_height,
+            mode,
+            grid: None,
+        }
+    }
+
+    /// Overlay grid lines of the given color along block boundaries.
+    pub fn with_grid(mut self, color: Rgb<u8>) -> Self {
+        self.grid = Some(color);
+        self
+    }
+}
+
+impl Filter<ImageBuffer<Rgb<u8>, Vec<u8>>, ImageBuffer<Rgb<u8>, Vec<u8>>> for PixelateFilter {
+    fn apply(&self, img: &ImageBuffer<Rgb<u8>, Vec<u8>>) -> ImageBuffer<Rgb<u8>, Vec<u8>> {
+        mosaic::pixelate(
+            img,
+            self.block_width,
+            self.block_height,
+            self.mode,
+            self.grid,
+        )
     }
 }
 
@@ -138,10 +1248,108 @@ mod tests {
     #[test]
     fn test_halftone_filter() {
         let img: ImageBuffer<Rgb<u8>, Vec<u8>> = create_test_image();
-        let filter: HalftoneFilter = HalftoneFilter;
+        let filter: HalftoneFilter = HalftoneFilter::default();
+        let ht_img: ImageBuffer<Luma<u8>, Vec<u8>> =
+            <HalftoneFilter as Filter<_, _>>::apply(&filter, &img);
+        assert_eq!(ht_img.dimensions(), (3, 3));
+    }
+
+    #[test]
+    fn test_halftone_filter_custom_matrix() {
+        let img: ImageBuffer<Rgb<u8>, Vec<u8>> = create_test_image();
+        for matrix in [
+            ThresholdMap::bayer(8),
+            ThresholdMap::cluster_dot(4),
+            ThresholdMap::blue_noise(4),
+        ] {
+            let filter: HalftoneFilter = HalftoneFilter::default().with_matrix(matrix);
+            let ht_img: ImageBuffer<Luma<u8>, Vec<u8>> =
+                <HalftoneFilter as Filter<_, _>>::apply(&filter, &img);
+            assert_eq!(ht_img.dimensions(), (3, 3));
+        }
+    }
+
+    #[test]
+    fn test_halftone_filter_error_diffusion() {
+        let img: ImageBuffer<Rgb<u8>, Vec<u8>> = create_test_image();
+        let filter: HalftoneFilter =
+            HalftoneFilter::new(DitherMethod::Atkinson).with_serpentine(true);
         let ht_img: ImageBuffer<Luma<u8>, Vec<u8>> =
             <HalftoneFilter as Filter<_, _>>::apply(&filter, &img);
         assert_eq!(ht_img.dimensions(), (3, 3));
+        assert!(ht_img.pixels().all(|p| p[0] == 0 || p[0] == 255));
+    }
+
+    #[test]
+    fn test_dither_filter() {
+        let img: ImageBuffer<Rgb<u8>, Vec<u8>> = create_test_image();
+        let palette = Palette::new(vec![Rgb([0, 0, 0]), Rgb([255, 255, 255])]);
+        let filter: DitherFilter = DitherFilter::palette(palette.clone())
+            .with_method(DitherMethod::FloydSteinberg)
+            .with_space(DitherSpace::Oklab);
+        let out: ImageBuffer<Rgb<u8>, Vec<u8>> =
+            <DitherFilter as Filter<_, _>>::apply(&filter, &img);
+        assert_eq!(out.dimensions(), (3, 3));
+        assert!(out.pixels().all(|p| palette.colors().contains(p)));
+    }
+
+    #[test]
+    fn test_quantize_filter() {
+        let img: ImageBuffer<Rgb<u8>, Vec<u8>> = create_test_image();
+        let filter: QuantizeFilter = QuantizeFilter::new(4, QuantizeMethod::Wu);
+        let Quantized { palette, image } = filter.quantize(&img);
+        assert!(palette.len() <= 4);
+        assert_eq!(image.dimensions(), (3, 3));
+        assert!(image.pixels().all(|p| palette.colors().contains(p)));
+        let applied: ImageBuffer<Rgb<u8>, Vec<u8>> =
+            <QuantizeFilter as Filter<_, _>>::apply(&filter, &img);
+        assert_eq!(applied, image);
+    }
+
+    #[test]
+    fn test_palette_map_filter() {
+        let img: ImageBuffer<Rgb<u8>, Vec<u8>> = create_test_image();
+        let palette = Palette::named("pico8").unwrap();
+        let filter: PaletteMapFilter = PaletteMapFilter::new(palette.clone(), ColorMetric::Oklab);
+        let out: ImageBuffer<Rgb<u8>, Vec<u8>> =
+            <PaletteMapFilter as Filter<_, _>>::apply(&filter, &img);
+        assert_eq!(out.dimensions(), (3, 3));
+        assert!(out.pixels().all(|p| palette.colors().contains(p)));
+    }
+
+    #[test]
+    fn test_blur_filter() {
+        let img: ImageBuffer<Rgb<u8>, Vec<u8>> = create_test_image();
+        let filter: BlurFilter = BlurFilter::gaussian(0.8);
+        let out: ImageBuffer<Rgb<u8>, Vec<u8>> = <BlurFilter as Filter<_, _>>::apply(&filter, &img);
+        assert_eq!(out.dimensions(), (3, 3));
+        // The center is averaged with its neighbours
+        let center = img.get_pixel(1, 1);
+        assert!(out.get_pixel(1, 1)[2] <= center[2]);
+    }
+
+    #[test]
+    fn test_convolve_filter() {
+        let img: ImageBuffer<Rgb<u8>, Vec<u8>> = create_test_image();
+        let kernel: Kernel = "1 1 1; 1 1 1; 1 1 1".parse().unwrap();
+        let filter: ConvolveFilter = ConvolveFilter::new(kernel);
+        assert_eq!(filter.divisor, 9.0);
+        let out: ImageBuffer<Rgb<u8>, Vec<u8>> =
+            <ConvolveFilter as Filter<_, _>>::apply(&filter, &img);
+        // The mean of a linear gradient is its center value
+        assert_eq!(*out.get_pixel(1, 1), Rgb([50, 50, 100]));
+        let loaded = ConvolveFilter::from_json(r#"[[1, 1, 1], [1, 1, 1], [1, 1, 1]]"#).unwrap();
+        assert_eq!(
+            (loaded.kernel, loaded.divisor, loaded.bias),
+            (filter.kernel, 9.0, 0.0)
+        );
+        let loaded =
+            ConvolveFilter::from_json(r#"{"kernel": [[0, 1, 0]], "divisor": 2, "bias": -5}"#)
+                .unwrap();
+        assert_eq!((loaded.divisor, loaded.bias), (2.0, -5.0));
+        assert!(ConvolveFilter::from_json(r#"{"kernel": [[1]], "divisor": 0}"#).is_err());
+        assert!(ConvolveFilter::from_json(r#"{"kernel": [[1]], "bias": "x"}"#).is_err());
+        assert!(ConvolveFilter::from_json("[[1, 2], [3]]").is_err());
     }
 
     #[test]
@@ -168,4 +1376,271 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_pixelate_filter() {
+        let img: ImageBuffer<Rgb<u8>, Vec<u8>> = create_test_image();
+        let filter: PixelateFilter = PixelateFilter::new(3, 3, BlockColor::Average);
+        let px_img: ImageBuffer<Rgb<u8>, Vec<u8>> =
+            <PixelateFilter as Filter<_, _>>::apply(&filter, &img);
+        assert_eq!(px_img.dimensions(), (3, 3));
+        // A single block covers the whole image, so every pixel is identical
+        let first = *px_img.get_pixel(0, 0);
+        assert!(px_img.pixels().all(|p| *p == first));
+        assert_eq!(first, Rgb([50, 50, 100]));
+    }
+
+    #[test]
+    fn test_edge_operator_filters() {
+        let img: ImageBuffer<Rgb<u8>, Vec<u8>> = create_test_image();
+        let operators = [
+            EdgeOperator::Sobel,
+            EdgeOperator::Prewitt,
+            EdgeOperator::Scharr,
+            EdgeOperator::Roberts,
+            EdgeOperator::Kirsch,
+            EdgeOperator::FreiChen,
+        ];
+        let outputs = operators.map(|operator| GradientEdgeFilter::new(operator, 1.0).apply(&img));
+        assert_eq!(outputs[0], SobelFilter::new(1.0).apply(&img));
+        for out in outputs {
+            assert_eq!(out.dimensions(), (3, 3));
+            // The gradient image has edges everywhere; the strongest maps to 255
+            assert_eq!(out.pixels().map(|p| p[0]).max(), Some(255));
+        }
+    }
+
+    #[test]
+    fn test_edge_normalization_and_threshold() {
+        let img: ImageBuffer<Rgb<u8>, Vec<u8>> = create_test_image();
+        let filter = SobelFilter::new(1.0).with_scale(
+            MagnitudeScale::new(1.0)
+                .with_normalization(Normalization::Fixed(1.0))
+                .with_threshold(Some(128)),
+        );
+        let out: ImageBuffer<Luma<u8>, Vec<u8>> =
+            <SobelFilter as Filter<_, _>>::apply(&filter, &img);
+        // Every nonzero gradient exceeds a magnitude of 1 and saturates
+        assert!(out.pixels().all(|p| p[0] == 0 || p[0] == 255));
+        assert!(out.pixels().any(|p| p[0] == 255));
+        // A lone dot on a flat field: fewer than 1% of the pixels have a
+        // gradient, yet the 99th percentile still shows it
+        let dot: ImageBuffer<Rgb<u8>, Vec<u8>> = ImageBuffer::from_fn(40, 40, |x, y| {
+            Rgb([if (x, y) == (20, 20) { 255 } else { 0 }; 3])
+        });
+        let filter = SobelFilter::new(1.0).with_scale(
+            MagnitudeScale::new(1.0).with_normalization(Normalization::Percentile(99.0)),
+        );
+        let out: ImageBuffer<Luma<u8>, Vec<u8>> =
+            <SobelFilter as Filter<_, _>>::apply(&filter, &dot);
+        assert_eq!(out.pixels().map(|p| p[0]).max(), Some(255));
+    }
+
+    #[test]
+    fn test_threshold_filter() {
+        let img: ImageBuffer<Rgb<u8>, Vec<u8>> = create_test_image();
+        for method in [
+            ThresholdMethod::Fixed(128),
+            ThresholdMethod::Otsu,
+            ThresholdMethod::Triangle,
+            ThresholdMethod::Sauvola {
+                radius: 1,
+                k: 0.2,
+                range: 128.0,
+            },
+        ] {
+            let filter = ThresholdFilter::new(method);
+            let out: ImageBuffer<Luma<u8>, Vec<u8>> =
+                <ThresholdFilter as Filter<_, _>>::apply(&filter, &img);
+            assert_eq!(out.dimensions(), (3, 3));
+            assert!(out.pixels().all(|p| p[0] == 0 || p[0] == 255));
+            let inverted: ImageBuffer<Luma<u8>, Vec<u8>> = filter.with_invert(true).apply(&img);
+            assert!(out
+                .pixels()
+                .zip(inverted.pixels())
+                .all(|(a, b)| a[0] != b[0]));
+        }
+    }
+
+    #[test]
+    fn test_equalize_filter() {
+        let img: ImageBuffer<Rgb<u8>, Vec<u8>> = create_test_image();
+        let clahe = EqualizeFilter::new(EqualizeMethod::Clahe {
+            tiles_x: 2,
+            tiles_y: 2,
+            clip_limit: 2.0,
+        });
+        for filter in [EqualizeFilter::default(), clahe] {
+            let out: ImageBuffer<Rgb<u8>, Vec<u8>> =
+                <EqualizeFilter as Filter<_, _>>::apply(&filter, &img);
+            assert_eq!(out.dimensions(), (3, 3));
+            let gray: ImageBuffer<Luma<u8>, Vec<u8>> =
+                grayscale::grayscale(&img, SRGB_LUMA_R, SRGB_LUMA_G, SRGB_LUMA_B);
+            let out: ImageBuffer<Luma<u8>, Vec<u8>> =
+                <EqualizeFilter as Filter<_, _>>::apply(&filter, &gray);
+            assert_eq!(out.dimensions(), (3, 3));
+        }
+
+        // A low-contrast ramp (100..=115) is stretched over the full range
+        let ramp: ImageBuffer<Luma<u8>, Vec<u8>> =
+            ImageBuffer::from_fn(4, 4, |x, y| Luma([100 + (y * 4 + x) as u8]));
+        let range = |values: Vec<u8>| {
+            let min = *values.iter().min().unwrap();
+            let max = *values.iter().max().unwrap();
+            (min, max)
+        };
+        let out: ImageBuffer<Luma<u8>, Vec<u8>> = EqualizeFilter::default().apply(&ramp);
+        let (min, max) = range(out.into_raw());
+        assert!(min <= 16, "darkest level {}", min);
+        assert_eq!(max, 255);
+        let rgb: ImageBuffer<Rgb<u8>, Vec<u8>> =
+            ImageBuffer::from_fn(4, 4, |x, y| Rgb([ramp.get_pixel(x, y)[0]; 3]));
+        let out: ImageBuffer<Rgb<u8>, Vec<u8>> = EqualizeFilter::default().apply(&rgb);
+        let (min, max) = range(out.into_raw());
+        assert!(min <= 16, "darkest level {}", min);
+        assert_eq!(max, 255);
+    }
+
+    #[test]
+    fn test_tone_filters() {
+        let img: ImageBuffer<Rgb<u8>, Vec<u8>> = create_test_image();
+        let levels = LevelsFilter::new(Levels::new(0, 100)).apply(&img);
+        assert_eq!(*levels.get_pixel(2, 2), Rgb([255, 255, 255]));
+        assert_eq!(levels.get_pixel(1, 0)[0], 128);
+        let auto = LevelsFilter::default()
+            .with_auto(0.0)
+            .with_per_channel(true)
+            .apply(&img);
+        assert_eq!(*auto.get_pixel(0, 0), Rgb([0, 0, 100]));
+        assert_eq!(*auto.get_pixel(2, 2), Rgb([255, 255, 100]));
+        let brighter = BrightnessContrastFilter::new(10.0, 1.0).apply(&img);
+        assert_eq!(*brighter.get_pixel(0, 0), Rgb([10, 10, 110]));
+        let unchanged = ExposureFilter::new(0.0).apply(&img);
+        assert_eq!(unchanged, img);
+    }
+
+    #[test]
+    fn test_curves_filter() {
+        let img: ImageBuffer<Rgb<u8>, Vec<u8>> = create_test_image();
+        let unchanged = CurvesFilter::default().apply(&img);
+        assert_eq!(unchanged, img);
+        // Invert through the master curve, then lift the red channel first
+        let filter = CurvesFilter::new(Curve::new(&[(0.0, 255.0), (255.0, 0.0)]))
+            .with_red(Curve::new(&[(0.0, 50.0), (255.0, 255.0)]))
+            .with_interpolation(CurveInterpolation::CatmullRom);
+        let out = filter.apply(&img);
+        assert_eq!(*out.get_pixel(0, 0), Rgb([205, 255, 155]));
+        let loaded = CurvesFilter::from_json(
+            r#"{"interpolation": "catmull-rom", "master": [[0, 255], [255, 0]], "red": "0,50 255,255"}"#,
+        )
+        .unwrap();
+        assert_eq!(loaded.apply(&img), out);
+        assert_eq!(loaded.green, Curve::identity());
+        assert!(CurvesFilter::from_json(r#"{"master": [[0, "x", 10]]}"#).is_err());
+        assert!(CurvesFilter::from_json(r#"{"interpolation": "linear"}"#).is_err());
+        assert!(CurvesFilter::from_json("[[0, 0], [255, 255]]").is_err());
+        assert!(CurvesFilter::from_json("{").is_err());
+    }
+
+    #[test]
+    fn test_lut_filter() {
+        let img: ImageBuffer<Rgb<u8>, Vec<u8>> = create_test_image();
+        // Baking a filter and applying the LUT reproduces the filter
+        let lut = ColorLut::bake(17, |img| InvertFilter.apply(img));
+        for interpolation in [LutInterpolation::Trilinear, LutInterpolation::Tetrahedral] {
+            let out = LutFilter::new(lut.clone())
+                .with_interpolation(interpolation)
+                .apply(&img);
+            assert_eq!(out, InvertFilter.apply(&img));
+        }
+    }
+
+    #[test]
+    fn test_toning_filters() {
+        let img: ImageBuffer<Rgb<u8>, Vec<u8>> = create_test_image();
+        let sepia = SepiaFilter::default().apply(&img);
+        let p = sepia.get_pixel(1, 1);
+        assert!(p[0] > p[1] && p[1] > p[2], "{:?}", p);
+        let duotone = GradientMapFilter::duotone(Rgb([0, 0, 0]), Rgb([255, 0, 0])).apply(&img);
+        assert!(duotone.pixels().all(|p| p[1] == 0 && p[2] == 0));
+        // Equal weights: the luminance of (50, 50, 100) is 66.7 -> 67
+        let tritone =
+            GradientMapFilter::tritone(Rgb([0, 0, 0]), Rgb([0, 255, 0]), Rgb([255, 255, 255]))
+                .with_luma(GrayscaleFilter::new(1.0 / 3.0, 1.0 / 3.0, 1.0 / 3.0))
+                .apply(&img);
+        assert_eq!(*tritone.get_pixel(1, 1), Rgb([0, 134, 0]));
+    }
+
+    #[test]
+    fn test_colormap_filter() {
+        let img: ImageBuffer<Rgb<u8>, Vec<u8>> = create_test_image();
+        let gray = GrayscaleFilter::new(1.0 / 3.0, 1.0 / 3.0, 1.0 / 3.0).apply(&img);
+        let colormap = Colormap::named("viridis").unwrap();
+        let out = ColormapFilter::new(colormap.clone()).apply(&gray);
+        assert_eq!(out.dimensions(), gray.dimensions());
+        for (p, g) in out.pixels().zip(gray.pixels()) {
+            assert_eq!(*p, colormap.color(g[0]));
+        }
+        let reversed = ColormapFilter::new(colormap.clone().reversed()).apply(&gray);
+        assert_eq!(
+            *reversed.get_pixel(0, 0),
+            colormap.color(255 - gray.get_pixel(0, 0)[0])
+        );
+    }
+
+    #[test]
+    fn test_canny_filter() {
+        let img: ImageBuffer<Rgb<u8>, Vec<u8>> = create_test_image();
+        let filter: CannyFilter = CannyFilter::new(0.0).with_thresholds(10.0, 20.0);
+        let out: ImageBuffer<Luma<u8>, Vec<u8>> =
+            <CannyFilter as Filter<_, _>>::apply(&filter, &img);
+        assert_eq!(out.dimensions(), (3, 3));
+        assert!(out.pixels().all(|p| p[0] == 0 || p[0] == 255));
+    }
+
+    #[test]
+    fn test_laplacian_filter() {
+        let img: ImageBuffer<Rgb<u8>, Vec<u8>> = create_test_image();
+        let filter: LaplacianFilter = LaplacianFilter::new(SecondDerivative::Laplacian4, 1.0);
+        let out: ImageBuffer<Luma<u8>, Vec<u8>> =
+            <LaplacianFilter as Filter<_, _>>::apply(&filter, &img);
+        assert_eq!(out.dimensions(), (3, 3));
+        let filter = filter.with_zero_crossings(0.1);
+        let out: ImageBuffer<Luma<u8>, Vec<u8>> =
+            <LaplacianFilter as Filter<_, _>>::apply(&filter, &img);
+        assert!(out.pixels().all(|p| p[0] == 0 || p[0] == 255));
+    }
+
+    #[test]
+    fn test_gradient_filter() {
+        let img: ImageBuffer<Rgb<u8>, Vec<u8>> = create_test_image();
+        let filter: GradientFilter = GradientFilter::new(GradientOperator::Sobel);
+        let gradient: Gradient = <GradientFilter as Filter<_, _>>::apply(&filter, &img);
+        assert_eq!((gradient.width(), gradient.height()), (3, 3));
+        // Red grows with x and green with y, so the luminance rises down-right
+        let angle = gradient.angle()[4];
+        assert!(angle > 0.0 && angle < std::f32::consts::FRAC_PI_2);
+    }
+
+    #[test]
+    fn test_color_gradient_filters() {
+        // Red/green halves whose luminance is almost identical
+        let img: ImageBuffer<Rgb<u8>, Vec<u8>> = ImageBuffer::from_fn(4, 2, |x, _| {
+            if x < 2 {
+                Rgb([255, 0, 0])
+            } else {
+                Rgb([0, 76, 0])
+            }
+        });
+        let luma: Gradient = GradientFilter::new(GradientOperator::Sobel).apply(&img);
+        let color: Gradient = GradientFilter::new(GradientOperator::Sobel)
+            .with_color(ChannelCombine::DiZenzo, EdgeSpace::Rgb)
+            .apply(&img);
+        assert!(color.magnitude()[1] > 10.0 * luma.magnitude()[1]);
+
+        let filter: ChannelEdgeFilter = ChannelEdgeFilter::new(GradientOperator::Sobel, 1.0);
+        let out: ImageBuffer<Rgb<u8>, Vec<u8>> =
+            <ChannelEdgeFilter as Filter<_, _>>::apply(&filter, &img);
+        assert_eq!(*out.get_pixel(1, 0), Rgb([255, 76, 0]));
+    }
 }
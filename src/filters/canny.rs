@@ -0,0 +1,258 @@
+//! Canny edge detection on top of the Sobel gradients.
+//!
+//! The pipeline is: Gaussian pre-smoothing, Sobel gradients, non-maximum
+//! suppression along the gradient direction, double thresholding and
+//! hysteresis edge tracking. The output is a binary edge map.
+use std::collections::VecDeque;
+
+use image::{ImageBuffer, Luma, Rgb};
+
+use super::convolution::{gaussian_blur_f32, BorderMode};
+use super::grayscale;
+use super::sobel::sobel_gradients;
+
+/// Fraction of pixels assumed not to be edges: the automatic high threshold
+/// is this quantile of the gradient magnitudes.
+const AUTO_NON_EDGE_FRACTION: f32 = 0.7;
+
+/// Automatic low threshold as a fraction of the high one.
+const AUTO_LOW_RATIO: f32 = 0.4;
+
+/// Smallest automatic high threshold: the Sobel magnitude of a 4-level step,
+/// so flat or dark images do not turn quantization noise into edges.
+const AUTO_MIN_HIGH: f32 = 16.0;
+
+/// How the hysteresis thresholds are chosen.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum CannyThresholds {
+    /// Derive both thresholds from the Sobel gradient magnitudes of the
+    /// smoothed image: `high` is their 70th percentile (at least 16, a
+    /// 4-level step) and `low = 0.4 * high`.
+    Auto,
+    /// Fixed thresholds on the Sobel gradient magnitude (`low <= high`).
+    Manual { low: f32, high: f32 },
+}
+
+/// Apply Canny edge detection to an RGB image.
+///
+/// # Arguments
+/// * `img` - The input RGB image
+/// * `sigma` - Standard deviation of the Gaussian pre-smoothing (0 disables it)
+/// * `thresholds` - Fixed or automatically chosen hysteresis thresholds
+/// * `border` - How pixels outside the image are synthesized
+///
+/// # Returns
+/// Binary edge map: 255 on one-pixel-wide edges, 0 elsewhere
+pub fn canny(
+    img: &ImageBuffer<Rgb<u8>, Vec<u8>>,
+    sigma: f32,
+    thresholds: CannyThresholds,
+    border: BorderMode,
+) -> ImageBuffer<Luma<u8>, Vec<u8>> {
+    let gray: ImageBuffer<Luma<u8>, Vec<u8>> = grayscale::grayscale(
+        img,
+        crate::color::SRGB_LUMA_R,
+        crate::color::SRGB_LUMA_G,
+        crate::color::SRGB_LUMA_B,
+    );
+    let (w, h) = gray.dimensions();
+    let mut plane: Vec<f32> = gray.pixels().map(|p| p[0] as f32).collect();
+    if sigma > 0.0 {
+        plane = gaussian_blur_f32(&plane, w, h, sigma, border);
+    }
+
+    let (gx, gy) = sobel_gradients(&plane, w, h, border);
+    let mag: Vec<f32> = gx.iter().zip(&gy).map(|(x, y)| x.hypot(*y)).collect();
+    let (low, high) = match thresholds {
+        CannyThresholds::Manual { low, high } => (low, high),
+        CannyThresholds::Auto => {
+            let high = quantile(&mag, AUTO_NON_EDGE_FRACTION).max(AUTO_MIN_HIGH);
+            (AUTO_LOW_RATIO * high, high)
+        }
+    };
+
+    let thin = non_maximum_suppression(&mag, &gx, &gy, w, h);
+    let edges = hysteresis(&thin, w, h, low, high);
+    ImageBuffer::from_fn(w, h, |x, y| {
+        Luma([if edges[(y * w + x) as usize] { 255 } else { 0 }])
+    })
+}
+
+/// The `q` quantile (`0.0..=1.0`) of a set of values, by nearest rank.
+fn quantile(values: &[f32], q: f32) -> f32 {
+    if values.is_empty() {
+        return 0.0;
+    }
+    let mut sorted = values.to_vec();
+    let rank = ((values.len() - 1) as f32 * q).round() as usize;
+    let (_, v, _) = sorted.select_nth_unstable_by(rank, f32::total_cmp);
+    *v
+}
+
+/// Keep only gradient magnitudes that are local maxima along the gradient
+/// direction (quantized to 0, 45, 90 or 135 degrees); all others become 0.
+fn non_maximum_suppression(
+    mag: &[f32],
+    gx: &[f32],
+    gy: &[f32],
+    width: u32,
+    height: u32,
+) -> Vec<f32> {
+    let (w, h) = (width as i32, height as i32);
+    let at = |x: i32, y: i32| {
+        if x >= 0 && x < w && y >= 0 && y < h {
+            mag[(y * w + x) as usize]
+        } else {
+            0.0
+        }
+    };
+    let mut out = vec![0.0f32; mag.len()];
+    for y in 0..h {
+        for x in 0..w {
+            let i = (y * w + x) as usize;
+            let m = mag[i];
+            if m == 0.0 {
+                continue;
+            }
+            // Angle of the gradient folded into [0, 180).
+            let angle = gy[i].atan2(gx[i]).to_degrees().rem_euclid(180.0);
+            let (dx, dy) = if !(22.5..157.5).contains(&angle) {
+                (1, 0)
+            } else if angle < 67.5 {
+                (1, 1)
+            } else if angle < 112.5 {
+                (0, 1)
+            } else {
+                (-1, 1)
+            };
+            // Ties are broken towards one side so plateaus stay one pixel wide.
+            if m > at(x - dx, y - dy) && m >= at(x + dx, y + dy) {
+                out[i] = m;
+            }
+        }
+    }
+    out
+}
+
+/// Double thresholding and hysteresis: pixels at or above `high` are edges,
+/// and pixels at or above `low` become edges when 8-connected to one.
+fn hysteresis(mag: &[f32], width: u32, height: u32, low: f32, high: f32) -> Vec<bool> {
+    let (w, h) = (width as i32, height as i32);
+    let mut edges = vec![false; mag.len()];
+    let mut queue: VecDeque<(i32, i32)> = VecDeque::new();
+    for (i, &m) in mag.iter().enumerate() {
+        if m > 0.0 && m >= high {
+            edges[i] = true;
+            queue.push_back((i as i32 % w, i as i32 / w));
+        }
+    }
+    while let Some((x, y)) = queue.pop_front() {
+        for dy in -1..=1 {
+            for dx in -1..=1 {
+                let (nx, ny) = (x + dx, y + dy);
+                if nx < 0 || nx >= w || ny < 0 || ny >= h {
+                    continue;
+                }
+                let j = (ny * w + nx) as usize;
+                if !edges[j] && mag[j] > 0.0 && mag[j] >= low {
+                    edges[j] = true;
+                    queue.push_back((nx, ny));
+                }
+            }
+        }
+    }
+    edges
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{ImageBuffer, Rgb};
+
+    fn square_image() -> ImageBuffer<Rgb<u8>, Vec<u8>> {
+        // Bright 8x8 square centered on a dark 16x16 background
+        ImageBuffer::from_fn(16, 16, |x, y| {
+            if (4..12).contains(&x) && (4..12).contains(&y) {
+                Rgb([220, 220, 220])
+            } else {
+                Rgb([20, 20, 20])
+            }
+        })
+    }
+
+    #[test]
+    fn detects_thin_closed_outline() {
+        let edges = canny(
+            &square_image(),
+            1.0,
+            CannyThresholds::Manual {
+                low: 100.0,
+                high: 300.0,
+            },
+            BorderMode::Reflect101,
+        );
+        assert!(edges.pixels().all(|p| p[0] == 0 || p[0] == 255));
+        // Flat regions carry no edges
+        assert_eq!(edges.get_pixel(0, 0)[0], 0);
+        assert_eq!(edges.get_pixel(8, 8)[0], 0);
+        // Along the middle row, the left and right sides are each one pixel wide
+        let row: Vec<u32> = (0..16)
+            .filter(|&x| edges.get_pixel(x, 8)[0] == 255)
+            .collect();
+        assert_eq!(row.len(), 2, "{:?}", row);
+        assert!(row[0] >= 3 && row[0] <= 4 && row[1] >= 11 && row[1] <= 12);
+    }
+
+    #[test]
+    fn auto_thresholds_ignore_flat_images() {
+        let img: ImageBuffer<Rgb<u8>, Vec<u8>> = ImageBuffer::from_pixel(8, 8, Rgb([90, 90, 90]));
+        let edges = canny(&img, 1.4, CannyThresholds::Auto, BorderMode::Reflect101);
+        assert!(edges.pixels().all(|p| p[0] == 0));
+        let edges = canny(
+            &square_image(),
+            1.4,
+            CannyThresholds::Auto,
+            BorderMode::Clamp,
+        );
+        assert!(edges.pixels().any(|p| p[0] == 255));
+    }
+
+    #[test]
+    fn auto_thresholds_on_dark_images() {
+        // A faint square on black, with a few specks of sensor noise
+        let specks = [(2, 2), (28, 3), (3, 27), (27, 28)];
+        let img: ImageBuffer<Rgb<u8>, Vec<u8>> = ImageBuffer::from_fn(32, 32, |x, y| {
+            if (12..20).contains(&x) && (12..20).contains(&y) {
+                Rgb([40, 40, 40])
+            } else if specks.contains(&(x, y)) {
+                Rgb([3, 3, 3])
+            } else {
+                Rgb([0, 0, 0])
+            }
+        });
+        let edges = canny(&img, 1.0, CannyThresholds::Auto, BorderMode::Reflect101);
+        let row: Vec<u32> = (0..32)
+            .filter(|&x| edges.get_pixel(x, 16)[0] == 255)
+            .collect();
+        assert_eq!(row.len(), 2, "{:?}", row);
+        // The noise stays below the minimum threshold
+        let near_square = |x: u32, y: u32| (10..22).contains(&x) && (10..22).contains(&y);
+        assert!(edges
+            .enumerate_pixels()
+            .all(|(x, y, p)| p[0] == 0 || near_square(x, y)));
+    }
+
+    #[test]
+    fn hysteresis_follows_weak_pixels_connected_to_strong_ones() {
+        // One strong pixel, a chain of weak ones and an isolated weak pixel
+        let mag = [
+            9.0, 4.0, 0.0, 0.0, 0.0, //
+            0.0, 0.0, 4.0, 0.0, 4.0, //
+        ];
+        let edges = hysteresis(&mag, 5, 2, 3.0, 8.0);
+        assert_eq!(
+            edges,
+            [true, true, false, false, false, false, false, true, false, false]
+        );
+    }
+}
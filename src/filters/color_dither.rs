@@ -0,0 +1,299 @@
+//! Multi-level and palette dithering for RGB images.
+//!
+//! Pixels are quantized to gray levels, per-channel levels, or the nearest entry
+//! of a palette, using either ordered or error-diffusion dithering to hide the
+//! banding. Palette matching and error diffusion can run directly on sRGB values
+//! or in the perceptual OKLab space.
+use image::{ImageBuffer, Rgb, RgbImage};
+
+use super::dither::{diffuse, DitherMethod};
+use super::ordered::ThresholdMap;
+use crate::color::{
+    linear_srgb_to_oklab, linear_to_srgb, oklab_to_linear_srgb, srgb_to_linear, Palette,
+    SRGB_LUMA_B, SRGB_LUMA_G, SRGB_LUMA_R,
+};
+
+/// What the dithered output is quantized to.
+#[derive(Clone, Debug, PartialEq)]
+pub enum DitherTarget {
+    /// `n` evenly spaced gray levels (`n >= 2`); the output is gray.
+    Gray(u32),
+    /// `n` evenly spaced levels per RGB channel (`n >= 2`).
+    Levels(u32),
+    /// The nearest color of a fixed palette.
+    Palette(Palette),
+}
+
+/// Color space in which palette distances and diffused errors are measured.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DitherSpace {
+    /// Gamma-encoded sRGB channels, each treated independently.
+    Rgb,
+    /// Perceptually uniform OKLab.
+    Oklab,
+}
+
+/// Dither an RGB image to the given target.
+///
+/// # Arguments
+/// * `img` - The input RGB image
+/// * `target` - Gray levels, per-channel levels or palette to quantize to
+/// * `space` - Working space for palette targets (level targets always use sRGB)
+/// * `method` - Dithering algorithm
+/// * `map` - Threshold matrix (ordered dithering only)
+/// * `serpentine` - Alternate scan direction per row (error diffusion only)
+/// * `strength` - Fraction of the quantization error (or ordered noise) to apply
+///
+/// # Returns
+/// RGB image whose pixels all belong to the target set
+pub fn color_dither(
+    img: &ImageBuffer<Rgb<u8>, Vec<u8>>,
+    target: &DitherTarget,
+    space: DitherSpace,
+    method: DitherMethod,
+    map: &ThresholdMap,
+    serpentine: bool,
+    strength: f32,
+) -> ImageBuffer<Rgb<u8>, Vec<u8>> {
+    let (width, height) = img.dimensions();
+    let quantizer = Quantizer::new(target, space);
+    let mut buf: Vec<[f32; 3]> = img.pixels().map(|p| quantizer.encode(p)).collect();
+
+    match method.kernel() {
+        Some(kernel) => diffuse(&mut buf, width, height, kernel, serpentine, strength, |v| {
+            quantizer.quantize(v)
+        }),
+        None => {
+            for (i, v) in buf.iter_mut().enumerate() {
+                let (x, y) = (i as u32 % width, i as u32 / width);
+                // Centered threshold noise, scaled to one quantization step.
+                let noise = (map.threshold(x, y) - 0.5) * quantizer.spread * strength;
+                *v = quantizer.quantize(quantizer.offset(*v, noise));
+            }
+        }
+    }
+
+    let mut imgbuf: RgbImage = ImageBuffer::new(width, height);
+    for (pixel, v) in imgbuf.pixels_mut().zip(buf) {
+        *pixel = quantizer.decode(v);
+    }
+    imgbuf
+}
+
+/// Snaps working-space values to the target set.
+struct Quantizer<'a> {
+    target: &'a DitherTarget,
+    space: DitherSpace,
+    /// Palette entries converted to the working space.
+    entries: Vec<[f32; 3]>,
+    /// Typical distance between adjacent output values (ordered noise amplitude).
+    spread: f32,
+}
+
+impl<'a> Quantizer<'a> {
+    fn new(target: &'a DitherTarget, space: DitherSpace) -> Self {
+        let space = match target {
+            DitherTarget::Palette(_) => space,
+            _ => DitherSpace::Rgb,
+        };
+        let mut q = Self {
+            target,
+            space,
+            entries: Vec::new(),
+            spread: 0.0,
+        };
+        match target {
+            DitherTarget::Gray(n) | DitherTarget::Levels(n) => {
+                assert!(*n >= 2, "At least two levels are required");
+                q.spread = 1.0 / (*n as f32 - 1.0);
+            }
+            DitherTarget::Palette(palette) => {
+                q.entries = palette.colors().iter().map(|c| q.encode(c)).collect();
+                q.spread = mean_nearest_distance(&q.entries);
+            }
+        }
+        q
+    }
+
+    /// Add ordered noise to a working-space value. In OKLab only lightness is
+    /// offset: the same noise on `a` and `b` would tint neutral colors.
+    fn offset(&self, v: [f32; 3], noise: f32) -> [f32; 3] {
+        match self.space {
+            DitherSpace::Rgb => v.map(|c| c + noise),
+            DitherSpace::Oklab => [v[0] + noise, v[1], v[2]],
+        }
+    }
+
+    /// Convert a pixel to the working space.
+    fn encode(&self, p: &Rgb<u8>) -> [f32; 3] {
+        let v = p.0.map(|c| c as f32 / 255.0);
+        match (self.target, self.space) {
+            (DitherTarget::Gray(_), _) => {
+                let y = v[0] * SRGB_LUMA_R as f32
+                    + v[1] * SRGB_LUMA_G as f32
+                    + v[2] * SRGB_LUMA_B as f32;
+                [y, y, y]
+            }
+            (_, DitherSpace::Rgb) => v,
+            (_, DitherSpace::Oklab) => linear_srgb_to_oklab(v.map(srgb_to_linear)),
+        }
+    }
+
+    /// Convert a quantized working-space value back to an sRGB pixel.
+    fn decode(&self, v: [f32; 3]) -> Rgb<u8> {
+        if let DitherTarget::Palette(palette) = self.target {
+            // Return the exact palette color rather than a round-tripped one.
+            return palette.colors()[nearest(&self.entries, v)];
+        }
+        let rgb = match self.space {
+            DitherSpace::Rgb => v,
+            DitherSpace::Oklab => oklab_to_linear_srgb(v).map(linear_to_srgb),
+        };
+        Rgb(rgb.map(|c| (c * 255.0).round().clamp(0.0, 255.0) as u8))
+    }
+
+    /// Snap a working-space value to the nearest member of the target set.
+    fn quantize(&self, v: [f32; 3]) -> [f32; 3] {
+        match self.target {
+            DitherTarget::Gray(n) | DitherTarget::Levels(n) => {
+                let steps = *n as f32 - 1.0;
+                v.map(|c| (c.clamp(0.0, 1.0) * steps).round() / steps)
+            }
+            DitherTarget::Palette(_) => self.entries[nearest(&self.entries, v)],
+        }
+    }
+}
+
+/// Squared Euclidean distance between two colors.
+fn distance_sq(a: [f32; 3], b: [f32; 3]) -> f32 {
+    (0..3).map(|c| (a[c] - b[c]) * (a[c] - b[c])).sum()
+}
+
+/// Index of the entry closest to `v`.
+fn nearest(entries: &[[f32; 3]], v: [f32; 3]) -> usize {
+    let mut best = 0;
+    let mut best_d = f32::INFINITY;
+    for (i, &e) in entries.iter().enumerate() {
+        let d = distance_sq(e, v);
+        if d < best_d {
+            best = i;
+            best_d = d;
+        }
+    }
+    best
+}
+
+/// Average distance from each entry to its closest neighbour.
+fn mean_nearest_distance(entries: &[[f32; 3]]) -> f32 {
+    if entries.len() < 2 {
+        return 0.0;
+    }
+    let total: f32 = entries
+        .iter()
+        .enumerate()
+        .map(|(i, &a)| {
+            entries
+                .iter()
+                .enumerate()
+                .filter(|&(j, _)| j != i)
+                .map(|(_, &b)| distance_sq(a, b))
+                .fold(f32::INFINITY, f32::min)
+                .sqrt()
+        })
+        .sum();
+    total / entries.len() as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{ImageBuffer, Rgb};
+
+    fn create_test_image() -> ImageBuffer<Rgb<u8>, Vec<u8>> {
+        // Horizontal gradient with a colored tint
+        ImageBuffer::from_fn(16, 8, |x, y| Rgb([(x * 16) as u8, (y * 32) as u8, 128]))
+    }
+
+    #[test]
+    fn test_gray_levels_output() {
+        let img = create_test_image();
+        for method in [DitherMethod::Ordered, DitherMethod::FloydSteinberg] {
+            let out = color_dither(
+                &img,
+                &DitherTarget::Gray(4),
+                DitherSpace::Rgb,
+                method,
+                &ThresholdMap::default(),
+                false,
+                1.0,
+            );
+            for p in out.pixels() {
+                assert!(p[0] == p[1] && p[1] == p[2]);
+                assert!([0, 85, 170, 255].contains(&p[0]), "{:?}", p);
+            }
+        }
+    }
+
+    #[test]
+    fn test_palette_output_only_uses_palette_colors() {
+        let img = create_test_image();
+        let palette = Palette::new(vec![
+            Rgb([0, 0, 0]),
+            Rgb([255, 0, 0]),
+            Rgb([0, 0, 255]),
+            Rgb([255, 255, 255]),
+        ]);
+        let target = DitherTarget::Palette(palette.clone());
+        for space in [DitherSpace::Rgb, DitherSpace::Oklab] {
+            for method in [DitherMethod::Ordered, DitherMethod::Atkinson] {
+                let out = color_dither(
+                    &img,
+                    &target,
+                    space,
+                    method,
+                    &ThresholdMap::bayer(8),
+                    true,
+                    1.0,
+                );
+                for p in out.pixels() {
+                    assert!(palette.colors().contains(p), "{:?} not in palette", p);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_levels_without_noise_posterizes() {
+        let img: ImageBuffer<Rgb<u8>, Vec<u8>> = ImageBuffer::from_pixel(2, 2, Rgb([100, 200, 30]));
+        let out = color_dither(
+            &img,
+            &DitherTarget::Levels(2),
+            DitherSpace::Rgb,
+            DitherMethod::Ordered,
+            &ThresholdMap::default(),
+            false,
+            0.0,
+        );
+        assert!(out.pixels().all(|p| *p == Rgb([0, 255, 0])));
+    }
+
+    #[test]
+    fn test_oklab_ordered_keeps_neutrals_neutral() {
+        // A warm and a cool entry whose a and b share a sign, next to the grays
+        let mut colors: Vec<Rgb<u8>> = (0..8).map(|i| Rgb([i * 32; 3])).collect();
+        colors.extend([Rgb([255; 3]), Rgb([200, 150, 90]), Rgb([90, 140, 190])]);
+        let palette = Palette::new(colors);
+        let img: ImageBuffer<Rgb<u8>, Vec<u8>> =
+            ImageBuffer::from_fn(64, 8, |x, _| Rgb([(x * 4) as u8; 3]));
+        let out = color_dither(
+            &img,
+            &DitherTarget::Palette(palette),
+            DitherSpace::Oklab,
+            DitherMethod::Ordered,
+            &ThresholdMap::bayer(8),
+            false,
+            1.0,
+        );
+        assert!(out.pixels().all(|p| p[0] == p[1] && p[1] == p[2]));
+    }
+}
@@ -0,0 +1,32 @@
+//! Grade an RGB image through a 1D/3D color lookup table.
+use std::collections::HashMap;
+
+use image::{ImageBuffer, Rgb};
+
+use crate::color::{ColorLut, LutInterpolation};
+
+/// Map every pixel through `lut`.
+///
+/// # Arguments
+/// * `img` - The input RGB image
+/// * `lut` - The lookup table (normalized `0.0..=1.0` values)
+/// * `interpolation` - How the 3D lattice is interpolated
+///
+/// # Returns
+/// Graded RGB image
+pub fn apply_color_lut(
+    img: &ImageBuffer<Rgb<u8>, Vec<u8>>,
+    lut: &ColorLut,
+    interpolation: LutInterpolation,
+) -> ImageBuffer<Rgb<u8>, Vec<u8>> {
+    // Photos repeat colors, so cache each lookup.
+    let mut cache: HashMap<[u8; 3], Rgb<u8>> = HashMap::new();
+    let mut out = img.clone();
+    for pixel in out.pixels_mut() {
+        *pixel = *cache.entry(pixel.0).or_insert_with(|| {
+            let rgb = lut.sample(pixel.0.map(|v| v as f32 / 255.0), interpolation);
+            Rgb(rgb.map(|v| (v * 255.0).round().clamp(0.0, 255.0) as u8))
+        });
+    }
+    out
+}
@@ -0,0 +1,22 @@
+//! Pseudo-color a grayscale image through a colormap.
+use image::{ImageBuffer, Luma, Rgb};
+
+use crate::color::Colormap;
+
+/// Replace every gray level with its colormap color.
+///
+/// # Arguments
+/// * `gray` - The input grayscale image
+/// * `colormap` - Colors for gray levels 0..=255
+///
+/// # Returns
+/// Pseudo-colored RGB image
+pub fn apply_colormap(
+    gray: &ImageBuffer<Luma<u8>, Vec<u8>>,
+    colormap: &Colormap,
+) -> ImageBuffer<Rgb<u8>, Vec<u8>> {
+    let (width, height) = gray.dimensions();
+    ImageBuffer::from_fn(width, height, |x, y| {
+        colormap.color(gray.get_pixel(x, y)[0])
+    })
+}
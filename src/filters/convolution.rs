@@ -1,76 +1,704 @@
-//! Reusable 2D convolution utilities for grayscale images.
-use image::{ImageBuffer, Luma};
+//! Reusable convolution utilities: runtime-sized 2D kernels on images with any
+//! channel count, and separable 1D passes on `f32` planes.
+//!
+//! All functions compute a correlation (the kernel is not flipped), which is the
+//! convention used by the built-in edge and blur kernels. Samples outside the
+//! image are synthesized according to a [`BorderMode`].
+use std::str::FromStr;
+
+use image::{ImageBuffer, Luma, Pixel, Rgb};
+
+/// Relative tolerance used when testing whether a kernel is separable.
+const SEPARABLE_EPSILON: f32 = 1e-5;
+
+/// How samples outside the image are synthesized during convolution.
+///
+/// The examples show how a row `abcdefgh` is extended on both sides.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub enum BorderMode {
+    /// `000|abcdefgh|000`: out-of-range samples are zero.
+    Zero,
+    /// `vvv|abcdefgh|vvv`: out-of-range samples take a fixed value (in the
+    /// same units as the plane, i.e. 0..=255 for 8-bit images).
+    Constant(f32),
+    /// `aaa|abcdefgh|hhh`: repeat the edge sample.
+    Clamp,
+    /// `cba|abcdefgh|hgf`: mirror, repeating the edge sample.
+    Reflect,
+    /// `dcb|abcdefgh|gfe`: mirror around the edge sample without repeating it.
+    #[default]
+    Reflect101,
+    /// `fgh|abcdefgh|abc`: tile the image periodically.
+    Wrap,
+}
+
+impl BorderMode {
+    /// Map a possibly out-of-range coordinate onto `0..len`.
+    ///
+    /// Returns `None` for `Zero` and `Constant` when `i` lies outside the image.
+    pub fn index(self, i: i32, len: i32) -> Option<usize> {
+        if (0..len).contains(&i) {
+            return Some(i as usize);
+        }
+        let mapped = match self {
+            BorderMode::Zero | BorderMode::Constant(_) => return None,
+            BorderMode::Clamp => i.clamp(0, len - 1),
+            BorderMode::Wrap => i.rem_euclid(len),
+            BorderMode::Reflect => {
+                let m = i.rem_euclid(2 * len);
+                if m < len {
+                    m
+                } else {
+                    2 * len - 1 - m
+                }
+            }
+            BorderMode::Reflect101 if len == 1 => 0,
+            BorderMode::Reflect101 => {
+                let period = 2 * (len - 1);
+                let m = i.rem_euclid(period);
+                if m < len {
+                    m
+                } else {
+                    period - m
+                }
+            }
+        };
+        Some(mapped as usize)
+    }
+
+    /// Value used for samples that do not map into the image.
+    fn fill(self) -> f32 {
+        match self {
+            BorderMode::Constant(v) => v,
+            _ => 0.0,
+        }
+    }
+}
+
+/// A rectangular convolution kernel with a runtime size and anchor point.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Kernel {
+    width: usize,
+    height: usize,
+    weights: Vec<f32>,
+    anchor: (usize, usize),
+}
+
+impl Kernel {
+    /// Create a kernel from row-major `weights`, anchored at its center
+    /// (`width / 2`, `height / 2`).
+    pub fn new(width: usize, height: usize, weights: Vec<f32>) -> Self {
+        assert!(width > 0 && height > 0, "Kernel must not be empty");
+        assert_eq!(
+            weights.len(),
+            width * height,
+            "Weight count must equal width * height"
+        );
+        Self {
+            width,
+            height,
+            weights,
+            anchor: (width / 2, height / 2),
+        }
+    }
+
+    /// Create a kernel from a compile-time square array.
+    pub fn from_square<const K: usize>(kernel: &[[f32; K]; K]) -> Self {
+        Self::new(K, K, kernel.concat())
+    }
+
+    /// Create the outer product of a horizontal and a vertical 1D kernel.
+    pub fn from_separable(horizontal: &[f32], vertical: &[f32]) -> Self {
+        let weights = vertical
+            .iter()
+            .flat_map(|&v| horizontal.iter().map(move |&h| v * h))
+            .collect();
+        Self::new(horizontal.len(), vertical.len(), weights)
+    }
+
+    /// Move the anchor (the tap aligned with the output pixel).
+    pub fn with_anchor(mut self, x: usize, y: usize) -> Self {
+        assert!(
+            x < self.width && y < self.height,
+            "Anchor must lie inside the kernel"
+        );
+        self.anchor = (x, y);
+        self
+    }
+
+    /// Scale the weights so they sum to 1. Kernels summing to zero (e.g. edge
+    /// detectors) are returned unchanged.
+    pub fn normalized(mut self) -> Self {
+        let sum = self.sum();
+        if sum.abs() > f32::EPSILON {
+            self.weights.iter_mut().for_each(|w| *w /= sum);
+        }
+        self
+    }
+
+    /// Kernel width in taps.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Kernel height in taps.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Anchor position `(x, y)` within the kernel.
+    pub fn anchor(&self) -> (usize, usize) {
+        self.anchor
+    }
+
+    /// Row-major weights.
+    pub fn weights(&self) -> &[f32] {
+        &self.weights
+    }
+
+    /// Weight at tap (`x`, `y`).
+    pub fn get(&self, x: usize, y: usize) -> f32 {
+        self.weights[y * self.width + x]
+    }
+
+    /// Discrete Laplacian using the 4-neighbourhood (`eight == false`) or the
+    /// full 8-neighbourhood.
+    pub fn laplacian(eight: bool) -> Self {
+        let weights = if eight {
+            vec![1.0, 1.0, 1.0, 1.0, -8.0, 1.0, 1.0, 1.0, 1.0]
+        } else {
+            vec![0.0, 1.0, 0.0, 1.0, -4.0, 1.0, 0.0, 1.0, 0.0]
+        };
+        Self::new(3, 3, weights)
+    }
+
+    /// Laplacian of Gaussian with radius `ceil(3 * sigma)`, shifted so the
+    /// weights sum to zero (flat regions respond with exactly 0).
+    pub fn laplacian_of_gaussian(sigma: f32) -> Self {
+        assert!(sigma > 0.0, "Sigma must be positive");
+        let r = (3.0 * sigma).ceil() as i32;
+        let s2 = sigma * sigma;
+        let mut weights: Vec<f32> = (-r..=r)
+            .flat_map(|y| (-r..=r).map(move |x| (x * x + y * y) as f32))
+            .map(|d2| (d2 - 2.0 * s2) / (s2 * s2) * (-d2 / (2.0 * s2)).exp())
+            .collect();
+        let mean = weights.iter().sum::<f32>() / weights.len() as f32;
+        weights.iter_mut().for_each(|w| *w -= mean);
+        let size = (2 * r + 1) as usize;
+        Self::new(size, size, weights)
+    }
+
+    /// Sum of all weights.
+    pub fn sum(&self) -> f32 {
+        self.weights.iter().sum()
+    }
+
+    /// Factor the kernel into `(horizontal, vertical)` 1D kernels if it has
+    /// rank one, so it can be applied as two cheaper passes.
+    pub fn separate(&self) -> Option<(Vec<f32>, Vec<f32>)> {
+        let (pivot_idx, pivot) = self
+            .weights
+            .iter()
+            .copied()
+            .enumerate()
+            .max_by(|a, b| a.1.abs().total_cmp(&b.1.abs()))?;
+        if pivot == 0.0 {
+            return None;
+        }
+        let (px, py) = (pivot_idx % self.width, pivot_idx / self.width);
+        let horizontal: Vec<f32> = (0..self.width).map(|x| self.get(x, py)).collect();
+        let vertical: Vec<f32> = (0..self.height).map(|y| self.get(px, y) / pivot).collect();
+        let tolerance = SEPARABLE_EPSILON * pivot.abs();
+        let rank_one = self
+            .weights
+            .chunks(self.width)
+            .zip(&vertical)
+            .all(|(row, &v)| {
+                row.iter()
+                    .zip(&horizontal)
+                    .all(|(&w, &h)| (w - v * h).abs() <= tolerance)
+            });
+        rank_one.then_some((horizontal, vertical))
+    }
+}
+
+impl Kernel {
+    /// Build a kernel from JSON: either a bare array of rows, or an object
+    /// such as `{"kernel": [[0, -1, 0], [-1, 5, -1], [0, -1, 0]], "anchor": [1, 1]}`.
+    ///
+    /// Other keys of the object are ignored.
+    pub fn from_json(value: &serde_json::Value) -> Result<Self, String> {
+        let rows = value.get("kernel").unwrap_or(value);
+        let rows = rows
+            .as_array()
+            .ok_or("kernel must be an array of rows")?
+            .iter()
+            .map(|row| {
+                row.as_array()
+                    .ok_or(format!("kernel rows must be arrays: {row}"))?
+                    .iter()
+                    .map(|v| {
+                        v.as_f64()
+                            .map(|v| v as f32)
+                            .ok_or(format!("kernel values must be numbers: {v}"))
+                    })
+                    .collect::<Result<Vec<f32>, String>>()
+            })
+            .collect::<Result<Vec<Vec<f32>>, String>>()?;
+        let (width, height, weights) = matrix_from_rows(rows, "kernel")?;
+        let kernel = Self::new(width, height, weights);
+        let Some(anchor) = value.get("anchor") else {
+            return Ok(kernel);
+        };
+        let xy: Option<Vec<usize>> = anchor
+            .as_array()
+            .and_then(|a| a.iter().map(|v| v.as_u64().map(|v| v as usize)).collect());
+        match xy.as_deref() {
+            Some(&[x, y]) if x < width && y < height => Ok(kernel.with_anchor(x, y)),
+            Some(&[_, _]) => Err("anchor must lie inside the kernel".into()),
+            _ => Err("anchor must be an [x, y] pair of indices".into()),
+        }
+    }
+}
+
+impl FromStr for Kernel {
+    type Err = String;
+
+    /// Parse a kernel of weights in the [`parse_matrix`] format, anchored at
+    /// its center.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (width, height, weights) = parse_matrix(s, "kernel")?;
+        Ok(Self::new(width, height, weights))
+    }
+}
+
+/// Parse a matrix of numbers, returning its width, height and row-major values.
+///
+/// Rows are separated by newlines or `;`, values by whitespace or `,`.
+/// Lines starting with `#` are ignored. All rows must have equal length;
+/// `what` names the matrix in error messages.
+pub fn parse_matrix(s: &str, what: &str) -> Result<(usize, usize, Vec<f32>), String> {
+    let mut rows: Vec<Vec<f32>> = Vec::new();
+    for line in s.lines().filter(|l| !l.trim_start().starts_with('#')) {
+        for row in line.split(';') {
+            let values = row
+                .split(|c: char| c == ',' || c.is_whitespace())
+                .filter(|v| !v.is_empty())
+                .map(|v| {
+                    v.parse::<f32>()
+                        .map_err(|_| format!("invalid {what} value: {v}"))
+                })
+                .collect::<Result<Vec<f32>, String>>()?;
+            if !values.is_empty() {
+                rows.push(values);
+            }
+        }
+    }
+    matrix_from_rows(rows, what)
+}
+
+/// Check that `rows` form a non-empty rectangle and flatten them.
+fn matrix_from_rows(rows: Vec<Vec<f32>>, what: &str) -> Result<(usize, usize, Vec<f32>), String> {
+    let width = rows.first().map_or(0, |r| r.len());
+    if width == 0 {
+        return Err(format!("{what} is empty"));
+    }
+    if rows.iter().any(|r| r.len() != width) {
+        return Err(format!("{what} rows must all have the same length"));
+    }
+    Ok((width, rows.len(), rows.concat()))
+}
+
+/// Correlate a 1D kernel with a plane along one axis; `anchor` is the tap
+/// aligned with the output sample.
+fn convolve_axis(
+    plane: &[f32],
+    width: u32,
+    height: u32,
+    taps: &[f32],
+    anchor: usize,
+    horizontal: bool,
+    border: BorderMode,
+) -> Vec<f32> {
+    assert_eq!(plane.len(), (width * height) as usize);
+    let (w, h) = (width as i32, height as i32);
+    let a = anchor as i32;
+    let fill = border.fill();
+    let mut out = vec![0.0f32; plane.len()];
+    for y in 0..h {
+        for x in 0..w {
+            let mut acc = 0.0f32;
+            for (k, &weight) in taps.iter().enumerate() {
+                let d = k as i32 - a;
+                let sample = if horizontal {
+                    border
+                        .index(x + d, w)
+                        .map(|ix| plane[y as usize * w as usize + ix])
+                } else {
+                    border
+                        .index(y + d, h)
+                        .map(|iy| plane[iy * w as usize + x as usize])
+                };
+                acc += sample.unwrap_or(fill) * weight;
+            }
+            out[(y * w + x) as usize] = acc;
+        }
+    }
+    out
+}
+
+/// Convolve a row-major `f32` plane with a 2D kernel.
+///
+/// Separable kernels are applied as a horizontal then a vertical 1D pass.
+pub fn convolve_plane_f32(
+    plane: &[f32],
+    width: u32,
+    height: u32,
+    kernel: &Kernel,
+    border: BorderMode,
+) -> Vec<f32> {
+    assert_eq!(plane.len(), (width * height) as usize);
+    let (ax, ay) = kernel.anchor;
+    if let Some((horizontal, vertical)) = kernel.separate() {
+        let tmp = convolve_axis(plane, width, height, &horizontal, ax, true, border);
+        // Rows outside the image have already been through the horizontal
+        // pass in the 2D formulation, so a constant fill scales with its sum.
+        let vertical_border = match border {
+            BorderMode::Constant(v) => BorderMode::Constant(v * horizontal.iter().sum::<f32>()),
+            other => other,
+        };
+        return convolve_axis(&tmp, width, height, &vertical, ay, false, vertical_border);
+    }
+
+    let (w, h) = (width as i32, height as i32);
+    let fill = border.fill();
+    let mut out = vec![0.0f32; plane.len()];
+    for y in 0..h {
+        for x in 0..w {
+            let mut acc = 0.0f32;
+            for ky in 0..kernel.height {
+                let iy = border.index(y + ky as i32 - ay as i32, h);
+                for kx in 0..kernel.width {
+                    let ix = border.index(x + kx as i32 - ax as i32, w);
+                    let sample = match (ix, iy) {
+                        (Some(ix), Some(iy)) => plane[iy * w as usize + ix],
+                        _ => fill,
+                    };
+                    acc += sample * kernel.get(kx, ky);
+                }
+            }
+            out[(y * w + x) as usize] = acc;
+        }
+    }
+    out
+}
+
+/// Convolve every channel of an 8-bit image with `kernel`.
+///
+/// Works for any pixel type (`Luma`, `LumaA`, `Rgb`, `Rgba`, ...). The result is
+/// interleaved like the input: `width * height * P::CHANNEL_COUNT` values.
+pub fn convolve_f32<P>(
+    img: &ImageBuffer<P, Vec<u8>>,
+    kernel: &Kernel,
+    border: BorderMode,
+) -> Vec<f32>
+where
+    P: Pixel<Subpixel = u8>,
+{
+    let (w, h) = img.dimensions();
+    let channels = P::CHANNEL_COUNT as usize;
+    let raw = img.as_raw();
+    let mut out = vec![0.0f32; raw.len()];
+    for c in 0..channels {
+        let plane: Vec<f32> = raw
+            .iter()
+            .skip(c)
+            .step_by(channels)
+            .map(|&v| v as f32)
+            .collect();
+        let result = convolve_plane_f32(&plane, w, h, kernel, border);
+        for (i, v) in result.into_iter().enumerate() {
+            out[i * channels + c] = v;
+        }
+    }
+    out
+}
 
 /// Convolve a grayscale image with an odd-sized square kernel, returning f32 values.
 ///
-/// - `K` must be odd (3, 5, ...). Zero padding is used at the borders.
+/// - `K` must be odd (3, 5, ...).
 pub fn convolve_gray_f32<const K: usize>(
     img: &ImageBuffer<Luma<u8>, Vec<u8>>,
     kernel: &[[f32; K]; K],
+    border: BorderMode,
 ) -> Vec<f32> {
     assert!(K % 2 == 1, "Kernel size must be odd");
-    let (w, h) = img.dimensions();
-    let mut out = vec![0.0f32; (w * h) as usize];
-    let r = (K / 2) as i32;
+    convolve_f32(img, &Kernel::from_square(kernel), border)
+}
 
-    for y in 0..h as i32 {
-        for x in 0..w as i32 {
-            let mut acc = 0.0f32;
-            for ky in 0..K as i32 {
-                for kx in 0..K as i32 {
-                    let ix = x + kx - r;
-                    let iy = y + ky - r;
-                    // Retrieves the pixel value at the specified coordinates (`ix`, `iy`) from the image.
-                    // If the coordinates are within the image bounds, returns the first channel value as `f32`.
-                    // If the coordinates are out of bounds, returns `0.0`.
-                    let v = if ix >= 0 && ix < w as i32 && iy >= 0 && iy < h as i32 {
-                        img.get_pixel(ix as u32, iy as u32)[0] as f32
-                    } else {
-                        0.0
-                    };
-                    acc += v * kernel[ky as usize][kx as usize];
-                }
+/// How gradient magnitudes are mapped onto the 0..=255 output range.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub enum Normalization {
+    /// Divide by the largest magnitude in the image.
+    #[default]
+    Max,
+    /// Divide by the given percentile (0..=100) of the magnitudes; stronger
+    /// responses saturate, so a few outliers no longer dim every other edge.
+    Percentile(f32),
+    /// Divide by a fixed magnitude, independent of the image content. The same
+    /// edge gets the same brightness in every frame.
+    Fixed(f32),
+    /// `ln(1 + m) / ln(1 + max)`: compresses strong edges so weak ones stay
+    /// visible.
+    Log,
+}
+
+/// Normalization, intensity and optional binary threshold for magnitude output.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct MagnitudeScale {
+    /// How magnitudes are mapped to `0.0..=1.0` before `intensity`.
+    pub normalization: Normalization,
+    /// Multiplier applied after normalization.
+    pub intensity: f32,
+    /// When set, output levels at or above this value become 255, the rest 0.
+    pub threshold: Option<u8>,
+}
+
+impl Default for MagnitudeScale {
+    fn default() -> Self {
+        Self::new(1.0)
+    }
+}
+
+impl MagnitudeScale {
+    /// Per-image max normalization with the given intensity and no threshold.
+    pub fn new(intensity: f32) -> Self {
+        Self {
+            normalization: Normalization::Max,
+            intensity,
+            threshold: None,
+        }
+    }
+
+    /// Normalize with `normalization` instead of the per-image maximum.
+    pub fn with_normalization(mut self, normalization: Normalization) -> Self {
+        self.normalization = normalization;
+        self
+    }
+
+    /// Binarize the output at `threshold` (0..=255), or keep gray levels
+    /// with `None`.
+    pub fn with_threshold(mut self, threshold: Option<u8>) -> Self {
+        self.threshold = threshold;
+        self
+    }
+
+    /// The magnitude that maps to full white (before `intensity`).
+    ///
+    /// Percentiles are taken over the nonzero magnitudes only, so line art and
+    /// mostly flat images, where nearly every pixel has no gradient, do not
+    /// get a reference of 0 and turn black.
+    pub fn reference(&self, magnitudes: &[f32]) -> f32 {
+        match self.normalization {
+            Normalization::Max | Normalization::Log => {
+                magnitudes.iter().copied().fold(0.0f32, f32::max)
+            }
+            Normalization::Percentile(p) => {
+                let nonzero: Vec<f32> = magnitudes.iter().copied().filter(|&m| m > 0.0).collect();
+                percentile(&nonzero, p)
             }
-            out[(y as u32 * w + x as u32) as usize] = acc;
+            Normalization::Fixed(scale) => scale.max(0.0),
         }
     }
-    out
+
+    /// Output level in 0.0..=255.0 for magnitude `m` given a `reference`
+    /// obtained from [`MagnitudeScale::reference`].
+    pub fn level(&self, m: f32, reference: f32) -> f32 {
+        let normalized = if reference <= 0.0 {
+            0.0
+        } else if self.normalization == Normalization::Log {
+            (m.max(0.0)).ln_1p() / reference.ln_1p()
+        } else {
+            m / reference
+        };
+        let level = (normalized * 255.0 * self.intensity.max(0.0)).clamp(0.0, 255.0);
+        match self.threshold {
+            Some(t) if level.round() >= t as f32 => 255.0,
+            Some(_) => 0.0,
+            None => level,
+        }
+    }
+
+    /// Map every magnitude to its output level.
+    pub fn levels(&self, magnitudes: &[f32]) -> Vec<f32> {
+        let reference = self.reference(magnitudes);
+        magnitudes
+            .iter()
+            .map(|&m| self.level(m, reference))
+            .collect()
+    }
+}
+
+/// The `p`-th percentile (0..=100, nearest rank) of `values`; 0 when empty.
+fn percentile(values: &[f32], p: f32) -> f32 {
+    if values.is_empty() {
+        return 0.0;
+    }
+    let mut sorted = values.to_vec();
+    let rank = ((p.clamp(0.0, 100.0) / 100.0) * (sorted.len() - 1) as f32).round() as usize;
+    let (_, value, _) = sorted.select_nth_unstable_by(rank, f32::total_cmp);
+    *value
 }
 
-/// Compute gradient magnitude and map to `Luma<u8>` with adjustable intensity.
+/// Compute gradient magnitude and map to `Luma<u8>` according to `scale`.
 ///
-/// The result is first normalized by the maximum magnitude, then multiplied by
-/// `intensity` before clamping to 0..=255. Values >1 brighten edges; <1 dims them.
+/// The magnitude is normalized as selected by `scale.normalization`, then
+/// multiplied by `scale.intensity` before clamping to 0..=255 and applying the
+/// optional binary threshold. Intensities >1 brighten edges; <1 dims them.
 pub fn magnitude_to_luma_u8_scaled(
     gx: &[f32],
     gy: &[f32],
     width: u32,
     height: u32,
-    intensity: f32,
+    scale: MagnitudeScale,
 ) -> ImageBuffer<Luma<u8>, Vec<u8>> {
     assert_eq!(gx.len(), gy.len());
-    let mut mag = vec![0.0f32; gx.len()];
-    let mut max_mag = 0.0f32;
-    for i in 0..gx.len() {
-        let m = (gx[i] * gx[i] + gy[i] * gy[i]).sqrt();
-        mag[i] = m;
-        if m > max_mag {
-            max_mag = m;
-        }
-    }
-    let base_scale = if max_mag > 0.0 { 255.0 / max_mag } else { 0.0 };
-    let scale = base_scale * intensity.max(0.0);
-    let mut out: ImageBuffer<Luma<u8>, Vec<u8>> = ImageBuffer::new(width, height);
-    for y in 0..height {
-        for x in 0..width {
-            let m = mag[(y * width + x) as usize];
-            let n = (m * scale).round().clamp(0.0, 255.0) as u8;
-            out.put_pixel(x, y, Luma([n]));
+    let mag: Vec<f32> = gx
+        .iter()
+        .zip(gy)
+        .map(|(x, y)| (x * x + y * y).sqrt())
+        .collect();
+    magnitude_to_luma_u8(&mag, width, height, scale)
+}
+
+/// Map a precomputed, non-negative magnitude to `Luma<u8>` according to
+/// `scale`, like [`magnitude_to_luma_u8_scaled`] without the gradient pair.
+pub fn magnitude_to_luma_u8(
+    magnitude: &[f32],
+    width: u32,
+    height: u32,
+    scale: MagnitudeScale,
+) -> ImageBuffer<Luma<u8>, Vec<u8>> {
+    let levels = scale.levels(magnitude);
+    ImageBuffer::from_fn(width, height, |x, y| {
+        Luma([levels[(y * width + x) as usize].round() as u8])
+    })
+}
+
+/// Gaussian blur of a plane (separable passes).
+pub fn gaussian_blur_f32(
+    plane: &[f32],
+    width: u32,
+    height: u32,
+    sigma: f32,
+    border: BorderMode,
+) -> Vec<f32> {
+    let k = gaussian_kernel_1d(sigma);
+    convolve_plane_f32(
+        plane,
+        width,
+        height,
+        &Kernel::from_separable(&k, &k),
+        border,
+    )
+}
+
+/// Difference of Gaussians: the plane blurred with `sigma1` minus the plane
+/// blurred with `sigma2` (usually `sigma2 > sigma1`).
+pub fn difference_of_gaussians_f32(
+    plane: &[f32],
+    width: u32,
+    height: u32,
+    sigma1: f32,
+    sigma2: f32,
+    border: BorderMode,
+) -> Vec<f32> {
+    let narrow = gaussian_blur_f32(plane, width, height, sigma1, border);
+    let wide = gaussian_blur_f32(plane, width, height, sigma2, border);
+    narrow.iter().zip(&wide).map(|(a, b)| a - b).collect()
+}
+
+/// Normalized 1D Gaussian kernel with radius `ceil(3 * sigma)`.
+pub fn gaussian_kernel_1d(sigma: f32) -> Vec<f32> {
+    assert!(sigma > 0.0, "Sigma must be positive");
+    let r = (3.0 * sigma).ceil() as i32;
+    let mut kernel: Vec<f32> = (-r..=r)
+        .map(|i| (-(i * i) as f32 / (2.0 * sigma * sigma)).exp())
+        .collect();
+    let sum: f32 = kernel.iter().sum();
+    kernel.iter_mut().for_each(|v| *v /= sum);
+    kernel
+}
+
+/// Normalized 1D box kernel of length `2 * radius + 1`.
+pub fn box_kernel_1d(radius: u32) -> Vec<f32> {
+    let len = 2 * radius as usize + 1;
+    vec![1.0 / len as f32; len]
+}
+
+/// Box-blur a plane along one axis with a running sum: O(1) per pixel,
+/// independent of `radius`.
+pub fn box_blur_running_sum_f32(
+    plane: &[f32],
+    width: u32,
+    height: u32,
+    radius: u32,
+    horizontal: bool,
+    border: BorderMode,
+) -> Vec<f32> {
+    assert_eq!(plane.len(), (width * height) as usize);
+    let (w, h) = (width as usize, height as usize);
+    let (lines, len) = if horizontal { (h, w) } else { (w, h) };
+    let fill = border.fill();
+    let sample = |line: usize, i: i32| {
+        border
+            .index(i, len as i32)
+            .map(|i| {
+                if horizontal {
+                    plane[line * w + i]
+                } else {
+                    plane[i * w + line]
+                }
+            })
+            .unwrap_or(fill)
+    };
+    let r = radius as i32;
+    let norm = 1.0 / (2 * r + 1) as f32;
+    let mut out = vec![0.0f32; plane.len()];
+    for line in 0..lines {
+        // Window covers [i - r, i + r]; start with the samples for i = 0.
+        let mut acc: f32 = (-r..=r).map(|i| sample(line, i)).sum();
+        for i in 0..len {
+            let idx = if horizontal {
+                line * w + i
+            } else {
+                i * w + line
+            };
+            out[idx] = acc * norm;
+            acc += sample(line, i as i32 + r + 1) - sample(line, i as i32 - r);
         }
     }
     out
 }
 
+/// Split an RGB image into three `f32` channel planes.
+pub fn rgb_to_planes(img: &ImageBuffer<Rgb<u8>, Vec<u8>>) -> [Vec<f32>; 3] {
+    [0, 1, 2].map(|c| img.pixels().map(|p| p[c] as f32).collect())
+}
+
+/// Recombine three `f32` channel planes into an RGB image, rounding and
+/// clamping to 0..=255.
+pub fn planes_to_rgb(
+    planes: &[Vec<f32>; 3],
+    width: u32,
+    height: u32,
+) -> ImageBuffer<Rgb<u8>, Vec<u8>> {
+    ImageBuffer::from_fn(width, height, |x, y| {
+        let i = (y * width + x) as usize;
+        Rgb([0, 1, 2].map(|c| planes[c][i].round().clamp(0.0, 255.0) as u8))
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -81,11 +709,226 @@ mod tests {
         let img: ImageBuffer<Luma<u8>, Vec<u8>> =
             ImageBuffer::from_fn(3, 3, |x, y| Luma([x as u8 + y as u8]));
         const K: [[f32; 3]; 3] = [[0.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 0.0]];
-        let out = convolve_gray_f32::<3>(&img, &K);
+        let out = convolve_gray_f32::<3>(&img, &K, BorderMode::Zero);
         for y in 0..3 {
             for x in 0..3 {
                 assert_eq!(out[(y * 3 + x) as usize].round() as u8, x as u8 + y as u8);
             }
         }
     }
+
+    #[test]
+    fn test_running_sum_matches_box_kernel() {
+        let (w, h) = (7u32, 5u32);
+        let plane: Vec<f32> = (0..w * h).map(|i| (i * 37 % 11) as f32).collect();
+        for horizontal in [true, false] {
+            for border in [
+                BorderMode::Zero,
+                BorderMode::Constant(3.0),
+                BorderMode::Reflect,
+                BorderMode::Wrap,
+            ] {
+                let fast = box_blur_running_sum_f32(&plane, w, h, 2, horizontal, border);
+                let k = box_kernel_1d(2);
+                let kernel = if horizontal {
+                    Kernel::new(k.len(), 1, k)
+                } else {
+                    Kernel::new(1, k.len(), k)
+                };
+                let slow = convolve_plane_f32(&plane, w, h, &kernel, border);
+                for (a, b) in fast.iter().zip(&slow) {
+                    assert!((a - b).abs() < 1e-4);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_second_derivative_kernels_sum_to_zero() {
+        for kernel in [
+            Kernel::laplacian(false),
+            Kernel::laplacian(true),
+            Kernel::laplacian_of_gaussian(1.4),
+        ] {
+            assert!(kernel.sum().abs() < 1e-4);
+            assert!(kernel.separate().is_none());
+            let (cx, cy) = kernel.anchor();
+            assert!(kernel.get(cx, cy) < 0.0);
+        }
+        assert_eq!(Kernel::laplacian_of_gaussian(1.0).width(), 7);
+    }
+
+    #[test]
+    fn test_parse_kernel() {
+        let k: Kernel = "0,-1,0;-1,5,-1;0,-1,0".parse().unwrap();
+        assert_eq!((k.width(), k.height(), k.anchor()), (3, 3, (1, 1)));
+        assert_eq!(k.get(1, 1), 5.0);
+        let k: Kernel = "# emboss\n-1 0\n0 1\n".parse().unwrap();
+        assert_eq!((k.width(), k.height()), (2, 2));
+        assert!("1,2;3".parse::<Kernel>().is_err());
+        assert!("".parse::<Kernel>().is_err());
+        assert!("1,x".parse::<Kernel>().is_err());
+    }
+
+    #[test]
+    fn test_kernel_from_json() {
+        let json = |text: &str| Kernel::from_json(&serde_json::from_str(text).unwrap());
+        let k = json("[[0, -1, 0], [-1, 5, -1], [0, -1, 0]]").unwrap();
+        assert_eq!(k, "0,-1,0;-1,5,-1;0,-1,0".parse().unwrap());
+        let k = json(r#"{"kernel": [[1, 2], [3, 4]], "anchor": [0, 1], "bias": 9}"#).unwrap();
+        assert_eq!((k.width(), k.height(), k.anchor()), (2, 2, (0, 1)));
+        assert_eq!(k.get(1, 1), 4.0);
+        assert!(json("[[1, 2], [3]]").is_err());
+        assert!(json("[[1, \"x\"]]").is_err());
+        assert!(json("[1, 2]").is_err());
+        assert!(json("[[]]").is_err());
+        assert!(json(r#"{"weights": [[1]]}"#).is_err());
+        assert!(json(r#"{"kernel": [[1, 2]], "anchor": [2, 0]}"#).is_err());
+        assert!(json(r#"{"kernel": [[1, 2]], "anchor": [0, -1]}"#).is_err());
+        assert!(json(r#"{"kernel": [[1, 2]], "anchor": [0]}"#).is_err());
+    }
+
+    #[test]
+    fn test_border_index() {
+        let map = |mode: BorderMode| (-3..11).map(|i| mode.index(i, 8)).collect::<Vec<_>>();
+        let some = |v: &[usize]| v.iter().map(|&i| Some(i)).collect::<Vec<_>>();
+        assert_eq!(
+            map(BorderMode::Clamp),
+            some(&[0, 0, 0, 0, 1, 2, 3, 4, 5, 6, 7, 7, 7, 7])
+        );
+        assert_eq!(
+            map(BorderMode::Reflect),
+            some(&[2, 1, 0, 0, 1, 2, 3, 4, 5, 6, 7, 7, 6, 5])
+        );
+        assert_eq!(
+            map(BorderMode::Reflect101),
+            some(&[3, 2, 1, 0, 1, 2, 3, 4, 5, 6, 7, 6, 5, 4])
+        );
+        assert_eq!(
+            map(BorderMode::Wrap),
+            some(&[5, 6, 7, 0, 1, 2, 3, 4, 5, 6, 7, 0, 1, 2])
+        );
+        assert_eq!(BorderMode::Zero.index(-1, 8), None);
+        assert_eq!(BorderMode::Reflect101.index(-2, 1), Some(0));
+    }
+
+    #[test]
+    fn test_constant_border_matches_between_paths() {
+        let (w, h) = (4u32, 3u32);
+        let plane = vec![10.0f32; (w * h) as usize];
+        let border = BorderMode::Constant(40.0);
+        let separable = Kernel::new(3, 3, vec![1.0; 9]).normalized();
+        let direct = Kernel::new(3, 3, vec![1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 2.0]);
+        assert!(separable.separate().is_some() && direct.separate().is_none());
+        let a = convolve_plane_f32(&plane, w, h, &separable, border);
+        let b = convolve_plane_f32(&plane, w, h, &direct, border);
+        // Top-left corner: 5 of 9 taps fall outside
+        assert!((a[0] - (4.0 * 10.0 + 5.0 * 40.0) / 9.0).abs() < 1e-4);
+        assert!((b[0] - (3.0 * 10.0 + 5.0 * 40.0 + 2.0 * 10.0)).abs() < 1e-4);
+        // A flat image keeps its value everywhere with clamp or reflect borders
+        for border in [
+            BorderMode::Clamp,
+            BorderMode::Reflect,
+            BorderMode::Reflect101,
+        ] {
+            let out = convolve_plane_f32(&plane, w, h, &separable, border);
+            assert!(out.iter().all(|v| (v - 10.0).abs() < 1e-4));
+        }
+    }
+
+    #[test]
+    fn test_gaussian_kernel_is_normalized() {
+        let k = gaussian_kernel_1d(1.5);
+        assert_eq!(k.len(), 11);
+        assert!((k.iter().sum::<f32>() - 1.0).abs() < 1e-5);
+        assert!(k[5] > k[4] && (k[4] - k[6]).abs() < 1e-7);
+    }
+
+    #[test]
+    fn test_kernel_separation() {
+        const SOBEL_X: [[f32; 3]; 3] = [[-1.0, 0.0, 1.0], [-2.0, 0.0, 2.0], [-1.0, 0.0, 1.0]];
+        let sobel = Kernel::from_square(&SOBEL_X);
+        let (h, v) = sobel.separate().expect("Sobel is separable");
+        assert_eq!(Kernel::from_separable(&h, &v), sobel);
+
+        let laplacian = Kernel::new(3, 3, vec![0.0, 1.0, 0.0, 1.0, -4.0, 1.0, 0.0, 1.0, 0.0]);
+        assert!(laplacian.separate().is_none());
+    }
+
+    #[test]
+    fn test_separable_and_direct_paths_agree() {
+        let (w, h) = (6u32, 5u32);
+        let plane: Vec<f32> = (0..w * h).map(|i| (i * 13 % 7) as f32).collect();
+        let kernel = Kernel::from_separable(&[1.0, 2.0, 1.0], &[1.0, -1.0]).with_anchor(1, 0);
+        let fast = convolve_plane_f32(&plane, w, h, &kernel, BorderMode::Zero);
+        // Perturb one tap by a negligible amount to force the direct path
+        let mut weights = kernel.weights().to_vec();
+        weights[0] += 1e-3;
+        let direct = Kernel::new(3, 2, weights).with_anchor(1, 0);
+        assert!(direct.separate().is_none());
+        let slow = convolve_plane_f32(&plane, w, h, &direct, BorderMode::Zero);
+        for (a, b) in fast.iter().zip(&slow) {
+            assert!((a - b).abs() < 0.05);
+        }
+    }
+
+    #[test]
+    fn test_convolve_rgb_and_anchor() {
+        let img: ImageBuffer<Rgb<u8>, Vec<u8>> =
+            ImageBuffer::from_fn(4, 2, |x, y| Rgb([x as u8, y as u8, 10]));
+        // 1x2 kernel picking the pixel to the right (anchor on the left tap)
+        let shift = Kernel::new(2, 1, vec![0.0, 1.0]).with_anchor(0, 0);
+        let out = convolve_f32(&img, &shift, BorderMode::Zero);
+        assert_eq!(out.len(), 4 * 2 * 3);
+        assert_eq!(&out[0..3], &[1.0, 0.0, 10.0]);
+        // Zero padding past the right border
+        assert_eq!(&out[9..12], &[0.0, 0.0, 0.0]);
+        assert_eq!(Kernel::new(2, 2, vec![1.0; 4]).normalized().sum(), 1.0);
+    }
+
+    #[test]
+    fn test_magnitude_normalization_modes() {
+        // One outlier among uniform magnitudes
+        let mut mags = vec![10.0f32; 99];
+        mags.push(1000.0);
+        let max = MagnitudeScale::new(1.0).levels(&mags);
+        assert!((max[0] - 2.55).abs() < 1e-3);
+        assert_eq!(max[99], 255.0);
+        // The 99th percentile ignores the outlier, which saturates
+        let pct = MagnitudeScale::new(1.0)
+            .with_normalization(Normalization::Percentile(99.0))
+            .levels(&mags);
+        assert_eq!(pct[0], 255.0);
+        assert_eq!(pct[99], 255.0);
+        // A fixed scale does not depend on the image content
+        let fixed = MagnitudeScale::new(1.0).with_normalization(Normalization::Fixed(20.0));
+        assert_eq!(fixed.levels(&mags)[0], 127.5);
+        assert_eq!(fixed.levels(&[10.0])[0], 127.5);
+        // Log compression lifts weak magnitudes
+        let log = MagnitudeScale::new(1.0)
+            .with_normalization(Normalization::Log)
+            .levels(&mags);
+        assert!(log[0] > 80.0 && log[99] == 255.0);
+        // Thresholding binarizes the scaled levels
+        let binary = fixed.with_threshold(Some(128)).levels(&[9.9, 10.1, 40.0]);
+        assert_eq!(binary, vec![0.0, 255.0, 255.0]);
+        assert_eq!(MagnitudeScale::new(1.0).levels(&[0.0, 0.0]), vec![0.0, 0.0]);
+    }
+
+    #[test]
+    fn test_percentile_ignores_flat_pixels() {
+        // A single edge on a flat 10x10 field: 98% of the magnitudes are 0
+        let mut mags = vec![0.0f32; 100];
+        mags[44] = 50.0;
+        mags[45] = 40.0;
+        let pct = MagnitudeScale::new(1.0).with_normalization(Normalization::Percentile(99.0));
+        let levels = pct.levels(&mags);
+        assert_eq!(levels[44], 255.0);
+        assert_eq!(levels[45], 204.0);
+        assert!(levels
+            .iter()
+            .enumerate()
+            .all(|(i, &l)| l == 0.0 || i == 44 || i == 45));
+        assert!(pct.levels(&[0.0; 4]).iter().all(|&l| l == 0.0));
+    }
 }
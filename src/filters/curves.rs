@@ -0,0 +1,286 @@
+//! Tone curves through control points, compiled into lookup tables.
+//!
+//! A curve is a piecewise cubic Hermite spline through its points; the two
+//! interpolation modes differ only in how the tangents are chosen. Outside
+//! the first and last point the curve is flat.
+use std::str::FromStr;
+
+use super::tone::{lut_from_fn, ToneCurve8};
+
+/// How the curve between control points is interpolated.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub enum CurveInterpolation {
+    /// Fritsch-Carlson monotone cubic: never overshoots the control points,
+    /// so a rising set of points always gives a rising curve.
+    #[default]
+    MonotoneCubic,
+    /// Catmull-Rom spline: smoother, but may overshoot between points that
+    /// change direction.
+    CatmullRom,
+}
+
+/// Control points `(input, output)` in `0.0..=255.0`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Curve {
+    points: Vec<(f32, f32)>,
+}
+
+impl Default for Curve {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+impl Curve {
+    /// Create a curve from control points, in any order.
+    ///
+    /// Points are clamped to `0.0..=255.0` and sorted by input; when two
+    /// points share an input, the last one wins.
+    pub fn new(points: &[(f32, f32)]) -> Self {
+        let mut points: Vec<(f32, f32)> = points
+            .iter()
+            .map(|&(x, y)| (x.clamp(0.0, 255.0), y.clamp(0.0, 255.0)))
+            .collect();
+        points.sort_by(|a, b| a.0.total_cmp(&b.0));
+        let mut deduped: Vec<(f32, f32)> = Vec::with_capacity(points.len());
+        for p in points {
+            match deduped.last_mut() {
+                Some(last) if last.0 == p.0 => *last = p,
+                _ => deduped.push(p),
+            }
+        }
+        Self { points: deduped }
+    }
+
+    /// The straight line from `(0, 0)` to `(255, 255)`.
+    pub fn identity() -> Self {
+        Self::new(&[(0.0, 0.0), (255.0, 255.0)])
+    }
+
+    /// Sorted control points.
+    pub fn points(&self) -> &[(f32, f32)] {
+        &self.points
+    }
+
+    /// Build a curve from JSON: an array of `[x, y]` pairs such as
+    /// `[[0, 0], [128, 150], [255, 255]]`, or a string in the inline format.
+    pub fn from_json(value: &serde_json::Value) -> Result<Self, String> {
+        if let Some(text) = value.as_str() {
+            return text.parse();
+        }
+        let points = value
+            .as_array()
+            .ok_or("curve must be an array of [x, y] pairs")?
+            .iter()
+            .map(|point| {
+                let xy = point
+                    .as_array()
+                    .ok_or(format!("curve points must be [x, y] pairs: {point}"))?
+                    .iter()
+                    .map(|v| {
+                        v.as_f64()
+                            .map(|v| v as f32)
+                            .ok_or(format!("curve values must be numbers: {point}"))
+                    })
+                    .collect::<Result<Vec<f32>, String>>()?;
+                match xy[..] {
+                    [x, y] if (0.0..=255.0).contains(&x) && (0.0..=255.0).contains(&y) => {
+                        Ok((x, y))
+                    }
+                    [_, _] => Err(format!("curve point out of range 0..255: {point}")),
+                    _ => Err(format!("curve points must be [x, y] pairs: {point}")),
+                }
+            })
+            .collect::<Result<Vec<(f32, f32)>, String>>()?;
+        if points.is_empty() {
+            return Err("curve has no points".into());
+        }
+        Ok(Self::new(&points))
+    }
+
+    /// Hermite tangents at each control point.
+    fn tangents(&self, interpolation: CurveInterpolation) -> Vec<f32> {
+        let p = &self.points;
+        let n = p.len();
+        let secants: Vec<f32> = p
+            .windows(2)
+            .map(|w| (w[1].1 - w[0].1) / (w[1].0 - w[0].0))
+            .collect();
+        let mut m = vec![0.0f32; n];
+        m[0] = secants[0];
+        m[n - 1] = secants[n - 2];
+        for k in 1..n - 1 {
+            m[k] = match interpolation {
+                CurveInterpolation::CatmullRom => {
+                    (p[k + 1].1 - p[k - 1].1) / (p[k + 1].0 - p[k - 1].0)
+                }
+                CurveInterpolation::MonotoneCubic => {
+                    if secants[k - 1] * secants[k] <= 0.0 {
+                        0.0
+                    } else {
+                        (secants[k - 1] + secants[k]) / 2.0
+                    }
+                }
+            };
+        }
+        if interpolation == CurveInterpolation::MonotoneCubic {
+            // Fritsch-Carlson: keep (alpha, beta) inside the circle of radius 3
+            for (k, &d) in secants.iter().enumerate() {
+                if d == 0.0 {
+                    m[k] = 0.0;
+                    m[k + 1] = 0.0;
+                    continue;
+                }
+                let (a, b) = (m[k] / d, m[k + 1] / d);
+                let s = a * a + b * b;
+                if s > 9.0 {
+                    let t = 3.0 / s.sqrt();
+                    m[k] = t * a * d;
+                    m[k + 1] = t * b * d;
+                }
+            }
+        }
+        m
+    }
+
+    /// Compile into a lookup table.
+    pub fn lut(&self, interpolation: CurveInterpolation) -> ToneCurve8 {
+        let p = &self.points;
+        match p.len() {
+            0 => return lut_from_fn(|v| v),
+            1 => return lut_from_fn(|_| p[0].1 as f64),
+            _ => {}
+        }
+        let m = self.tangents(interpolation);
+        lut_from_fn(|v| {
+            let v = v as f32;
+            let (first, last) = (p[0], p[p.len() - 1]);
+            if v <= first.0 {
+                return first.1 as f64;
+            }
+            if v >= last.0 {
+                return last.1 as f64;
+            }
+            let k = p.partition_point(|q| q.0 <= v) - 1;
+            let (x0, y0) = p[k];
+            let (x1, y1) = p[k + 1];
+            let h = x1 - x0;
+            let t = (v - x0) / h;
+            let (t2, t3) = (t * t, t * t * t);
+            let y = (2.0 * t3 - 3.0 * t2 + 1.0) * y0
+                + (t3 - 2.0 * t2 + t) * h * m[k]
+                + (-2.0 * t3 + 3.0 * t2) * y1
+                + (t3 - t2) * h * m[k + 1];
+            y as f64
+        })
+    }
+}
+
+impl FromStr for Curve {
+    type Err = String;
+
+    /// Parse control points such as `"0,0 64,50 192,210 255,255"`.
+    ///
+    /// Points are separated by whitespace or `;`, coordinates by `,` or `:`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let points = s
+            .split(|c: char| c == ';' || c.is_whitespace())
+            .filter(|p| !p.is_empty())
+            .map(|p| {
+                let xy = p
+                    .split([',', ':'])
+                    .map(|v| {
+                        v.trim()
+                            .parse::<f32>()
+                            .map_err(|_| format!("invalid curve value: {v}"))
+                    })
+                    .collect::<Result<Vec<f32>, String>>()?;
+                match xy[..] {
+                    [x, y] if (0.0..=255.0).contains(&x) && (0.0..=255.0).contains(&y) => {
+                        Ok((x, y))
+                    }
+                    [_, _] => Err(format!("curve point out of range 0..255: {p}")),
+                    _ => Err(format!("curve points must be x,y pairs: {p}")),
+                }
+            })
+            .collect::<Result<Vec<(f32, f32)>, String>>()?;
+        if points.is_empty() {
+            return Err("curve has no points".into());
+        }
+        Ok(Self::new(&points))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INTERPOLATIONS: [CurveInterpolation; 2] = [
+        CurveInterpolation::MonotoneCubic,
+        CurveInterpolation::CatmullRom,
+    ];
+
+    #[test]
+    fn test_identity_and_control_points() {
+        for interpolation in INTERPOLATIONS {
+            let lut = Curve::identity().lut(interpolation);
+            assert!(lut.iter().enumerate().all(|(i, &v)| v == i as u8));
+            let curve = Curve::new(&[(255.0, 230.0), (0.0, 20.0), (64.0, 40.0), (192.0, 220.0)]);
+            let lut = curve.lut(interpolation);
+            assert_eq!((lut[0], lut[64], lut[192], lut[255]), (20, 40, 220, 230));
+        }
+        // Flat outside the outermost points
+        let lut = Curve::new(&[(50.0, 10.0), (200.0, 240.0)]).lut(CurveInterpolation::default());
+        assert_eq!((lut[0], lut[50], lut[200], lut[255]), (10, 10, 240, 240));
+        assert_eq!(
+            Curve::new(&[(10.0, 99.0)]).lut(CurveInterpolation::default())[200],
+            99
+        );
+    }
+
+    #[test]
+    fn test_monotone_cubic_does_not_overshoot() {
+        // A steep step: Catmull-Rom rings around it, monotone cubic does not
+        let curve = Curve::new(&[(0.0, 50.0), (100.0, 60.0), (110.0, 200.0), (255.0, 210.0)]);
+        let monotone = curve.lut(CurveInterpolation::MonotoneCubic);
+        assert!(monotone.windows(2).all(|w| w[0] <= w[1]));
+        let catmull = curve.lut(CurveInterpolation::CatmullRom);
+        assert!(catmull.windows(2).any(|w| w[0] > w[1]));
+    }
+
+    #[test]
+    fn test_parse_curve() {
+        let curve: Curve = "0,0 128:150;255,255".parse().unwrap();
+        assert_eq!(
+            curve.points(),
+            &[(0.0, 0.0), (128.0, 150.0), (255.0, 255.0)]
+        );
+        assert!("0,0 300,255".parse::<Curve>().is_err());
+        assert!("0,0,1".parse::<Curve>().is_err());
+        assert!("a,b".parse::<Curve>().is_err());
+        assert!("  ".parse::<Curve>().is_err());
+    }
+
+    #[test]
+    fn test_curve_from_json() {
+        let json = |text: &str| Curve::from_json(&serde_json::from_str(text).unwrap());
+        let curve = json("[[0, 0], [128, 150.5], [255, 255]]").unwrap();
+        assert_eq!(
+            curve.points(),
+            &[(0.0, 0.0), (128.0, 150.5), (255.0, 255.0)]
+        );
+        assert_eq!(
+            json("\"0,20 255,235\"").unwrap().points(),
+            &[(0.0, 20.0), (255.0, 235.0)]
+        );
+        // Non-numeric entries and points that are not pairs are errors, not dropped
+        assert!(json("[[0, \"x\", 10]]").is_err());
+        assert!(json("[[0, \"x\"]]").is_err());
+        assert!(json("[[0, 0], [255]]").is_err());
+        assert!(json("[[0, 0, 1]]").is_err());
+        assert!(json("[[0, 300]]").is_err());
+        assert!(json("[0, 0]").is_err());
+        assert!(json("[]").is_err());
+        assert!(json("{}").is_err());
+    }
+}
@@ -1,31 +1,269 @@
-//! Ordered dithering (Bayer matrix) operating on grayscale images.
+//! Ordered (threshold matrix) and error-diffusion dithering on grayscale images.
 //!
 //! Input and output are `Luma<u8>` buffers; output pixels are 0 or 255.
 use image::{ImageBuffer, Luma};
 
-const PATTERN_SIZE: u32 = 4;
-const THRESHOLD_MULTIPLIER: u8 = 16;
-const THRESHOLD_OFFSET: u8 = 8;
+use super::ordered::ThresholdMap;
 
-/// Perform halftoning using a 4x4 Bayer matrix on a grayscale image.
-pub fn halftoning(img: &ImageBuffer<Luma<u8>, Vec<u8>>) -> ImageBuffer<Luma<u8>, Vec<u8>> {
+/// Perform ordered-dither halftoning on a grayscale image.
+///
+/// A pixel becomes white when its value reaches the tiled threshold at its
+/// position (thresholds are scaled from `(0, 1)` to `0..256`).
+pub fn halftoning(
+    img: &ImageBuffer<Luma<u8>, Vec<u8>>,
+    map: &ThresholdMap,
+) -> ImageBuffer<Luma<u8>, Vec<u8>> {
     let (width, height) = img.dimensions();
-
-    let pattern: [[u8; 4]; 4] = [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
-
     let mut imgbuf: ImageBuffer<Luma<u8>, Vec<u8>> = ImageBuffer::new(width, height);
 
     for (x, y, pixel) in imgbuf.enumerate_pixels_mut() {
-        let threshold: u8 = pattern[(x % PATTERN_SIZE) as usize][(y % PATTERN_SIZE) as usize]
-            * THRESHOLD_MULTIPLIER
-            + THRESHOLD_OFFSET;
+        let threshold: f32 = map.threshold(x, y) * 256.0;
         let img_pixel: &Luma<u8> = img.get_pixel(x, y);
-        let val = img_pixel[0];
+        let val = img_pixel[0] as f32;
         pixel[0] = if val >= threshold { 255 } else { 0 };
     }
     imgbuf
 }
 
+/// Dithering algorithm used to reduce an image to black and white.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DitherMethod {
+    /// Ordered dithering with a tiled threshold matrix (Bayer by default).
+    Ordered,
+    /// Floyd–Steinberg error diffusion (4 neighbours).
+    FloydSteinberg,
+    /// Jarvis–Judice–Ninke error diffusion (12 neighbours).
+    JarvisJudiceNinke,
+    /// Stucki error diffusion (12 neighbours).
+    Stucki,
+    /// Atkinson error diffusion; only 3/4 of the error is propagated.
+    Atkinson,
+    /// Burkes error diffusion (7 neighbours, two rows).
+    Burkes,
+    /// Sierra (three-row) error diffusion.
+    Sierra,
+    /// Two-row Sierra error diffusion.
+    SierraTwoRow,
+    /// Sierra Lite error diffusion (3 neighbours).
+    SierraLite,
+}
+
+/// An error-diffusion kernel: `(dx, dy, weight)` taps and their common divisor.
+///
+/// Offsets are given for left-to-right scanning; `dx` is mirrored on
+/// right-to-left rows when serpentine scanning is enabled.
+pub struct DiffusionKernel {
+    taps: &'static [(i32, i32, f32)],
+    divisor: f32,
+}
+
+const FLOYD_STEINBERG: DiffusionKernel = DiffusionKernel {
+    taps: &[(1, 0, 7.0), (-1, 1, 3.0), (0, 1, 5.0), (1, 1, 1.0)],
+    divisor: 16.0,
+};
+
+const JARVIS_JUDICE_NINKE: DiffusionKernel = DiffusionKernel {
+    taps: &[
+        (1, 0, 7.0),
+        (2, 0, 5.0),
+        (-2, 1, 3.0),
+        (-1, 1, 5.0),
+        (0, 1, 7.0),
+        (1, 1, 5.0),
+        (2, 1, 3.0),
+        (-2, 2, 1.0),
+        (-1, 2, 3.0),
+        (0, 2, 5.0),
+        (1, 2, 3.0),
+        (2, 2, 1.0),
+    ],
+    divisor: 48.0,
+};
+
+const STUCKI: DiffusionKernel = DiffusionKernel {
+    taps: &[
+        (1, 0, 8.0),
+        (2, 0, 4.0),
+        (-2, 1, 2.0),
+        (-1, 1, 4.0),
+        (0, 1, 8.0),
+        (1, 1, 4.0),
+        (2, 1, 2.0),
+        (-2, 2, 1.0),
+        (-1, 2, 2.0),
+        (0, 2, 4.0),
+        (1, 2, 2.0),
+        (2, 2, 1.0),
+    ],
+    divisor: 42.0,
+};
+
+const ATKINSON: DiffusionKernel = DiffusionKernel {
+    taps: &[
+        (1, 0, 1.0),
+        (2, 0, 1.0),
+        (-1, 1, 1.0),
+        (0, 1, 1.0),
+        (1, 1, 1.0),
+        (0, 2, 1.0),
+    ],
+    divisor: 8.0,
+};
+
+const BURKES: DiffusionKernel = DiffusionKernel {
+    taps: &[
+        (1, 0, 8.0),
+        (2, 0, 4.0),
+        (-2, 1, 2.0),
+        (-1, 1, 4.0),
+        (0, 1, 8.0),
+        (1, 1, 4.0),
+        (2, 1, 2.0),
+    ],
+    divisor: 32.0,
+};
+
+const SIERRA: DiffusionKernel = DiffusionKernel {
+    taps: &[
+        (1, 0, 5.0),
+        (2, 0, 3.0),
+        (-2, 1, 2.0),
+        (-1, 1, 4.0),
+        (0, 1, 5.0),
+        (1, 1, 4.0),
+        (2, 1, 2.0),
+        (-1, 2, 2.0),
+        (0, 2, 3.0),
+        (1, 2, 2.0),
+    ],
+    divisor: 32.0,
+};
+
+const SIERRA_TWO_ROW: DiffusionKernel = DiffusionKernel {
+    taps: &[
+        (1, 0, 4.0),
+        (2, 0, 3.0),
+        (-2, 1, 1.0),
+        (-1, 1, 2.0),
+        (0, 1, 3.0),
+        (1, 1, 2.0),
+        (2, 1, 1.0),
+    ],
+    divisor: 16.0,
+};
+
+const SIERRA_LITE: DiffusionKernel = DiffusionKernel {
+    taps: &[(1, 0, 2.0), (-1, 1, 1.0), (0, 1, 1.0)],
+    divisor: 4.0,
+};
+
+impl DitherMethod {
+    /// Error-diffusion kernel for this method, or `None` for ordered dithering.
+    pub(crate) fn kernel(self) -> Option<&'static DiffusionKernel> {
+        match self {
+            DitherMethod::Ordered => None,
+            DitherMethod::FloydSteinberg => Some(&FLOYD_STEINBERG),
+            DitherMethod::JarvisJudiceNinke => Some(&JARVIS_JUDICE_NINKE),
+            DitherMethod::Stucki => Some(&STUCKI),
+            DitherMethod::Atkinson => Some(&ATKINSON),
+            DitherMethod::Burkes => Some(&BURKES),
+            DitherMethod::Sierra => Some(&SIERRA),
+            DitherMethod::SierraTwoRow => Some(&SIERRA_TWO_ROW),
+            DitherMethod::SierraLite => Some(&SIERRA_LITE),
+        }
+    }
+}
+
+/// Dither a grayscale image to black and white with the given method.
+///
+/// # Arguments
+/// * `img` - The input grayscale image
+/// * `method` - Dithering algorithm
+/// * `map` - Threshold matrix (ordered dithering only)
+/// * `serpentine` - Alternate scan direction per row (error diffusion only)
+/// * `strength` - Fraction of the quantization error to propagate, typically
+///   `0.0..=1.0` (error diffusion only)
+///
+/// # Returns
+/// Black-and-white image with pixel values 0 or 255
+pub fn dither(
+    img: &ImageBuffer<Luma<u8>, Vec<u8>>,
+    method: DitherMethod,
+    map: &ThresholdMap,
+    serpentine: bool,
+    strength: f32,
+) -> ImageBuffer<Luma<u8>, Vec<u8>> {
+    match method.kernel() {
+        None => halftoning(img, map),
+        Some(kernel) => error_diffusion(img, kernel, serpentine, strength),
+    }
+}
+
+/// Binarize a grayscale image while diffusing the quantization error to
+/// not-yet-visited neighbours.
+fn error_diffusion(
+    img: &ImageBuffer<Luma<u8>, Vec<u8>>,
+    kernel: &DiffusionKernel,
+    serpentine: bool,
+    strength: f32,
+) -> ImageBuffer<Luma<u8>, Vec<u8>> {
+    let (width, height) = img.dimensions();
+    let mut buf: Vec<[f32; 1]> = img.pixels().map(|p| [p[0] as f32]).collect();
+    diffuse(
+        &mut buf,
+        width,
+        height,
+        kernel,
+        serpentine,
+        strength,
+        |[v]| if v >= 128.0 { [255.0] } else { [0.0] },
+    );
+    ImageBuffer::from_fn(width, height, |x, y| {
+        Luma([buf[(y * width + x) as usize][0] as u8])
+    })
+}
+
+/// Generic error-diffusion pass over a row-major buffer of `C`-channel values.
+///
+/// Each value is replaced in place by `quantize(value)`, and the difference
+/// (scaled by `strength`) is spread to neighbours according to `kernel`.
+pub fn diffuse<const C: usize>(
+    buf: &mut [[f32; C]],
+    width: u32,
+    height: u32,
+    kernel: &DiffusionKernel,
+    serpentine: bool,
+    strength: f32,
+    mut quantize: impl FnMut([f32; C]) -> [f32; C],
+) {
+    let (w, h) = (width as i32, height as i32);
+    for y in 0..h {
+        let reverse = serpentine && y % 2 == 1;
+        for i in 0..w {
+            let x = if reverse { w - 1 - i } else { i };
+            let idx = (y * w + x) as usize;
+            let old = buf[idx];
+            let new = quantize(old);
+            buf[idx] = new;
+
+            let mut err = [0.0f32; C];
+            for c in 0..C {
+                err[c] = (old[c] - new[c]) * strength;
+            }
+            for &(dx, dy, weight) in kernel.taps {
+                let nx = if reverse { x - dx } else { x + dx };
+                let ny = y + dy;
+                if nx >= 0 && nx < w && ny < h {
+                    let target = &mut buf[(ny * w + nx) as usize];
+                    for c in 0..C {
+                        target[c] += err[c] * weight / kernel.divisor;
+                    }
+                }
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -39,7 +277,7 @@ mod tests {
     #[test]
     fn test_halftoning() {
         let img: ImageBuffer<Luma<u8>, Vec<u8>> = create_test_image();
-        let ht: ImageBuffer<Luma<u8>, Vec<u8>> = halftoning(&img);
+        let ht: ImageBuffer<Luma<u8>, Vec<u8>> = halftoning(&img, &ThresholdMap::default());
         assert_eq!(ht.dimensions(), (4, 4));
         // Ensure that each pixel has a value of 0 or 255
         for pixel in ht.pixels() {
@@ -47,4 +285,79 @@ mod tests {
             assert!(v == 0 || v == 255);
         }
     }
+
+    #[test]
+    fn test_error_diffusion_preserves_mean() {
+        // A flat mid-gray image should come out roughly half white
+        let img: ImageBuffer<Luma<u8>, Vec<u8>> = ImageBuffer::from_pixel(16, 16, Luma([128]));
+        for method in [
+            DitherMethod::FloydSteinberg,
+            DitherMethod::JarvisJudiceNinke,
+            DitherMethod::Stucki,
+            DitherMethod::Burkes,
+            DitherMethod::Sierra,
+            DitherMethod::SierraTwoRow,
+            DitherMethod::SierraLite,
+        ] {
+            let out = dither(&img, method, &ThresholdMap::default(), true, 1.0);
+            assert!(out.pixels().all(|p| p[0] == 0 || p[0] == 255));
+            let white = out.pixels().filter(|p| p[0] == 255).count();
+            assert!(
+                (112..=144).contains(&white),
+                "{:?} produced {} white pixels",
+                method,
+                white
+            );
+        }
+    }
+
+    #[test]
+    fn test_error_diffusion_zero_strength_thresholds() {
+        let img: ImageBuffer<Luma<u8>, Vec<u8>> =
+            ImageBuffer::from_fn(8, 1, |x, _| Luma([(x * 32) as u8]));
+        let out = dither(
+            &img,
+            DitherMethod::FloydSteinberg,
+            &ThresholdMap::default(),
+            false,
+            0.0,
+        );
+        for (x, _, p) in out.enumerate_pixels() {
+            let expected = if x * 32 >= 128 { 255 } else { 0 };
+            assert_eq!(p[0], expected);
+        }
+    }
+
+    /// The original 4x4 Bayer halftone, kept to pin the default output.
+    fn legacy_halftoning(img: &ImageBuffer<Luma<u8>, Vec<u8>>) -> ImageBuffer<Luma<u8>, Vec<u8>> {
+        let pattern: [[u8; 4]; 4] = [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+        ImageBuffer::from_fn(img.width(), img.height(), |x, y| {
+            let threshold = pattern[(x % 4) as usize][(y % 4) as usize] * 16 + 8;
+            Luma([if img.get_pixel(x, y)[0] >= threshold {
+                255
+            } else {
+                0
+            }])
+        })
+    }
+
+    #[test]
+    fn test_halftoning_matches_legacy_bayer_thresholds() {
+        let map = ThresholdMap::default();
+        // Flat fields at every level between two thresholds
+        for level in (0..=255).step_by(5) {
+            let img: ImageBuffer<Luma<u8>, Vec<u8>> = ImageBuffer::from_pixel(4, 4, Luma([level]));
+            assert_eq!(halftoning(&img, &map), legacy_halftoning(&img), "{}", level);
+        }
+        // Gradients in both directions over several tiles
+        let horizontal: ImageBuffer<Luma<u8>, Vec<u8>> =
+            ImageBuffer::from_fn(16, 8, |x, y| Luma([(x * 16 + y) as u8]));
+        assert_eq!(
+            halftoning(&horizontal, &map),
+            legacy_halftoning(&horizontal)
+        );
+        let vertical: ImageBuffer<Luma<u8>, Vec<u8>> =
+            ImageBuffer::from_fn(8, 16, |x, y| Luma([(y * 16 + x) as u8]));
+        assert_eq!(halftoning(&vertical, &map), legacy_halftoning(&vertical));
+    }
 }
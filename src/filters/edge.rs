@@ -0,0 +1,292 @@
+//! Gradient and compass edge operators built on top of reusable convolution.
+//!
+//! Every operator converts the input to grayscale, convolves it with a small
+//! set of masks and maps the per-pixel response to `Luma<u8>`, normalized by the
+//! strongest response and scaled by `intensity`.
+use image::{ImageBuffer, Luma, Rgb};
+
+use super::convolution::{convolve_gray_f32, magnitude_to_luma_u8, BorderMode, MagnitudeScale};
+use super::gradient::{Gradient, GradientOperator};
+use super::grayscale;
+
+pub(crate) const PREWITT_X: [[f32; 3]; 3] = [[-1.0, 0.0, 1.0], [-1.0, 0.0, 1.0], [-1.0, 0.0, 1.0]];
+
+pub(crate) const PREWITT_Y: [[f32; 3]; 3] = [[-1.0, -1.0, -1.0], [0.0, 0.0, 0.0], [1.0, 1.0, 1.0]];
+
+pub(crate) const SCHARR_X: [[f32; 3]; 3] = [[-3.0, 0.0, 3.0], [-10.0, 0.0, 10.0], [-3.0, 0.0, 3.0]];
+
+pub(crate) const SCHARR_Y: [[f32; 3]; 3] = [[-3.0, -10.0, -3.0], [0.0, 0.0, 0.0], [3.0, 10.0, 3.0]];
+
+// The 2x2 Roberts cross masks, embedded in 3x3 so the top-left tap sits on the
+// output pixel.
+pub(crate) const ROBERTS_X: [[f32; 3]; 3] = [[0.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, -1.0]];
+
+pub(crate) const ROBERTS_Y: [[f32; 3]; 3] = [[0.0, 0.0, 0.0], [0.0, 0.0, 1.0], [0.0, -1.0, 0.0]];
+
+/// Kirsch "north" mask; the other seven compass masks are rotations of it.
+const KIRSCH_NORTH: [[f32; 3]; 3] = [[5.0, 5.0, 5.0], [-3.0, 0.0, -3.0], [-3.0, -3.0, -3.0]];
+
+const R2: f32 = std::f32::consts::SQRT_2;
+const E: f32 = 1.0 / (2.0 * std::f32::consts::SQRT_2);
+
+/// Orthonormal Frei-Chen basis: four edge masks, four line masks and the mean.
+const FREI_CHEN: [[[f32; 3]; 3]; 9] = [
+    [[E, R2 * E, E], [0.0, 0.0, 0.0], [-E, -R2 * E, -E]],
+    [[E, 0.0, -E], [R2 * E, 0.0, -R2 * E], [E, 0.0, -E]],
+    [[0.0, -E, R2 * E], [E, 0.0, -E], [-R2 * E, E, 0.0]],
+    [[R2 * E, -E, 0.0], [-E, 0.0, E], [0.0, E, -R2 * E]],
+    [[0.0, 0.5, 0.0], [-0.5, 0.0, -0.5], [0.0, 0.5, 0.0]],
+    [[-0.5, 0.0, 0.5], [0.0, 0.0, 0.0], [0.5, 0.0, -0.5]],
+    [
+        [1.0 / 6.0, -2.0 / 6.0, 1.0 / 6.0],
+        [-2.0 / 6.0, 4.0 / 6.0, -2.0 / 6.0],
+        [1.0 / 6.0, -2.0 / 6.0, 1.0 / 6.0],
+    ],
+    [
+        [-2.0 / 6.0, 1.0 / 6.0, -2.0 / 6.0],
+        [1.0 / 6.0, 4.0 / 6.0, 1.0 / 6.0],
+        [-2.0 / 6.0, 1.0 / 6.0, -2.0 / 6.0],
+    ],
+    [[1.0 / 3.0; 3]; 3],
+];
+
+/// Edge operators for [`operator_edges`]: a set of 3x3 masks and how their
+/// responses are combined into one magnitude per pixel.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum EdgeOperator {
+    /// Sobel gradient (center-weighted).
+    Sobel,
+    /// Prewitt gradient (uniformly weighted).
+    Prewitt,
+    /// Scharr gradient (rotation-invariant weighting).
+    Scharr,
+    /// Roberts cross (diagonal 2x2 differences).
+    Roberts,
+    /// Kirsch compass: the strongest of eight directional masks.
+    Kirsch,
+    /// Frei-Chen: each neighbourhood is projected onto the orthonormal
+    /// Frei-Chen basis; the response is `sqrt(M / S)`, the cosine of the angle
+    /// between the neighbourhood and the edge subspace, where `M` is the
+    /// energy in the four edge masks and `S` the total energy.
+    FreiChen,
+}
+
+impl EdgeOperator {
+    /// The derivative masks of gradient operators; `None` for Kirsch and
+    /// Frei-Chen, which do not measure a gradient direction.
+    pub fn gradient(self) -> Option<GradientOperator> {
+        match self {
+            EdgeOperator::Sobel => Some(GradientOperator::Sobel),
+            EdgeOperator::Prewitt => Some(GradientOperator::Prewitt),
+            EdgeOperator::Scharr => Some(GradientOperator::Scharr),
+            EdgeOperator::Roberts => Some(GradientOperator::Roberts),
+            EdgeOperator::Kirsch | EdgeOperator::FreiChen => None,
+        }
+    }
+
+    /// Masks convolved with the image: the gradient pair, the eight Kirsch
+    /// rotations or the nine Frei-Chen basis masks.
+    pub fn masks(self) -> Vec<[[f32; 3]; 3]> {
+        if let Some(gradient) = self.gradient() {
+            let (x, y) = gradient.masks();
+            return vec![*x, *y];
+        }
+        match self {
+            EdgeOperator::Kirsch => {
+                std::iter::successors(Some(KIRSCH_NORTH), |m| Some(rotate_compass(m)))
+                    .take(8)
+                    .collect()
+            }
+            _ => FREI_CHEN.to_vec(),
+        }
+    }
+
+    /// Combine the responses of [`EdgeOperator::masks`] at one pixel.
+    fn magnitude(self, responses: impl Iterator<Item = f32>) -> f32 {
+        match self {
+            EdgeOperator::Kirsch => responses.fold(0.0, |m, r| m.max(r.abs())),
+            EdgeOperator::FreiChen => {
+                let (mut edge, mut total) = (0.0, 0.0);
+                for (i, p) in responses.enumerate() {
+                    total += p * p;
+                    if i < 4 {
+                        edge += p * p;
+                    }
+                }
+                if total > 0.0 {
+                    (edge / total).sqrt()
+                } else {
+                    0.0
+                }
+            }
+            _ => responses.map(|r| r * r).sum::<f32>().sqrt(),
+        }
+    }
+}
+
+/// Convert an RGB image to grayscale with the sRGB luma weights.
+fn to_gray(img: &ImageBuffer<Rgb<u8>, Vec<u8>>) -> ImageBuffer<Luma<u8>, Vec<u8>> {
+    grayscale::grayscale(
+        img,
+        crate::color::SRGB_LUMA_R,
+        crate::color::SRGB_LUMA_G,
+        crate::color::SRGB_LUMA_B,
+    )
+}
+
+/// Rotate the outer ring of a 3x3 mask by one step (45 degrees) clockwise.
+fn rotate_compass(k: &[[f32; 3]; 3]) -> [[f32; 3]; 3] {
+    const RING: [(usize, usize); 8] = [
+        (0, 0),
+        (0, 1),
+        (0, 2),
+        (1, 2),
+        (2, 2),
+        (2, 1),
+        (2, 0),
+        (1, 0),
+    ];
+    let mut out = *k;
+    for i in 0..8 {
+        let (r, c) = RING[(i + 1) % 8];
+        let (sr, sc) = RING[i];
+        out[r][c] = k[sr][sc];
+    }
+    out
+}
+
+/// Apply an edge operator to an RGB image.
+///
+/// # Arguments
+/// * `img` - The input RGB image
+/// * `operator` - Masks and how their responses are combined
+/// * `scale` - Normalization, intensity and optional threshold of the magnitude
+/// * `border` - How pixels outside the image are synthesized
+///
+/// # Returns
+/// Grayscale edge magnitude image
+pub fn operator_edges(
+    img: &ImageBuffer<Rgb<u8>, Vec<u8>>,
+    operator: EdgeOperator,
+    scale: MagnitudeScale,
+    border: BorderMode,
+) -> ImageBuffer<Luma<u8>, Vec<u8>> {
+    if let Some(gradient) = operator.gradient() {
+        return Gradient::compute(img, gradient, border).to_luma(scale);
+    }
+    let gray = to_gray(img);
+    let (w, h) = gray.dimensions();
+    let responses: Vec<Vec<f32>> = operator
+        .masks()
+        .iter()
+        .map(|mask| convolve_gray_f32::<3>(&gray, mask, border))
+        .collect();
+    let magnitude: Vec<f32> = (0..(w * h) as usize)
+        .map(|i| operator.magnitude(responses.iter().map(|r| r[i])))
+        .collect();
+    magnitude_to_luma_u8(&magnitude, w, h, scale)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{ImageBuffer, Rgb};
+
+    fn vertical_edge_image() -> ImageBuffer<Rgb<u8>, Vec<u8>> {
+        // 6x3 image: left half dark, right half bright -> vertical edge around x=2/3
+        ImageBuffer::from_fn(6, 3, |x, _y| {
+            if x < 3 {
+                Rgb([10, 10, 10])
+            } else {
+                Rgb([240, 240, 240])
+            }
+        })
+    }
+
+    fn assert_detects_vertical_edge(operator: EdgeOperator) {
+        let edges = operator_edges(
+            &vertical_edge_image(),
+            operator,
+            MagnitudeScale::default(),
+            BorderMode::Reflect101,
+        );
+        assert_eq!(edges.dimensions(), (6, 3));
+        let left_val = edges.get_pixel(0, 1)[0];
+        let edge_val = edges.get_pixel(2, 1)[0].max(edges.get_pixel(3, 1)[0]);
+        assert!(edge_val > left_val);
+        assert_eq!(left_val, 0);
+    }
+
+    #[test]
+    fn sobel_detects_vertical_edge() {
+        assert_detects_vertical_edge(EdgeOperator::Sobel);
+    }
+
+    #[test]
+    fn prewitt_detects_vertical_edge() {
+        assert_detects_vertical_edge(EdgeOperator::Prewitt);
+    }
+
+    #[test]
+    fn scharr_detects_vertical_edge() {
+        assert_detects_vertical_edge(EdgeOperator::Scharr);
+    }
+
+    #[test]
+    fn roberts_detects_vertical_edge() {
+        assert_detects_vertical_edge(EdgeOperator::Roberts);
+    }
+
+    #[test]
+    fn kirsch_detects_vertical_edge() {
+        assert_detects_vertical_edge(EdgeOperator::Kirsch);
+    }
+
+    #[test]
+    fn frei_chen_detects_vertical_edge() {
+        assert_detects_vertical_edge(EdgeOperator::FreiChen);
+    }
+
+    #[test]
+    fn roberts_marks_the_dark_side() {
+        // The diagonal difference lands on the dark side of the step
+        let edges = operator_edges(
+            &vertical_edge_image(),
+            EdgeOperator::Roberts,
+            MagnitudeScale::default(),
+            BorderMode::Clamp,
+        );
+        assert_eq!(edges.get_pixel(2, 1)[0], 255);
+        assert_eq!(edges.get_pixel(3, 1)[0], 0);
+    }
+
+    #[test]
+    fn kirsch_masks_rotate_the_north_mask() {
+        let masks = EdgeOperator::Kirsch.masks();
+        assert_eq!(masks.len(), 8);
+        assert_eq!(masks[0], KIRSCH_NORTH);
+        assert_eq!(masks[2][0], [-3.0, -3.0, 5.0]);
+    }
+
+    #[test]
+    fn frei_chen_basis_is_orthonormal() {
+        for (i, a) in FREI_CHEN.iter().enumerate() {
+            for (j, b) in FREI_CHEN.iter().enumerate() {
+                let dot: f32 = a
+                    .iter()
+                    .flatten()
+                    .zip(b.iter().flatten())
+                    .map(|(x, y)| x * y)
+                    .sum();
+                let expected = if i == j { 1.0 } else { 0.0 };
+                assert!(
+                    (dot - expected).abs() < 1e-5,
+                    "G{} . G{} = {}",
+                    i + 1,
+                    j + 1,
+                    dot
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,255 @@
+//! Histogram equalization and Contrast-Limited Adaptive Histogram
+//! Equalization (CLAHE).
+//!
+//! Only the luminance is remapped. RGB pixels are scaled by the ratio of new
+//! to old luminance, which keeps their hue and saturation.
+use image::{ImageBuffer, Luma, Rgb};
+
+use super::threshold::histogram;
+use crate::color::srgb_luma;
+
+/// Equalization algorithm.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum EqualizeMethod {
+    /// Remap the whole image so its cumulative histogram becomes linear.
+    Global,
+    /// Equalize each tile of a `tiles_x` x `tiles_y` grid separately and blend
+    /// neighbouring tiles bilinearly. Histogram bins are clipped at
+    /// `clip_limit` times the average bin count before equalizing, which
+    /// limits noise amplification in flat regions (>= 1.0; lower is gentler).
+    Clahe {
+        tiles_x: u32,
+        tiles_y: u32,
+        clip_limit: f32,
+    },
+}
+
+/// Classic equalization lookup table: the darkest occupied level maps to 0 and
+/// the brightest to 255. Images with a single level are left unchanged.
+fn global_lut(hist: &[u32; 256]) -> [f32; 256] {
+    let total: u64 = hist.iter().map(|&c| c as u64).sum();
+    let cdf_min = hist.iter().copied().find(|&c| c > 0).unwrap_or(0) as u64;
+    let mut lut = [0.0f32; 256];
+    let mut cdf = 0u64;
+    for (i, &count) in hist.iter().enumerate() {
+        cdf += count as u64;
+        lut[i] = if total > cdf_min {
+            (cdf.saturating_sub(cdf_min)) as f32 * 255.0 / (total - cdf_min) as f32
+        } else {
+            i as f32
+        };
+    }
+    lut
+}
+
+/// Clipped-histogram lookup table for one CLAHE tile. The excess above the
+/// clip limit is spread evenly over all bins.
+fn clipped_lut(hist: &[u32; 256], clip_limit: f32) -> [f32; 256] {
+    let total: f32 = hist.iter().map(|&c| c as f32).sum();
+    let mut lut = [0.0f32; 256];
+    if total == 0.0 {
+        return lut;
+    }
+    let limit = (clip_limit * total / 256.0).max(1.0);
+    let excess: f32 = hist.iter().map(|&c| (c as f32 - limit).max(0.0)).sum();
+    let bonus = excess / 256.0;
+    let mut cdf = 0.0f32;
+    for (i, &count) in hist.iter().enumerate() {
+        cdf += (count as f32).min(limit) + bonus;
+        lut[i] = (cdf * 255.0 / total).min(255.0);
+    }
+    lut
+}
+
+/// Bounds of tile `index` when `len` pixels are split into `tiles` tiles.
+fn tile_span(index: u32, tiles: u32, len: u32) -> (u32, u32) {
+    let start = (index as u64 * len as u64 / tiles as u64) as u32;
+    let end = ((index as u64 + 1) * len as u64 / tiles as u64) as u32;
+    (start, end)
+}
+
+/// Lower tile index and blend weight for position `p` between tile centres.
+fn tile_blend(p: u32, tiles: u32, len: u32) -> (usize, usize, f32) {
+    let f = (p as f32 + 0.5) * tiles as f32 / len as f32 - 0.5;
+    let lower = f.floor().clamp(0.0, (tiles - 1) as f32);
+    let upper = (lower as u32 + 1).min(tiles - 1);
+    let weight = (f - lower).clamp(0.0, 1.0);
+    (lower as usize, upper as usize, weight)
+}
+
+/// Equalized luminance (0.0..=255.0) for every pixel of a gray plane.
+fn equalize_levels(gray: &ImageBuffer<Luma<u8>, Vec<u8>>, method: EqualizeMethod) -> Vec<f32> {
+    match method {
+        EqualizeMethod::Global => {
+            let lut = global_lut(&histogram(gray));
+            gray.pixels().map(|p| lut[p[0] as usize]).collect()
+        }
+        EqualizeMethod::Clahe {
+            tiles_x,
+            tiles_y,
+            clip_limit,
+        } => {
+            let (w, h) = gray.dimensions();
+            if w == 0 || h == 0 {
+                return Vec::new();
+            }
+            let tiles_x = tiles_x.clamp(1, w);
+            let tiles_y = tiles_y.clamp(1, h);
+            let mut luts = Vec::with_capacity((tiles_x * tiles_y) as usize);
+            for ty in 0..tiles_y {
+                let (y0, y1) = tile_span(ty, tiles_y, h);
+                for tx in 0..tiles_x {
+                    let (x0, x1) = tile_span(tx, tiles_x, w);
+                    let mut hist = [0u32; 256];
+                    for y in y0..y1 {
+                        for x in x0..x1 {
+                            hist[gray.get_pixel(x, y)[0] as usize] += 1;
+                        }
+                    }
+                    luts.push(clipped_lut(&hist, clip_limit));
+                }
+            }
+            let lut = |tx: usize, ty: usize| &luts[ty * tiles_x as usize + tx];
+            let mut levels = Vec::with_capacity((w * h) as usize);
+            for y in 0..h {
+                let (ty0, ty1, wy) = tile_blend(y, tiles_y, h);
+                for x in 0..w {
+                    let (tx0, tx1, wx) = tile_blend(x, tiles_x, w);
+                    let v = gray.get_pixel(x, y)[0] as usize;
+                    let top = lut(tx0, ty0)[v] * (1.0 - wx) + lut(tx1, ty0)[v] * wx;
+                    let bottom = lut(tx0, ty1)[v] * (1.0 - wx) + lut(tx1, ty1)[v] * wx;
+                    levels.push(top * (1.0 - wy) + bottom * wy);
+                }
+            }
+            levels
+        }
+    }
+}
+
+/// Equalize a grayscale image.
+///
+/// # Arguments
+/// * `img` - The input grayscale image
+/// * `method` - Global equalization or CLAHE
+///
+/// # Returns
+/// Equalized grayscale image
+pub fn equalize_gray(
+    img: &ImageBuffer<Luma<u8>, Vec<u8>>,
+    method: EqualizeMethod,
+) -> ImageBuffer<Luma<u8>, Vec<u8>> {
+    let (w, h) = img.dimensions();
+    let levels = equalize_levels(img, method);
+    ImageBuffer::from_fn(w, h, |x, y| {
+        Luma([levels[(y * w + x) as usize].round().clamp(0.0, 255.0) as u8])
+    })
+}
+
+/// Equalize the luminance of an RGB image.
+///
+/// Each pixel is scaled by `new / old` luminance; the scale is capped so no
+/// channel clips, keeping the hue of saturated colors. Black pixels become
+/// gray at their new level.
+///
+/// # Arguments
+/// * `img` - The input RGB image
+/// * `method` - Global equalization or CLAHE
+///
+/// # Returns
+/// RGB image with equalized luminance
+pub fn equalize(
+    img: &ImageBuffer<Rgb<u8>, Vec<u8>>,
+    method: EqualizeMethod,
+) -> ImageBuffer<Rgb<u8>, Vec<u8>> {
+    let (w, h) = img.dimensions();
+    let luminance: Vec<f32> = img.pixels().map(|p| srgb_luma(p) as f32).collect();
+    let gray: ImageBuffer<Luma<u8>, Vec<u8>> = ImageBuffer::from_fn(w, h, |x, y| {
+        Luma([luminance[(y * w + x) as usize].round().min(255.0) as u8])
+    });
+    let levels = equalize_levels(&gray, method);
+    ImageBuffer::from_fn(w, h, |x, y| {
+        let i = (y * w + x) as usize;
+        let p = img.get_pixel(x, y);
+        if luminance[i] <= 0.0 {
+            return Rgb([levels[i].round().clamp(0.0, 255.0) as u8; 3]);
+        }
+        let brightest = p[0].max(p[1]).max(p[2]) as f32;
+        let scale = (levels[i] / luminance[i]).min(255.0 / brightest);
+        Rgb(p
+            .0
+            .map(|c| (c as f32 * scale).round().clamp(0.0, 255.0) as u8))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dim_ramp() -> ImageBuffer<Luma<u8>, Vec<u8>> {
+        ImageBuffer::from_fn(16, 16, |x, y| Luma([40 + ((x + y) * 2) as u8]))
+    }
+
+    #[test]
+    fn test_global_stretches_to_full_range() {
+        let out = equalize_gray(&dim_ramp(), EqualizeMethod::Global);
+        assert_eq!(out.pixels().map(|p| p[0]).min(), Some(0));
+        assert_eq!(out.pixels().map(|p| p[0]).max(), Some(255));
+        // Monotonic: brighter inputs never map darker
+        assert!(out.get_pixel(15, 15)[0] >= out.get_pixel(8, 8)[0]);
+        let flat: ImageBuffer<Luma<u8>, Vec<u8>> = ImageBuffer::from_pixel(4, 4, Luma([77]));
+        assert_eq!(
+            *equalize_gray(&flat, EqualizeMethod::Global).get_pixel(0, 0),
+            Luma([77])
+        );
+    }
+
+    #[test]
+    fn test_clahe_limits_contrast() {
+        let img = dim_ramp();
+        let strong = equalize_gray(
+            &img,
+            EqualizeMethod::Clahe {
+                tiles_x: 2,
+                tiles_y: 2,
+                clip_limit: 40.0,
+            },
+        );
+        let gentle = equalize_gray(
+            &img,
+            EqualizeMethod::Clahe {
+                tiles_x: 2,
+                tiles_y: 2,
+                clip_limit: 1.0,
+            },
+        );
+        let spread = |img: &ImageBuffer<Luma<u8>, Vec<u8>>| {
+            let values: Vec<u8> = img.pixels().map(|p| p[0]).collect();
+            values.iter().max().unwrap() - values.iter().min().unwrap()
+        };
+        assert!(spread(&strong) > spread(&gentle));
+        assert!(spread(&strong) > spread(&img));
+        // No visible seams: neighbouring pixels across the tile boundary stay close
+        let a = strong.get_pixel(7, 4)[0] as i32;
+        let b = strong.get_pixel(8, 4)[0] as i32;
+        assert!((a - b).abs() <= 24, "{} vs {}", a, b);
+    }
+
+    #[test]
+    fn test_rgb_keeps_hue() {
+        let img: ImageBuffer<Rgb<u8>, Vec<u8>> = ImageBuffer::from_fn(8, 8, |x, _| {
+            let v = 20 + x as u8 * 4;
+            Rgb([v * 2, v, v / 2])
+        });
+        let out = equalize(&img, EqualizeMethod::Global);
+        for (a, b) in img.pixels().zip(out.pixels()) {
+            assert!(b[0] >= b[1] && b[1] >= b[2], "{:?}", b);
+            if b[1] < 16 {
+                // The darkest level maps to black, where ratios are meaningless
+                continue;
+            }
+            // Channel ratios survive the remapping
+            let ratio = |p: &Rgb<u8>| p[0] as f32 / p[1].max(1) as f32;
+            assert!((ratio(a) - ratio(b)).abs() < 0.15, "{:?} {:?}", a, b);
+        }
+    }
+}
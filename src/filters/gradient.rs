@@ -0,0 +1,431 @@
+//! First-derivative image gradients with per-pixel magnitude and direction.
+//!
+//! A [`Gradient`] keeps the raw `gx`/`gy` derivatives so callers can render the
+//! magnitude, visualize the direction as hue, or export the raw values.
+//! Gradients are computed on the luminance, or per color channel and then
+//! combined so edges between colors of equal brightness are not lost.
+use image::{ImageBuffer, Luma, LumaA, Rgb, Rgb32FImage};
+
+use super::convolution::{
+    convolve_plane_f32, magnitude_to_luma_u8_scaled, BorderMode, Kernel, MagnitudeScale,
+};
+use super::edge::{PREWITT_X, PREWITT_Y, ROBERTS_X, ROBERTS_Y, SCHARR_X, SCHARR_Y};
+use super::grayscale;
+use super::sobel::{SOBEL_X, SOBEL_Y};
+use crate::color::{hsv_to_rgb, linear_srgb_to_oklab, srgb_to_linear};
+
+/// Value representing a zero derivative in 16-bit raw output.
+pub const RAW_U16_OFFSET: f32 = 32768.0;
+
+/// Pair of derivative masks used to compute a gradient.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum GradientOperator {
+    /// Sobel (center-weighted) masks.
+    Sobel,
+    /// Prewitt (uniformly weighted) masks.
+    Prewitt,
+    /// Scharr (rotation-invariant) masks.
+    Scharr,
+    /// Roberts cross (diagonal differences, so the angle is rotated by 45°).
+    Roberts,
+}
+
+impl GradientOperator {
+    /// Horizontal and vertical 3x3 masks.
+    pub(crate) fn masks(self) -> (&'static [[f32; 3]; 3], &'static [[f32; 3]; 3]) {
+        match self {
+            GradientOperator::Sobel => (&SOBEL_X, &SOBEL_Y),
+            GradientOperator::Prewitt => (&PREWITT_X, &PREWITT_Y),
+            GradientOperator::Scharr => (&SCHARR_X, &SCHARR_Y),
+            GradientOperator::Roberts => (&ROBERTS_X, &ROBERTS_Y),
+        }
+    }
+
+    /// Largest derivative the masks produce on 8-bit input (a full-contrast
+    /// step): 1020 for Sobel, 765 for Prewitt, 4080 for Scharr, 255 for Roberts.
+    pub fn max_response(self) -> f32 {
+        let (kx, _) = self.masks();
+        255.0 * kx.iter().flatten().filter(|&&w| w > 0.0).sum::<f32>()
+    }
+}
+
+/// Scale for 16-bit raw output: the largest power of two that keeps
+/// derivatives up to `max_derivative` in magnitude inside `0..=65535`.
+pub fn raw_u16_scale(max_derivative: f32) -> f32 {
+    let fit = (RAW_U16_OFFSET - 1.0) / max_derivative.max(1.0);
+    2f32.powi(fit.log2().floor() as i32)
+}
+
+/// Color space whose channels are differentiated by color-aware edge detection.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum EdgeSpace {
+    /// Gamma-encoded R, G and B.
+    Rgb,
+    /// Perceptual OKLab, scaled by 255 so `L` spans the same range as RGB.
+    Oklab,
+}
+
+/// How per-channel gradients are merged into one.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ChannelCombine {
+    /// The gradient of the channel with the largest magnitude.
+    Max,
+    /// The sum of the channel gradients, each flipped to point the same way
+    /// as the strongest one so opposite changes (red to green) add up.
+    Sum,
+    /// Di Zenzo's multi-channel gradient: the dominant eigenvector of the
+    /// summed structure tensor.
+    DiZenzo,
+}
+
+/// Per-pixel horizontal and vertical derivatives of an image.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Gradient {
+    width: u32,
+    height: u32,
+    gx: Vec<f32>,
+    gy: Vec<f32>,
+}
+
+impl Gradient {
+    /// Wrap row-major derivative planes.
+    pub fn new(width: u32, height: u32, gx: Vec<f32>, gy: Vec<f32>) -> Self {
+        assert_eq!(gx.len(), (width * height) as usize);
+        assert_eq!(gy.len(), gx.len());
+        Self {
+            width,
+            height,
+            gx,
+            gy,
+        }
+    }
+
+    /// Compute the gradient of an RGB image's luminance.
+    pub fn compute(
+        img: &ImageBuffer<Rgb<u8>, Vec<u8>>,
+        operator: GradientOperator,
+        border: BorderMode,
+    ) -> Self {
+        let gray: ImageBuffer<Luma<u8>, Vec<u8>> = grayscale::grayscale(
+            img,
+            crate::color::SRGB_LUMA_R,
+            crate::color::SRGB_LUMA_G,
+            crate::color::SRGB_LUMA_B,
+        );
+        let (w, h) = gray.dimensions();
+        let plane: Vec<f32> = gray.pixels().map(|p| p[0] as f32).collect();
+        let (kx, ky) = operator.masks();
+        let gx = convolve_plane_f32(&plane, w, h, &Kernel::from_square(kx), border);
+        let gy = convolve_plane_f32(&plane, w, h, &Kernel::from_square(ky), border);
+        Self::new(w, h, gx, gy)
+    }
+
+    /// Compute one gradient per channel of `space`.
+    pub fn compute_channels(
+        img: &ImageBuffer<Rgb<u8>, Vec<u8>>,
+        operator: GradientOperator,
+        space: EdgeSpace,
+        border: BorderMode,
+    ) -> [Self; 3] {
+        let (w, h) = img.dimensions();
+        let pixels: Vec<[f32; 3]> = img
+            .pixels()
+            .map(|p| match space {
+                EdgeSpace::Rgb => p.0.map(|c| c as f32),
+                EdgeSpace::Oklab => {
+                    let linear = p.0.map(|c| srgb_to_linear(c as f32 / 255.0));
+                    linear_srgb_to_oklab(linear).map(|v| v * 255.0)
+                }
+            })
+            .collect();
+        let (kx, ky) = operator.masks();
+        [0, 1, 2].map(|c| {
+            let plane: Vec<f32> = pixels.iter().map(|p| p[c]).collect();
+            let gx = convolve_plane_f32(&plane, w, h, &Kernel::from_square(kx), border);
+            let gy = convolve_plane_f32(&plane, w, h, &Kernel::from_square(ky), border);
+            Self::new(w, h, gx, gy)
+        })
+    }
+
+    /// Merge per-channel gradients into a single gradient.
+    pub fn combine(channels: &[Self; 3], combine: ChannelCombine) -> Self {
+        let (w, h) = (channels[0].width, channels[0].height);
+        let len = channels[0].gx.len();
+        let mut gx = vec![0.0f32; len];
+        let mut gy = vec![0.0f32; len];
+        for i in 0..len {
+            let vectors = channels.each_ref().map(|g| (g.gx[i], g.gy[i]));
+            let strongest = vectors
+                .into_iter()
+                .max_by(|a, b| a.0.hypot(a.1).total_cmp(&b.0.hypot(b.1)))
+                .unwrap_or_default();
+            (gx[i], gy[i]) = match combine {
+                ChannelCombine::Max => strongest,
+                ChannelCombine::Sum => vectors.into_iter().fold((0.0, 0.0), |acc, (x, y)| {
+                    let sign = if x * strongest.0 + y * strongest.1 < 0.0 {
+                        -1.0
+                    } else {
+                        1.0
+                    };
+                    (acc.0 + sign * x, acc.1 + sign * y)
+                }),
+                ChannelCombine::DiZenzo => {
+                    let (mut gxx, mut gyy, mut gxy) = (0.0f32, 0.0f32, 0.0f32);
+                    for (x, y) in vectors {
+                        gxx += x * x;
+                        gyy += y * y;
+                        gxy += x * y;
+                    }
+                    let theta = 0.5 * (2.0 * gxy).atan2(gxx - gyy);
+                    let lambda = 0.5 * (gxx + gyy + ((gxx - gyy).powi(2) + 4.0 * gxy * gxy).sqrt());
+                    let m = lambda.max(0.0).sqrt();
+                    // The tensor only fixes the axis; orient it like the strongest channel.
+                    let (c, s) = (theta.cos(), theta.sin());
+                    let sign = if c * strongest.0 + s * strongest.1 < 0.0 {
+                        -1.0
+                    } else {
+                        1.0
+                    };
+                    (sign * m * c, sign * m * s)
+                }
+            };
+        }
+        Self::new(w, h, gx, gy)
+    }
+
+    /// Image width.
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Image height.
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Horizontal derivative (positive when brightness increases to the right).
+    pub fn gx(&self) -> &[f32] {
+        &self.gx
+    }
+
+    /// Vertical derivative (positive when brightness increases downwards).
+    pub fn gy(&self) -> &[f32] {
+        &self.gy
+    }
+
+    /// Gradient magnitude `sqrt(gx² + gy²)`.
+    pub fn magnitude(&self) -> Vec<f32> {
+        self.gx
+            .iter()
+            .zip(&self.gy)
+            .map(|(x, y)| x.hypot(*y))
+            .collect()
+    }
+
+    /// Gradient direction `atan2(gy, gx)` in radians (`-π..=π`), measured
+    /// clockwise from the +x axis because the y axis points down.
+    pub fn angle(&self) -> Vec<f32> {
+        self.gx
+            .iter()
+            .zip(&self.gy)
+            .map(|(x, y)| y.atan2(*x))
+            .collect()
+    }
+
+    /// Magnitude mapped to output levels by `scale`.
+    pub fn to_luma(&self, scale: MagnitudeScale) -> ImageBuffer<Luma<u8>, Vec<u8>> {
+        magnitude_to_luma_u8_scaled(&self.gx, &self.gy, self.width, self.height, scale)
+    }
+
+    /// Flow-style visualization: direction as hue, magnitude mapped by `scale`
+    /// as value, full saturation.
+    pub fn to_direction_rgb(&self, scale: MagnitudeScale) -> ImageBuffer<Rgb<u8>, Vec<u8>> {
+        let levels = scale.levels(&self.magnitude());
+        let angle = self.angle();
+        ImageBuffer::from_fn(self.width, self.height, |x, y| {
+            let i = (y * self.width + x) as usize;
+            let value = levels[i] / 255.0;
+            let rgb = hsv_to_rgb([angle[i].to_degrees(), 1.0, value]);
+            Rgb(rgb.map(|c| (c * 255.0).round() as u8))
+        })
+    }
+
+    /// Raw 16-bit export: luma holds `gx`, alpha holds `gy`, each stored as
+    /// `RAW_U16_OFFSET + scale * value` (clamped to `0..=65535`). Pick `scale`
+    /// with [`raw_u16_scale`] so the strongest response of the operator fits.
+    pub fn to_raw_u16(&self, scale: f32) -> ImageBuffer<LumaA<u16>, Vec<u16>> {
+        let encode = |v: f32| (RAW_U16_OFFSET + scale * v).round().clamp(0.0, 65535.0) as u16;
+        ImageBuffer::from_fn(self.width, self.height, |x, y| {
+            let i = (y * self.width + x) as usize;
+            LumaA([encode(self.gx[i]), encode(self.gy[i])])
+        })
+    }
+
+    /// Raw floating-point export: channels are `gx`, `gy` and the magnitude.
+    pub fn to_raw_f32(&self) -> Rgb32FImage {
+        ImageBuffer::from_fn(self.width, self.height, |x, y| {
+            let i = (y * self.width + x) as usize;
+            let (gx, gy) = (self.gx[i], self.gy[i]);
+            Rgb([gx, gy, gx.hypot(gy)])
+        })
+    }
+}
+
+/// Render per-channel gradients as an RGB image where each output channel
+/// shows the edges of one input channel.
+///
+/// All channels share one normalization reference (computed over the
+/// magnitudes of every channel) so their relative strengths are preserved.
+pub fn channels_to_rgb(
+    channels: &[Gradient; 3],
+    scale: MagnitudeScale,
+) -> ImageBuffer<Rgb<u8>, Vec<u8>> {
+    let magnitudes = channels.each_ref().map(Gradient::magnitude);
+    let reference = scale.reference(&magnitudes.concat());
+    let (w, h) = (channels[0].width, channels[0].height);
+    ImageBuffer::from_fn(w, h, |x, y| {
+        let i = (y * w + x) as usize;
+        Rgb([0, 1, 2].map(|c| scale.level(magnitudes[c][i], reference).round() as u8))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{ImageBuffer, Rgb};
+
+    fn horizontal_ramp() -> ImageBuffer<Rgb<u8>, Vec<u8>> {
+        ImageBuffer::from_fn(5, 4, |x, _| Rgb([x as u8 * 10; 3]))
+    }
+
+    #[test]
+    fn test_gradient_direction() {
+        let gradient = Gradient::compute(
+            &horizontal_ramp(),
+            GradientOperator::Sobel,
+            BorderMode::Clamp,
+        );
+        // Interior: brightness rises by 10 per pixel to the right
+        let i = (2 * 5 + 2) as usize;
+        assert_eq!(gradient.gx()[i], 80.0);
+        assert_eq!(gradient.gy()[i], 0.0);
+        assert_eq!(gradient.magnitude()[i], 80.0);
+        assert_eq!(gradient.angle()[i], 0.0);
+
+        // Transposing the image turns the gradient by 90 degrees
+        let ramp = horizontal_ramp();
+        let down: ImageBuffer<Rgb<u8>, Vec<u8>> =
+            ImageBuffer::from_fn(4, 5, |x, y| *ramp.get_pixel(y, x));
+        let gradient = Gradient::compute(&down, GradientOperator::Prewitt, BorderMode::Clamp);
+        let i = (2 * 4 + 2) as usize;
+        assert!((gradient.angle()[i] - std::f32::consts::FRAC_PI_2).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_gradient_renderings() {
+        let gradient = Gradient::new(2, 1, vec![4.0, -2.0], vec![0.0, 0.0]);
+        let rgb = gradient.to_direction_rgb(MagnitudeScale::default());
+        // 0 degrees is red at full value, 180 degrees cyan at half value
+        assert_eq!(*rgb.get_pixel(0, 0), Rgb([255, 0, 0]));
+        assert_eq!(*rgb.get_pixel(1, 0), Rgb([0, 128, 128]));
+
+        let raw = gradient.to_raw_u16(16.0);
+        assert_eq!(raw.get_pixel(0, 0).0, [32768 + 64, 32768]);
+        assert_eq!(raw.get_pixel(1, 0).0, [32768 - 32, 32768]);
+
+        let raw = gradient.to_raw_f32();
+        assert_eq!(raw.get_pixel(1, 0).0, [-2.0, 0.0, 2.0]);
+        assert_eq!(
+            gradient.to_luma(MagnitudeScale::default()).get_pixel(1, 0)[0],
+            128
+        );
+    }
+
+    #[test]
+    fn test_raw_u16_fits_full_contrast_steps() {
+        assert_eq!(raw_u16_scale(GradientOperator::Sobel.max_response()), 32.0);
+        assert_eq!(raw_u16_scale(GradientOperator::Scharr.max_response()), 8.0);
+        assert_eq!(
+            raw_u16_scale(GradientOperator::Roberts.max_response()),
+            128.0
+        );
+        // Black to white and back: Scharr reaches +-4080
+        let step: ImageBuffer<Rgb<u8>, Vec<u8>> = ImageBuffer::from_fn(8, 3, |x, _| {
+            Rgb([if (2..5).contains(&x) { 255 } else { 0 }; 3])
+        });
+        let operator = GradientOperator::Scharr;
+        let gradient = Gradient::compute(&step, operator, BorderMode::Clamp);
+        let extreme = gradient.gx().iter().fold(0.0f32, |m, v| m.max(v.abs()));
+        assert_eq!(extreme, operator.max_response());
+        let scale = raw_u16_scale(operator.max_response());
+        let raw = gradient.to_raw_u16(scale);
+        for (p, &gx) in raw.pixels().zip(gradient.gx()) {
+            assert_eq!((p[0] as f32 - RAW_U16_OFFSET) / scale, gx);
+        }
+        assert!(raw.pixels().all(|p| p[0] > 0 && p[0] < 65535));
+    }
+
+    /// Red next to green with (nearly) equal luminance.
+    fn isoluminant_edge() -> ImageBuffer<Rgb<u8>, Vec<u8>> {
+        ImageBuffer::from_fn(6, 3, |x, _| {
+            if x < 3 {
+                Rgb([255, 0, 0])
+            } else {
+                Rgb([0, 76, 0])
+            }
+        })
+    }
+
+    #[test]
+    fn test_color_gradients_find_isoluminant_edges() {
+        let img = isoluminant_edge();
+        let luma = Gradient::compute(&img, GradientOperator::Sobel, BorderMode::Clamp);
+        let luma_edge = luma.magnitude()[2 + 6];
+        let channels = Gradient::compute_channels(
+            &img,
+            GradientOperator::Sobel,
+            EdgeSpace::Rgb,
+            BorderMode::Clamp,
+        );
+        for combine in [
+            ChannelCombine::Max,
+            ChannelCombine::Sum,
+            ChannelCombine::DiZenzo,
+        ] {
+            let combined = Gradient::combine(&channels, combine);
+            let m = combined.magnitude();
+            assert!(m[2 + 6] > 10.0 * luma_edge.max(1.0), "{:?}", combine);
+            assert_eq!(m[6], 0.0);
+            // The combined gradient points across the edge
+            assert!(combined.gy()[2 + 6].abs() < 1e-3);
+        }
+        let max = Gradient::combine(&channels, ChannelCombine::Max).magnitude()[2 + 6];
+        let sum = Gradient::combine(&channels, ChannelCombine::Sum).magnitude()[2 + 6];
+        let di_zenzo = Gradient::combine(&channels, ChannelCombine::DiZenzo).magnitude()[2 + 6];
+        // R falls by 255 and G rises by 76 across the step (Sobel gain 4)
+        assert_eq!(max, 4.0 * 255.0);
+        assert_eq!(sum, 4.0 * (255.0 + 76.0));
+        assert!((di_zenzo - 4.0 * (255.0f32.powi(2) + 76.0f32.powi(2)).sqrt()).abs() < 0.1);
+
+        let oklab = Gradient::compute_channels(
+            &img,
+            GradientOperator::Sobel,
+            EdgeSpace::Oklab,
+            BorderMode::Clamp,
+        );
+        assert!(Gradient::combine(&oklab, ChannelCombine::DiZenzo).magnitude()[2 + 6] > 100.0);
+    }
+
+    #[test]
+    fn test_channels_to_rgb() {
+        let channels = Gradient::compute_channels(
+            &isoluminant_edge(),
+            GradientOperator::Sobel,
+            EdgeSpace::Rgb,
+            BorderMode::Clamp,
+        );
+        let rgb = channels_to_rgb(&channels, MagnitudeScale::default());
+        assert_eq!(*rgb.get_pixel(2, 1), Rgb([255, 76, 0]));
+        assert_eq!(*rgb.get_pixel(0, 1), Rgb([0, 0, 0]));
+    }
+}
@@ -0,0 +1,207 @@
+//! Second-derivative edge and blob detection: Laplacian, Laplacian of
+//! Gaussian (LoG) and Difference of Gaussians (DoG).
+//!
+//! The signed response is either shown as a magnitude image or reduced to its
+//! zero crossings, which mark edges as thin closed contours.
+use image::{ImageBuffer, Luma, Rgb};
+
+use super::convolution::{
+    convolve_plane_f32, difference_of_gaussians_f32, magnitude_to_luma_u8, BorderMode, Kernel,
+    MagnitudeScale,
+};
+use super::grayscale;
+
+/// Responses smaller than this fraction of the strongest one count as zero.
+const ZERO_EPSILON: f32 = 1e-4;
+
+/// Second-derivative operator.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum SecondDerivative {
+    /// 3x3 Laplacian over the 4-neighbourhood.
+    Laplacian4,
+    /// 3x3 Laplacian over the 8-neighbourhood.
+    Laplacian8,
+    /// Laplacian of Gaussian with standard deviation `sigma` (> 0).
+    LoG { sigma: f32 },
+    /// Gaussian with `sigma1` minus Gaussian with `sigma2` (both > 0).
+    DoG { sigma1: f32, sigma2: f32 },
+}
+
+/// Signed response of a second-derivative operator on a grayscale plane.
+pub fn second_derivative_f32(
+    plane: &[f32],
+    width: u32,
+    height: u32,
+    operator: SecondDerivative,
+    border: BorderMode,
+) -> Vec<f32> {
+    match operator {
+        SecondDerivative::Laplacian4 => {
+            convolve_plane_f32(plane, width, height, &Kernel::laplacian(false), border)
+        }
+        SecondDerivative::Laplacian8 => {
+            convolve_plane_f32(plane, width, height, &Kernel::laplacian(true), border)
+        }
+        SecondDerivative::LoG { sigma } => convolve_plane_f32(
+            plane,
+            width,
+            height,
+            &Kernel::laplacian_of_gaussian(sigma),
+            border,
+        ),
+        SecondDerivative::DoG { sigma1, sigma2 } => {
+            difference_of_gaussians_f32(plane, width, height, sigma1, sigma2, border)
+        }
+    }
+}
+
+/// Mark zero crossings of a signed response.
+///
+/// A crossing exists between horizontally, vertically or diagonally adjacent
+/// pixels with opposite signs whose difference is at least `threshold` times
+/// the largest absolute response. The pixel closer to zero is marked.
+/// Responses within rounding noise of zero have no sign and never cross.
+pub fn zero_crossings(response: &[f32], width: u32, height: u32, threshold: f32) -> Vec<bool> {
+    let (w, h) = (width as usize, height as usize);
+    let max_abs = response.iter().fold(0.0f32, |m, v| m.max(v.abs()));
+    let min_step = (threshold * max_abs).max(f32::MIN_POSITIVE);
+    let noise = ZERO_EPSILON * max_abs;
+    let mut edges = vec![false; response.len()];
+    for y in 0..h {
+        for x in 0..w {
+            let i = y * w + x;
+            let a = response[i];
+            // Forward neighbours only; each pair is visited once.
+            for (dx, dy) in [(1i32, 0usize), (0, 1), (1, 1), (-1, 1)] {
+                let nx = x as i32 + dx;
+                let ny = y + dy;
+                if nx < 0 || nx as usize >= w || ny >= h {
+                    continue;
+                }
+                let j = ny * w + nx as usize;
+                let b = response[j];
+                let opposite = (a > noise && b < -noise) || (a < -noise && b > noise);
+                if opposite && (a - b).abs() >= min_step {
+                    edges[if a.abs() <= b.abs() { i } else { j }] = true;
+                }
+            }
+        }
+    }
+    edges
+}
+
+/// Apply a second-derivative operator to an RGB image.
+///
+/// # Arguments
+/// * `img` - The input RGB image
+/// * `operator` - Laplacian, LoG or DoG
+/// * `scale` - Normalization of the magnitude output (ignored for zero crossings)
+/// * `zero_crossing` - `Some(threshold)` to output a binary zero-crossing map
+/// * `border` - How pixels outside the image are synthesized
+///
+/// # Returns
+/// Grayscale magnitude image, or a binary edge map when `zero_crossing` is set
+pub fn second_derivative_edges(
+    img: &ImageBuffer<Rgb<u8>, Vec<u8>>,
+    operator: SecondDerivative,
+    scale: MagnitudeScale,
+    zero_crossing: Option<f32>,
+    border: BorderMode,
+) -> ImageBuffer<Luma<u8>, Vec<u8>> {
+    let gray: ImageBuffer<Luma<u8>, Vec<u8>> = grayscale::grayscale(
+        img,
+        crate::color::SRGB_LUMA_R,
+        crate::color::SRGB_LUMA_G,
+        crate::color::SRGB_LUMA_B,
+    );
+    let (w, h) = gray.dimensions();
+    let plane: Vec<f32> = gray.pixels().map(|p| p[0] as f32).collect();
+    let response = second_derivative_f32(&plane, w, h, operator, border);
+    match zero_crossing {
+        Some(threshold) => {
+            let edges = zero_crossings(&response, w, h, threshold);
+            ImageBuffer::from_fn(w, h, |x, y| {
+                Luma([if edges[(y * w + x) as usize] { 255 } else { 0 }])
+            })
+        }
+        None => {
+            let magnitude: Vec<f32> = response.iter().map(|v| v.abs()).collect();
+            magnitude_to_luma_u8(&magnitude, w, h, scale)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{ImageBuffer, Rgb};
+
+    fn vertical_edge_image() -> ImageBuffer<Rgb<u8>, Vec<u8>> {
+        ImageBuffer::from_fn(12, 5, |x, _y| {
+            if x < 6 {
+                Rgb([10, 10, 10])
+            } else {
+                Rgb([240, 240, 240])
+            }
+        })
+    }
+
+    #[test]
+    fn detects_vertical_edge() {
+        for operator in [
+            SecondDerivative::Laplacian4,
+            SecondDerivative::Laplacian8,
+            SecondDerivative::LoG { sigma: 1.0 },
+            SecondDerivative::DoG {
+                sigma1: 1.0,
+                sigma2: 1.6,
+            },
+        ] {
+            let edges = second_derivative_edges(
+                &vertical_edge_image(),
+                operator,
+                MagnitudeScale::default(),
+                None,
+                BorderMode::Reflect101,
+            );
+            let flat = edges.get_pixel(0, 2)[0];
+            let edge = edges.get_pixel(5, 2)[0].max(edges.get_pixel(6, 2)[0]);
+            assert!(edge > flat, "{:?}", operator);
+            assert_eq!(flat, 0, "{:?}", operator);
+        }
+    }
+
+    #[test]
+    fn zero_crossing_is_one_pixel_wide() {
+        let edges = second_derivative_edges(
+            &vertical_edge_image(),
+            SecondDerivative::LoG { sigma: 1.0 },
+            MagnitudeScale::default(),
+            Some(0.1),
+            BorderMode::Reflect101,
+        );
+        for y in 0..5 {
+            let row: Vec<u32> = (0..12)
+                .filter(|&x| edges.get_pixel(x, y)[0] == 255)
+                .collect();
+            assert_eq!(row.len(), 1, "row {y}: {:?}", row);
+            assert!(row[0] == 5 || row[0] == 6);
+        }
+    }
+
+    #[test]
+    fn zero_crossings_respect_threshold() {
+        let response = [1.0, -1.0, 0.0, 0.05, -0.05, 0.0];
+        assert_eq!(
+            zero_crossings(&response, 6, 1, 0.5),
+            [true, false, false, false, false, false]
+        );
+        assert_eq!(
+            zero_crossings(&response, 6, 1, 0.0)
+                .iter()
+                .filter(|&&e| e)
+                .count(),
+            2
+        );
+    }
+}
@@ -0,0 +1,160 @@
+//! Block mosaic ("pixelate") utilities for RGB images.
+//!
+//! The image is split into `block_width` x `block_height` tiles, and each tile is
+//! filled with a single representative color. Blocks along the right and bottom
+//! edges may be smaller than the requested size.
+use image::{ImageBuffer, Rgb, RgbImage};
+
+/// How the representative color of each block is chosen.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum BlockColor {
+    /// Per-channel arithmetic mean of the block.
+    Average,
+    /// Per-channel median of the block.
+    Median,
+    /// Color of the pixel at the block center.
+    Center,
+}
+
+/// Pixelate an RGB image into uniform blocks.
+///
+/// # Arguments
+/// * `img` - The input RGB image
+/// * `block_width` - Block width in pixels (>= 1)
+/// * `block_height` - Block height in pixels (>= 1)
+/// * `mode` - How each block's fill color is computed
+/// * `grid` - Optional color for 1px lines drawn along interior block boundaries
+///
+/// # Returns
+/// RGB image after pixelation
+pub fn pixelate(
+    img: &ImageBuffer<Rgb<u8>, Vec<u8>>,
+    block_width: u32,
+    block_height: u32,
+    mode: BlockColor,
+    grid: Option<Rgb<u8>>,
+) -> ImageBuffer<Rgb<u8>, Vec<u8>> {
+    assert!(
+        block_width > 0 && block_height > 0,
+        "Block size must be non-zero"
+    );
+    let (width, height) = img.dimensions();
+    let mut imgbuf: RgbImage = ImageBuffer::new(width, height);
+
+    for by in (0..height).step_by(block_height as usize) {
+        for bx in (0..width).step_by(block_width as usize) {
+            // Partial blocks at the right/bottom edges are clipped to the image.
+            let bw = block_width.min(width - bx);
+            let bh = block_height.min(height - by);
+            let color = block_color(img, bx, by, bw, bh, mode);
+            for y in by..by + bh {
+                for x in bx..bx + bw {
+                    imgbuf.put_pixel(x, y, color);
+                }
+            }
+        }
+    }
+
+    if let Some(line) = grid {
+        for (x, y, pixel) in imgbuf.enumerate_pixels_mut() {
+            let on_column = x > 0 && x % block_width == 0;
+            let on_row = y > 0 && y % block_height == 0;
+            if on_column || on_row {
+                *pixel = line;
+            }
+        }
+    }
+    imgbuf
+}
+
+/// Compute the fill color of the block whose top-left corner is (`x0`, `y0`).
+fn block_color(
+    img: &ImageBuffer<Rgb<u8>, Vec<u8>>,
+    x0: u32,
+    y0: u32,
+    w: u32,
+    h: u32,
+    mode: BlockColor,
+) -> Rgb<u8> {
+    match mode {
+        BlockColor::Average => {
+            let mut sum = [0u64; 3];
+            for y in y0..y0 + h {
+                for x in x0..x0 + w {
+                    let p = img.get_pixel(x, y);
+                    for n in 0..3 {
+                        sum[n] += p[n] as u64;
+                    }
+                }
+            }
+            let count = (w as u64) * (h as u64);
+            // Round to nearest by adding half the divisor before dividing.
+            Rgb(sum.map(|s| ((s + count / 2) / count) as u8))
+        }
+        BlockColor::Median => {
+            let mut channels: [Vec<u8>; 3] = Default::default();
+            for y in y0..y0 + h {
+                for x in x0..x0 + w {
+                    let p = img.get_pixel(x, y);
+                    for n in 0..3 {
+                        channels[n].push(p[n]);
+                    }
+                }
+            }
+            Rgb(channels.map(|mut values| {
+                let mid = values.len() / 2;
+                *values.select_nth_unstable(mid).1
+            }))
+        }
+        BlockColor::Center => *img.get_pixel(x0 + w / 2, y0 + h / 2),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{ImageBuffer, Rgb};
+
+    fn create_test_image() -> ImageBuffer<Rgb<u8>, Vec<u8>> {
+        // 5x3 image: R = x * 50, G = y * 100, B = 30
+        ImageBuffer::from_fn(5, 3, |x, y| Rgb([(x * 50) as u8, (y * 100) as u8, 30]))
+    }
+
+    #[test]
+    fn test_pixelate_average_with_partial_blocks() {
+        let img = create_test_image();
+        let out = pixelate(&img, 2, 2, BlockColor::Average, None);
+        assert_eq!(out.dimensions(), (5, 3));
+        // First block covers x=0..2, y=0..2
+        assert_eq!(*out.get_pixel(1, 1), Rgb([25, 50, 30]));
+        // Partial block at the bottom-right corner is a single pixel
+        assert_eq!(*out.get_pixel(4, 2), *img.get_pixel(4, 2));
+        // Every pixel of a block shares the same color
+        assert_eq!(out.get_pixel(2, 0), out.get_pixel(3, 1));
+    }
+
+    #[test]
+    fn test_pixelate_median_and_center() {
+        let img: ImageBuffer<Rgb<u8>, Vec<u8>> = ImageBuffer::from_fn(3, 1, |x, _| {
+            if x == 2 {
+                Rgb([255, 255, 255])
+            } else {
+                Rgb([10, 20, 30])
+            }
+        });
+        let median = pixelate(&img, 3, 1, BlockColor::Median, None);
+        assert_eq!(*median.get_pixel(2, 0), Rgb([10, 20, 30]));
+        let center = pixelate(&img, 3, 1, BlockColor::Center, None);
+        assert_eq!(*center.get_pixel(0, 0), *img.get_pixel(1, 0));
+    }
+
+    #[test]
+    fn test_pixelate_grid_overlay() {
+        let img = create_test_image();
+        let line = Rgb([255, 0, 255]);
+        let out = pixelate(&img, 2, 2, BlockColor::Average, Some(line));
+        assert_eq!(*out.get_pixel(2, 0), line);
+        assert_eq!(*out.get_pixel(0, 2), line);
+        assert_ne!(*out.get_pixel(1, 1), line);
+    }
+}
@@ -0,0 +1,291 @@
+//! Threshold maps for ordered dithering.
+//!
+//! A [`ThresholdMap`] is a small tile of thresholds in `(0, 1)` that is repeated
+//! across the image. Maps are built from *ranks*: the cell with rank `r` out of
+//! `n` cells gets threshold `(r + 0.5) / n`, so every map yields `n + 1` evenly
+//! spaced output levels regardless of its size.
+use std::str::FromStr;
+
+use super::convolution::parse_matrix;
+use super::rng::SplitMix64;
+
+/// Gaussian spread used by the void-and-cluster energy filter (Ulichney, 1993).
+const VOID_CLUSTER_SIGMA: f32 = 1.5;
+
+/// Seed of the initial random pattern for void-and-cluster generation.
+const VOID_CLUSTER_SEED: u64 = 0x5EED_B1CE;
+
+/// A tileable ordered-dither threshold matrix.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ThresholdMap {
+    width: u32,
+    height: u32,
+    values: Vec<f32>,
+}
+
+impl ThresholdMap {
+    /// Build a map from per-cell ranks given in row-major order.
+    ///
+    /// Ranks only need to be ordered, not contiguous; ties are broken by
+    /// position. Panics if `ranks.len() != width * height` or the map is empty.
+    pub fn from_ranks(width: u32, height: u32, ranks: &[f32]) -> Self {
+        let n = (width * height) as usize;
+        assert!(n > 0, "Threshold map must not be empty");
+        assert_eq!(ranks.len(), n, "Rank count must equal width * height");
+        let mut order: Vec<usize> = (0..n).collect();
+        order.sort_by(|&a, &b| ranks[a].total_cmp(&ranks[b]));
+        let mut values = vec![0.0f32; n];
+        for (rank, &idx) in order.iter().enumerate() {
+            values[idx] = (rank as f32 + 0.5) / n as f32;
+        }
+        Self {
+            width,
+            height,
+            values,
+        }
+    }
+
+    /// Recursively generated `size` x `size` Bayer matrix.
+    ///
+    /// `size` must be a power of two (2, 4, 8, 16, ...). The map holds the
+    /// transpose of the textbook matrix (a Bayer matrix as well), which keeps
+    /// the default 4x4 halftone identical to the original `pattern[x][y]`
+    /// lookup.
+    pub fn bayer(size: u32) -> Self {
+        assert!(
+            size >= 2 && size.is_power_of_two(),
+            "Bayer size must be a power of two >= 2"
+        );
+        // Transposed M(2n) = [[4M, 4M + 3], [4M + 2, 4M + 1]], from M(1) = [0].
+        let mut m: Vec<u32> = vec![0];
+        let mut n = 1u32;
+        while n < size {
+            let next = n * 2;
+            let mut grown = vec![0u32; (next * next) as usize];
+            for y in 0..next {
+                for x in 0..next {
+                    let base = 4 * m[((y % n) * n + (x % n)) as usize];
+                    let offset = match (x / n, y / n) {
+                        (0, 0) => 0,
+                        (1, 0) => 3,
+                        (0, _) => 2,
+                        _ => 1,
+                    };
+                    grown[(y * next + x) as usize] = base + offset;
+                }
+            }
+            m = grown;
+            n = next;
+        }
+        let ranks: Vec<f32> = m.iter().map(|&v| v as f32).collect();
+        Self::from_ranks(size, size, &ranks)
+    }
+
+    /// `size` x `size` clustered-dot matrix: dots grow outward from the cell
+    /// center, mimicking an AM print screen.
+    pub fn cluster_dot(size: u32) -> Self {
+        assert!(size >= 2, "Cluster-dot size must be >= 2");
+        let c = (size as f32 - 1.0) / 2.0;
+        let mut keys: Vec<(f32, f32)> = Vec::with_capacity((size * size) as usize);
+        for y in 0..size {
+            for x in 0..size {
+                let (dx, dy) = (x as f32 - c, y as f32 - c);
+                keys.push(((dx * dx + dy * dy).sqrt(), dy.atan2(dx)));
+            }
+        }
+        // Farthest cells get the lowest thresholds so the central dot stays
+        // dark longest; ties are broken by angle to spiral the growth.
+        let mut order: Vec<usize> = (0..keys.len()).collect();
+        order.sort_by(|&a, &b| {
+            keys[b]
+                .0
+                .total_cmp(&keys[a].0)
+                .then(keys[a].1.total_cmp(&keys[b].1))
+        });
+        let mut ranks = vec![0.0f32; keys.len()];
+        for (rank, &idx) in order.iter().enumerate() {
+            ranks[idx] = rank as f32;
+        }
+        Self::from_ranks(size, size, &ranks)
+    }
+
+    /// `size` x `size` blue-noise matrix generated with Ulichney's
+    /// void-and-cluster algorithm. The result is deterministic.
+    pub fn blue_noise(size: u32) -> Self {
+        assert!(size >= 2, "Blue-noise size must be >= 2");
+        let s = size as usize;
+        let n = s * s;
+
+        // Toroidal Gaussian weights indexed by (dy * s + dx).
+        let mut lut = vec![0.0f32; n];
+        for dy in 0..s {
+            for dx in 0..s {
+                let wx = dx.min(s - dx) as f32;
+                let wy = dy.min(s - dy) as f32;
+                lut[dy * s + dx] =
+                    (-(wx * wx + wy * wy) / (2.0 * VOID_CLUSTER_SIGMA * VOID_CLUSTER_SIGMA)).exp();
+            }
+        }
+        let update = |energy: &mut [f32], idx: usize, sign: f32| {
+            let (px, py) = (idx % s, idx / s);
+            for y in 0..s {
+                for x in 0..s {
+                    let dx = (x + s - px) % s;
+                    let dy = (y + s - py) % s;
+                    energy[y * s + x] += sign * lut[dy * s + dx];
+                }
+            }
+        };
+        // Tightest cluster: the set pixel with the highest energy.
+        let tightest = |bits: &[bool], energy: &[f32]| {
+            (0..n)
+                .filter(|&i| bits[i])
+                .max_by(|&a, &b| energy[a].total_cmp(&energy[b]))
+                .unwrap()
+        };
+        // Largest void: the unset pixel with the lowest energy.
+        let largest_void = |bits: &[bool], energy: &[f32]| {
+            (0..n)
+                .filter(|&i| !bits[i])
+                .min_by(|&a, &b| energy[a].total_cmp(&energy[b]))
+                .unwrap()
+        };
+
+        // Initial binary pattern: roughly 10% of the pixels set at random.
+        let mut rng = SplitMix64::new(VOID_CLUSTER_SEED);
+        let ones = (n / 10).max(1);
+        let mut bits = vec![false; n];
+        let mut energy = vec![0.0f32; n];
+        let mut placed = 0;
+        while placed < ones {
+            let i = rng.next_below(n);
+            if !bits[i] {
+                bits[i] = true;
+                update(&mut energy, i, 1.0);
+                placed += 1;
+            }
+        }
+        // Relax the pattern by moving the tightest cluster into the largest void.
+        // Rounded energy updates can make swaps cycle, so stop after `n` moves.
+        for _ in 0..n {
+            let cluster = tightest(&bits, &energy);
+            bits[cluster] = false;
+            update(&mut energy, cluster, -1.0);
+            let void = largest_void(&bits, &energy);
+            bits[void] = true;
+            update(&mut energy, void, 1.0);
+            if void == cluster {
+                break;
+            }
+        }
+
+        let mut ranks = vec![0.0f32; n];
+        // Phase 1: remove set pixels from the prototype, ranking downward.
+        let (mut p_bits, mut p_energy) = (bits.clone(), energy.clone());
+        for rank in (0..ones).rev() {
+            let cluster = tightest(&p_bits, &p_energy);
+            p_bits[cluster] = false;
+            update(&mut p_energy, cluster, -1.0);
+            ranks[cluster] = rank as f32;
+        }
+        // Phase 2: fill voids from the prototype, ranking upward.
+        for rank in ones..n {
+            let void = largest_void(&bits, &energy);
+            bits[void] = true;
+            update(&mut energy, void, 1.0);
+            ranks[void] = rank as f32;
+        }
+        Self::from_ranks(size, size, &ranks)
+    }
+
+    /// Tile width in pixels.
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Tile height in pixels.
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Normalized threshold in `(0, 1)` at image position (`x`, `y`).
+    pub fn threshold(&self, x: u32, y: u32) -> f32 {
+        self.values[((y % self.height) * self.width + (x % self.width)) as usize]
+    }
+}
+
+impl Default for ThresholdMap {
+    /// The classic 4x4 Bayer matrix.
+    fn default() -> Self {
+        Self::bayer(4)
+    }
+}
+
+impl FromStr for ThresholdMap {
+    type Err = String;
+
+    /// Parse a custom matrix of ranks in the [`parse_matrix`] format.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (width, height, ranks) = parse_matrix(s, "threshold matrix")?;
+        Ok(Self::from_ranks(width as u32, height as u32, &ranks))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Recover integer ranks from normalized thresholds.
+    fn ranks(map: &ThresholdMap) -> Vec<u32> {
+        let n = map.values.len() as f32;
+        map.values
+            .iter()
+            .map(|t| (t * n - 0.5).round() as u32)
+            .collect()
+    }
+
+    #[test]
+    fn test_bayer_matches_classic_matrices() {
+        // Row-major ranks: the transpose of the textbook matrices
+        assert_eq!(ranks(&ThresholdMap::bayer(2)), vec![0, 3, 2, 1]);
+        assert_eq!(
+            ranks(&ThresholdMap::bayer(4)),
+            vec![0, 12, 3, 15, 8, 4, 11, 7, 2, 14, 1, 13, 10, 6, 9, 5]
+        );
+        let map = ThresholdMap::bayer(4);
+        assert!(map.threshold(1, 0) > map.threshold(0, 1));
+        let big = ThresholdMap::bayer(16);
+        let mut r = ranks(&big);
+        r.sort_unstable();
+        assert_eq!(r, (0..256).collect::<Vec<u32>>());
+    }
+
+    #[test]
+    fn test_cluster_dot_center_is_darkest() {
+        let map = ThresholdMap::cluster_dot(5);
+        let center = map.threshold(2, 2);
+        assert!(map.values.iter().all(|&t| t <= center));
+        assert!(map.threshold(0, 0) < map.threshold(1, 1));
+    }
+
+    #[test]
+    fn test_blue_noise_is_a_permutation() {
+        let map = ThresholdMap::blue_noise(8);
+        let mut r = ranks(&map);
+        r.sort_unstable();
+        assert_eq!(r, (0..64).collect::<Vec<u32>>());
+        // Deterministic across runs
+        assert_eq!(map, ThresholdMap::blue_noise(8));
+    }
+
+    #[test]
+    fn test_parse_custom_matrix() {
+        let map: ThresholdMap = "# 2x2\n0, 3\n2 1".parse().unwrap();
+        assert_eq!((map.width(), map.height()), (2, 2));
+        assert_eq!(map, ThresholdMap::bayer(2));
+        let inline: ThresholdMap = "0 3; 2 1".parse().unwrap();
+        assert_eq!(inline, map);
+        assert!("1 2\n3".parse::<ThresholdMap>().is_err());
+        assert!("".parse::<ThresholdMap>().is_err());
+    }
+}
@@ -0,0 +1,61 @@
+//! Snap every pixel of an RGB image to its nearest palette color.
+use std::collections::HashMap;
+
+use image::{ImageBuffer, Rgb};
+
+use crate::color::{ColorMetric, Palette};
+
+/// Map each pixel to the nearest palette entry under `metric`.
+///
+/// # Arguments
+/// * `img` - The input RGB image
+/// * `palette` - Target palette
+/// * `metric` - Distance used to pick the nearest color
+///
+/// # Returns
+/// RGB image containing only palette colors
+pub fn palette_map(
+    img: &ImageBuffer<Rgb<u8>, Vec<u8>>,
+    palette: &Palette,
+    metric: ColorMetric,
+) -> ImageBuffer<Rgb<u8>, Vec<u8>> {
+    // Convert the palette once; photos repeat colors, so cache lookups too.
+    let entries: Vec<[f32; 3]> = palette
+        .colors()
+        .iter()
+        .map(|&c| metric.coordinates(c))
+        .collect();
+    let mut cache: HashMap<[u8; 3], Rgb<u8>> = HashMap::new();
+    let mut out = img.clone();
+    for pixel in out.pixels_mut() {
+        *pixel = *cache.entry(pixel.0).or_insert_with(|| {
+            let target = metric.coordinates(*pixel);
+            let (idx, _) = entries
+                .iter()
+                .map(|&e| metric.distance(target, e))
+                .enumerate()
+                .min_by(|a, b| a.1.total_cmp(&b.1))
+                .unwrap();
+            palette.colors()[idx]
+        });
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{ImageBuffer, Rgb};
+
+    #[test]
+    fn test_palette_map() {
+        let img: ImageBuffer<Rgb<u8>, Vec<u8>> =
+            ImageBuffer::from_fn(4, 1, |x, _| Rgb([(x * 80) as u8, 10, 10]));
+        let palette = Palette::new(vec![Rgb([0, 0, 0]), Rgb([255, 0, 0])]);
+        for metric in [ColorMetric::Rgb, ColorMetric::Redmean, ColorMetric::Oklab] {
+            let out = palette_map(&img, &palette, metric);
+            assert_eq!(*out.get_pixel(0, 0), Rgb([0, 0, 0]));
+            assert_eq!(*out.get_pixel(3, 0), Rgb([255, 0, 0]));
+        }
+    }
+}
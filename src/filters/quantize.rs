@@ -0,0 +1,547 @@
+//! Palette quantization: build an N-color palette that best represents an image.
+//!
+//! Colors are first accumulated into a 5-bit-per-channel histogram (32768 bins,
+//! each keeping the exact mean of the colors that fell into it). All methods
+//! work on these weighted bins, which keeps them fast on large images.
+use image::{ImageBuffer, Rgb};
+
+use super::rng::SplitMix64;
+use crate::color::Palette;
+
+/// Bits kept per channel in the quantization histogram.
+const HIST_BITS: u32 = 5;
+/// Number of bins per channel.
+const HIST_SIDE: usize = 1 << HIST_BITS;
+/// Deepest octree level (one level per bit of an 8-bit channel).
+const OCTREE_DEPTH: usize = 8;
+/// Upper bound on Lloyd iterations for k-means.
+const KMEANS_MAX_ITERATIONS: usize = 32;
+
+/// Palette construction algorithm.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum QuantizeMethod {
+    /// Heckbert's median cut: recursively split the most spread-out color box.
+    MedianCut,
+    /// Gervautz–Purgathofer octree: merge the least-populated tree leaves.
+    Octree,
+    /// Lloyd's k-means with k-means++ seeding (deterministic for a given seed).
+    KMeans,
+    /// Wu's greedy variance-minimizing box splitting.
+    Wu,
+}
+
+/// A non-empty histogram bin: mean color and pixel count.
+#[derive(Copy, Clone, Debug)]
+struct Sample {
+    color: [f64; 3],
+    weight: f64,
+}
+
+/// Build a palette of at most `colors` entries for `img`.
+///
+/// # Arguments
+/// * `img` - The input RGB image
+/// * `colors` - Maximum palette size (>= 1)
+/// * `method` - Quantization algorithm
+/// * `seed` - Random seed (k-means only)
+///
+/// # Returns
+/// Palette with between 1 and `colors` entries
+pub fn build_palette(
+    img: &ImageBuffer<Rgb<u8>, Vec<u8>>,
+    colors: usize,
+    method: QuantizeMethod,
+    seed: u64,
+) -> Palette {
+    assert!(colors > 0, "At least one palette color is required");
+    let samples = histogram(img);
+    if samples.is_empty() {
+        return Palette::new(vec![Rgb([0, 0, 0])]);
+    }
+    let centers = match method {
+        QuantizeMethod::MedianCut => median_cut(&samples, colors),
+        QuantizeMethod::Octree => octree(&samples, colors),
+        QuantizeMethod::KMeans => kmeans(&samples, colors, seed),
+        QuantizeMethod::Wu => wu(img, colors),
+    };
+    let mut out: Vec<Rgb<u8>> = Vec::with_capacity(centers.len());
+    for c in centers {
+        let px = Rgb(c.map(|v| v.round().clamp(0.0, 255.0) as u8));
+        if !out.contains(&px) {
+            out.push(px);
+        }
+    }
+    Palette::new(out)
+}
+
+/// Accumulate the image into non-empty 5-bit histogram bins.
+fn histogram(img: &ImageBuffer<Rgb<u8>, Vec<u8>>) -> Vec<Sample> {
+    let shift = 8 - HIST_BITS;
+    let mut bins = vec![(0u64, [0u64; 3]); HIST_SIDE * HIST_SIDE * HIST_SIDE];
+    for p in img.pixels() {
+        let [r, g, b] = p.0.map(|c| (c >> shift) as usize);
+        let bin = &mut bins[(r * HIST_SIDE + g) * HIST_SIDE + b];
+        bin.0 += 1;
+        for n in 0..3 {
+            bin.1[n] += p[n] as u64;
+        }
+    }
+    bins.into_iter()
+        .filter(|(count, _)| *count > 0)
+        .map(|(count, sum)| Sample {
+            color: sum.map(|s| s as f64 / count as f64),
+            weight: count as f64,
+        })
+        .collect()
+}
+
+/// Weighted mean color of a set of samples.
+fn weighted_mean(samples: &[Sample]) -> [f64; 3] {
+    let mut sum = [0.0f64; 3];
+    let mut total = 0.0;
+    for s in samples {
+        for (acc, c) in sum.iter_mut().zip(s.color) {
+            *acc += c * s.weight;
+        }
+        total += s.weight;
+    }
+    sum.map(|v| v / total)
+}
+
+fn median_cut(samples: &[Sample], colors: usize) -> Vec<[f64; 3]> {
+    let mut boxes: Vec<Vec<Sample>> = vec![samples.to_vec()];
+    while boxes.len() < colors {
+        // Split the box with the widest channel range.
+        let Some((idx, channel, _)) = boxes
+            .iter()
+            .enumerate()
+            .filter(|(_, b)| b.len() > 1)
+            .map(|(i, b)| {
+                let (channel, range) = (0..3)
+                    .map(|n| {
+                        let lo = b.iter().map(|s| s.color[n]).fold(f64::INFINITY, f64::min);
+                        let hi = b.iter().map(|s| s.color[n]).fold(0.0, f64::max);
+                        (n, hi - lo)
+                    })
+                    .max_by(|a, b| a.1.total_cmp(&b.1))
+                    .unwrap();
+                (i, channel, range)
+            })
+            .max_by(|a, b| a.2.total_cmp(&b.2))
+        else {
+            break;
+        };
+        let mut b = boxes.swap_remove(idx);
+        b.sort_by(|x, y| x.color[channel].total_cmp(&y.color[channel]));
+        // Cut at the weighted median, keeping both halves non-empty.
+        let half: f64 = b.iter().map(|s| s.weight).sum::<f64>() / 2.0;
+        let mut acc = 0.0;
+        let mut cut = 1;
+        for (i, s) in b.iter().enumerate() {
+            acc += s.weight;
+            if acc >= half {
+                cut = (i + 1).clamp(1, b.len() - 1);
+                break;
+            }
+        }
+        let upper = b.split_off(cut);
+        boxes.push(b);
+        boxes.push(upper);
+    }
+    boxes.iter().map(|b| weighted_mean(b)).collect()
+}
+
+/// Octree node stored in an arena.
+#[derive(Default)]
+struct OctreeNode {
+    children: [Option<usize>; 8],
+    weight: f64,
+    sum: [f64; 3],
+    leaf: bool,
+}
+
+fn octree(samples: &[Sample], colors: usize) -> Vec<[f64; 3]> {
+    let mut nodes: Vec<OctreeNode> = vec![OctreeNode::default()];
+    // Nodes with children, per depth, so the deepest ones can be merged first.
+    let mut reducible: Vec<Vec<usize>> = vec![Vec::new(); OCTREE_DEPTH];
+    let mut leaves = 0usize;
+
+    for s in samples {
+        let rgb = s.color.map(|v| v.round() as u8);
+        let mut node = 0;
+        for (depth, level) in reducible.iter_mut().enumerate() {
+            let bit = 7 - depth;
+            let child = (((rgb[0] >> bit) & 1) << 2
+                | ((rgb[1] >> bit) & 1) << 1
+                | ((rgb[2] >> bit) & 1)) as usize;
+            node = match nodes[node].children[child] {
+                Some(next) => next,
+                None => {
+                    if nodes[node].children.iter().all(Option::is_none) {
+                        level.push(node);
+                    }
+                    nodes.push(OctreeNode::default());
+                    let next = nodes.len() - 1;
+                    nodes[node].children[child] = Some(next);
+                    next
+                }
+            };
+        }
+        let leaf = &mut nodes[node];
+        if !leaf.leaf {
+            leaf.leaf = true;
+            leaves += 1;
+        }
+        leaf.weight += s.weight;
+        for n in 0..3 {
+            leaf.sum[n] += s.color[n] * s.weight;
+        }
+    }
+
+    let mut sorted_depth = None;
+    while leaves > colors {
+        let Some(depth) = (0..OCTREE_DEPTH).rev().find(|&d| !reducible[d].is_empty()) else {
+            break;
+        };
+        // Merge the least-populated node at the deepest level. Weights at a
+        // level are final once every deeper level has been merged, so each
+        // level is sorted (descending) only once.
+        if sorted_depth != Some(depth) {
+            reducible[depth]
+                .sort_by(|&a, &b| subtree_weight(&nodes, b).total_cmp(&subtree_weight(&nodes, a)));
+            sorted_depth = Some(depth);
+        }
+        let node = reducible[depth].pop().unwrap();
+        let children: Vec<usize> = nodes[node].children.iter().flatten().copied().collect();
+        for &c in &children {
+            nodes[c].leaf = false;
+            let (w, sum) = (nodes[c].weight, nodes[c].sum);
+            nodes[node].weight += w;
+            for (acc, c) in nodes[node].sum.iter_mut().zip(sum) {
+                *acc += c;
+            }
+        }
+        nodes[node].children = [None; 8];
+        nodes[node].leaf = true;
+        leaves = leaves + 1 - children.len();
+    }
+
+    nodes
+        .iter()
+        .filter(|n| n.leaf)
+        .map(|n| n.sum.map(|v| v / n.weight))
+        .collect()
+}
+
+/// Pixel count of a node whose children are all leaves.
+fn subtree_weight(nodes: &[OctreeNode], node: usize) -> f64 {
+    nodes[node]
+        .children
+        .iter()
+        .flatten()
+        .map(|&c| nodes[c].weight)
+        .sum()
+}
+
+fn kmeans(samples: &[Sample], colors: usize, seed: u64) -> Vec<[f64; 3]> {
+    let k = colors.min(samples.len());
+    let mut rng = SplitMix64::new(seed);
+    let dist = |a: [f64; 3], b: [f64; 3]| (0..3).map(|n| (a[n] - b[n]).powi(2)).sum::<f64>();
+    // Draw an index with probability proportional to `weights`.
+    let mut pick = |weights: &[f64]| {
+        let total: f64 = weights.iter().sum();
+        let mut target = (rng.next_u64() >> 11) as f64 / (1u64 << 53) as f64 * total;
+        for (i, &w) in weights.iter().enumerate() {
+            if target < w {
+                return i;
+            }
+            target -= w;
+        }
+        weights.len() - 1
+    };
+
+    // k-means++ seeding.
+    let weights: Vec<f64> = samples.iter().map(|s| s.weight).collect();
+    let mut centers: Vec<[f64; 3]> = vec![samples[pick(&weights)].color];
+    let mut nearest_d: Vec<f64> = samples.iter().map(|s| dist(s.color, centers[0])).collect();
+    while centers.len() < k {
+        let scores: Vec<f64> = samples
+            .iter()
+            .zip(&nearest_d)
+            .map(|(s, d)| s.weight * d)
+            .collect();
+        if scores.iter().sum::<f64>() <= 0.0 {
+            break;
+        }
+        let c = samples[pick(&scores)].color;
+        for (d, s) in nearest_d.iter_mut().zip(samples) {
+            *d = d.min(dist(s.color, c));
+        }
+        centers.push(c);
+    }
+
+    // Lloyd iterations.
+    let mut assignment = vec![usize::MAX; samples.len()];
+    for _ in 0..KMEANS_MAX_ITERATIONS {
+        let mut changed = false;
+        for (a, s) in assignment.iter_mut().zip(samples) {
+            let best = (0..centers.len())
+                .min_by(|&i, &j| dist(s.color, centers[i]).total_cmp(&dist(s.color, centers[j])))
+                .unwrap();
+            if *a != best {
+                *a = best;
+                changed = true;
+            }
+        }
+        if !changed {
+            break;
+        }
+        let mut sums = vec![([0.0f64; 3], 0.0f64); centers.len()];
+        for (&a, s) in assignment.iter().zip(samples) {
+            for n in 0..3 {
+                sums[a].0[n] += s.color[n] * s.weight;
+            }
+            sums[a].1 += s.weight;
+        }
+        for (c, (sum, w)) in centers.iter_mut().zip(sums) {
+            // Empty clusters keep their previous center.
+            if w > 0.0 {
+                *c = sum.map(|v| v / w);
+            }
+        }
+    }
+    centers
+}
+
+/// Axis-aligned box in Wu's cumulative moment space; lower bounds are exclusive.
+#[derive(Copy, Clone, Default)]
+struct WuBox {
+    lo: [usize; 3],
+    hi: [usize; 3],
+}
+
+/// Cumulative color moments over a `(HIST_SIDE + 1)^3` lattice.
+struct WuMoments {
+    weight: Vec<f64>,
+    sum: [Vec<f64>; 3],
+    sq: Vec<f64>,
+}
+
+const WU_SIDE: usize = HIST_SIDE + 1;
+
+fn wu_index(r: usize, g: usize, b: usize) -> usize {
+    (r * WU_SIDE + g) * WU_SIDE + b
+}
+
+impl WuMoments {
+    fn new(img: &ImageBuffer<Rgb<u8>, Vec<u8>>) -> Self {
+        let len = WU_SIDE * WU_SIDE * WU_SIDE;
+        let mut m = Self {
+            weight: vec![0.0; len],
+            sum: [vec![0.0; len], vec![0.0; len], vec![0.0; len]],
+            sq: vec![0.0; len],
+        };
+        let shift = 8 - HIST_BITS;
+        for p in img.pixels() {
+            let [r, g, b] = p.0.map(|c| (c >> shift) as usize + 1);
+            let i = wu_index(r, g, b);
+            m.weight[i] += 1.0;
+            let mut sq = 0.0;
+            for n in 0..3 {
+                let v = p[n] as f64;
+                m.sum[n][i] += v;
+                sq += v * v;
+            }
+            m.sq[i] += sq;
+        }
+        // Turn the histogram into 3D prefix sums.
+        let [sum_r, sum_g, sum_b] = &mut m.sum;
+        for table in [&mut m.weight, sum_r, sum_g, sum_b, &mut m.sq] {
+            for axis in 0..3 {
+                for r in 1..WU_SIDE {
+                    for g in 1..WU_SIDE {
+                        for b in 1..WU_SIDE {
+                            let prev = match axis {
+                                0 => wu_index(r - 1, g, b),
+                                1 => wu_index(r, g - 1, b),
+                                _ => wu_index(r, g, b - 1),
+                            };
+                            table[wu_index(r, g, b)] += table[prev];
+                        }
+                    }
+                }
+            }
+        }
+        m
+    }
+
+    /// Sum of `table` over the box (inclusion–exclusion on the corners).
+    fn volume(table: &[f64], c: &WuBox) -> f64 {
+        let ([r0, g0, b0], [r1, g1, b1]) = (c.lo, c.hi);
+        table[wu_index(r1, g1, b1)] - table[wu_index(r1, g1, b0)] - table[wu_index(r1, g0, b1)]
+            + table[wu_index(r1, g0, b0)]
+            - table[wu_index(r0, g1, b1)]
+            + table[wu_index(r0, g1, b0)]
+            + table[wu_index(r0, g0, b1)]
+            - table[wu_index(r0, g0, b0)]
+    }
+
+    /// Sum of `table` over the box with its upper bound on `axis` moved to `pos`.
+    fn lower_part(table: &[f64], c: &WuBox, axis: usize, pos: usize) -> f64 {
+        let mut part = *c;
+        part.hi[axis] = pos;
+        Self::volume(table, &part)
+    }
+
+    /// `[weight, sum_r, sum_g, sum_b]` over a box.
+    fn stats(&self, c: &WuBox) -> [f64; 4] {
+        [
+            Self::volume(&self.weight, c),
+            Self::volume(&self.sum[0], c),
+            Self::volume(&self.sum[1], c),
+            Self::volume(&self.sum[2], c),
+        ]
+    }
+
+    /// Total squared deviation of the colors in a box from their mean.
+    fn variance(&self, c: &WuBox) -> f64 {
+        let [w, r, g, b] = self.stats(c);
+        if w <= 0.0 {
+            return 0.0;
+        }
+        Self::volume(&self.sq, c) - (r * r + g * g + b * b) / w
+    }
+
+    /// Split `c` where it maximizes the between-part variance; returns the two
+    /// halves or `None` if the box cannot be split.
+    fn cut(&self, c: &WuBox) -> Option<(WuBox, WuBox)> {
+        let whole = self.stats(c);
+        let score = |s: [f64; 4]| (s[1] * s[1] + s[2] * s[2] + s[3] * s[3]) / s[0];
+        let mut best: Option<(f64, usize, usize)> = None;
+        for axis in 0..3 {
+            for pos in c.lo[axis] + 1..c.hi[axis] {
+                let lower = [
+                    Self::lower_part(&self.weight, c, axis, pos),
+                    Self::lower_part(&self.sum[0], c, axis, pos),
+                    Self::lower_part(&self.sum[1], c, axis, pos),
+                    Self::lower_part(&self.sum[2], c, axis, pos),
+                ];
+                let upper = [0, 1, 2, 3].map(|n| whole[n] - lower[n]);
+                if lower[0] <= 0.0 || upper[0] <= 0.0 {
+                    continue;
+                }
+                let gain = score(lower) + score(upper);
+                let better = match best {
+                    Some((g, _, _)) => gain > g,
+                    None => true,
+                };
+                if better {
+                    best = Some((gain, axis, pos));
+                }
+            }
+        }
+        let (_, axis, pos) = best?;
+        let (mut a, mut b) = (*c, *c);
+        a.hi[axis] = pos;
+        b.lo[axis] = pos;
+        Some((a, b))
+    }
+}
+
+fn wu(img: &ImageBuffer<Rgb<u8>, Vec<u8>>, colors: usize) -> Vec<[f64; 3]> {
+    let moments = WuMoments::new(img);
+    let mut boxes = vec![WuBox {
+        lo: [0; 3],
+        hi: [HIST_SIDE; 3],
+    }];
+    let mut variances = vec![moments.variance(&boxes[0])];
+    while boxes.len() < colors {
+        // Split the box with the largest variance.
+        let (idx, &v) = variances
+            .iter()
+            .enumerate()
+            .max_by(|a, b| a.1.total_cmp(b.1))
+            .unwrap();
+        if v <= 0.0 {
+            break;
+        }
+        match moments.cut(&boxes[idx]) {
+            Some((a, b)) => {
+                boxes[idx] = a;
+                variances[idx] = moments.variance(&a);
+                boxes.push(b);
+                variances.push(moments.variance(&b));
+            }
+            None => variances[idx] = 0.0,
+        }
+    }
+    boxes
+        .iter()
+        .map(|c| moments.stats(c))
+        .filter(|s| s[0] > 0.0)
+        .map(|[w, r, g, b]| [r / w, g / w, b / w])
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::palette_map::palette_map;
+    use super::*;
+    use crate::color::ColorMetric;
+    use image::{ImageBuffer, Rgb};
+
+    /// Four flat quadrants of distinct colors.
+    fn create_test_image() -> ImageBuffer<Rgb<u8>, Vec<u8>> {
+        ImageBuffer::from_fn(8, 8, |x, y| match (x < 4, y < 4) {
+            (true, true) => Rgb([250, 10, 10]),
+            (false, true) => Rgb([10, 250, 10]),
+            (true, false) => Rgb([10, 10, 250]),
+            (false, false) => Rgb([240, 240, 240]),
+        })
+    }
+
+    const METHODS: [QuantizeMethod; 4] = [
+        QuantizeMethod::MedianCut,
+        QuantizeMethod::Octree,
+        QuantizeMethod::KMeans,
+        QuantizeMethod::Wu,
+    ];
+
+    #[test]
+    fn test_exact_palette_when_enough_colors() {
+        let img = create_test_image();
+        for method in METHODS {
+            let palette = build_palette(&img, 4, method, 0);
+            assert_eq!(palette.len(), 4, "{:?}", method);
+            let out = palette_map(&img, &palette, ColorMetric::Rgb);
+            assert_eq!(out, img, "{:?} did not reproduce the image", method);
+        }
+    }
+
+    #[test]
+    fn test_palette_size_is_bounded() {
+        let img: ImageBuffer<Rgb<u8>, Vec<u8>> = ImageBuffer::from_fn(32, 32, |x, y| {
+            Rgb([(x * 8) as u8, (y * 8) as u8, ((x + y) * 4) as u8])
+        });
+        for method in METHODS {
+            let palette = build_palette(&img, 16, method, 7);
+            assert!(
+                (1..=16).contains(&palette.len()),
+                "{:?} gave {}",
+                method,
+                palette.len()
+            );
+            let out = palette_map(&img, &palette, ColorMetric::Rgb);
+            assert!(out.pixels().all(|p| palette.colors().contains(p)));
+        }
+    }
+
+    #[test]
+    fn test_kmeans_is_deterministic_per_seed() {
+        let img: ImageBuffer<Rgb<u8>, Vec<u8>> =
+            ImageBuffer::from_fn(16, 16, |x, y| Rgb([(x * 16) as u8, (y * 16) as u8, 90]));
+        let a = build_palette(&img, 6, QuantizeMethod::KMeans, 42);
+        let b = build_palette(&img, 6, QuantizeMethod::KMeans, 42);
+        assert_eq!(a, b);
+    }
+}
@@ -1,18 +1,35 @@
 //! Sobel edge detection built on top of reusable convolution.
 use image::{ImageBuffer, Luma, Rgb};
 
-use super::convolution::{convolve_gray_f32, magnitude_to_luma_u8_scaled};
+use super::convolution::{
+    convolve_plane_f32, magnitude_to_luma_u8_scaled, BorderMode, Kernel, MagnitudeScale,
+};
 use super::grayscale;
 
-const SOBEL_X: [[f32; 3]; 3] = [[-1.0, 0.0, 1.0], [-2.0, 0.0, 2.0], [-1.0, 0.0, 1.0]];
+pub(crate) const SOBEL_X: [[f32; 3]; 3] = [[-1.0, 0.0, 1.0], [-2.0, 0.0, 2.0], [-1.0, 0.0, 1.0]];
 
-const SOBEL_Y: [[f32; 3]; 3] = [[-1.0, -2.0, -1.0], [0.0, 0.0, 0.0], [1.0, 2.0, 1.0]];
+pub(crate) const SOBEL_Y: [[f32; 3]; 3] = [[-1.0, -2.0, -1.0], [0.0, 0.0, 0.0], [1.0, 2.0, 1.0]];
+
+/// Horizontal and vertical Sobel derivatives `(gx, gy)` of a row-major plane.
+pub fn sobel_gradients(
+    plane: &[f32],
+    width: u32,
+    height: u32,
+    border: BorderMode,
+) -> (Vec<f32>, Vec<f32>) {
+    let gx = convolve_plane_f32(plane, width, height, &Kernel::from_square(&SOBEL_X), border);
+    let gy = convolve_plane_f32(plane, width, height, &Kernel::from_square(&SOBEL_Y), border);
+    (gx, gy)
+}
 
 /// Apply Sobel edge detection to an RGB image.
 /// Internally converts to grayscale, computes Gx and Gy, and outputs magnitude.
+/// `scale` maps the magnitude to output levels and `border` controls how pixels
+/// outside the image are synthesized.
 pub fn sobel_edges(
     img: &ImageBuffer<Rgb<u8>, Vec<u8>>,
-    intensity: f32,
+    scale: MagnitudeScale,
+    border: BorderMode,
 ) -> ImageBuffer<Luma<u8>, Vec<u8>> {
     let gray: ImageBuffer<Luma<u8>, Vec<u8>> = grayscale::grayscale(
         img,
@@ -22,9 +39,9 @@ pub fn sobel_edges(
     );
 
     let (w, h) = gray.dimensions();
-    let gx: Vec<f32> = convolve_gray_f32::<3>(&gray, &SOBEL_X);
-    let gy: Vec<f32> = convolve_gray_f32::<3>(&gray, &SOBEL_Y);
-    magnitude_to_luma_u8_scaled(&gx, &gy, w, h, intensity)
+    let plane: Vec<f32> = gray.pixels().map(|p| p[0] as f32).collect();
+    let (gx, gy) = sobel_gradients(&plane, w, h, border);
+    magnitude_to_luma_u8_scaled(&gx, &gy, w, h, scale)
 }
 
 #[cfg(test)]
@@ -42,11 +59,26 @@ mod tests {
                 Rgb([240, 240, 240])
             }
         });
-        let edges = sobel_edges(&img, 1.0);
+        let edges = sobel_edges(&img, MagnitudeScale::default(), BorderMode::Zero);
         assert_eq!(edges.dimensions(), (6, 3));
         // Edge strength around the middle columns should be higher than corners
         let left_val = edges.get_pixel(0, 1)[0];
         let edge_val = edges.get_pixel(3, 1)[0];
         assert!(edge_val > left_val);
     }
+
+    #[test]
+    fn flat_image_has_no_border_edges() {
+        let img: ImageBuffer<Rgb<u8>, Vec<u8>> =
+            ImageBuffer::from_pixel(5, 5, Rgb([120, 120, 120]));
+        assert!(
+            sobel_edges(&img, MagnitudeScale::default(), BorderMode::Zero)
+                .pixels()
+                .any(|p| p[0] > 0)
+        );
+        for border in [BorderMode::Clamp, BorderMode::Reflect, BorderMode::Wrap] {
+            let edges = sobel_edges(&img, MagnitudeScale::default(), border);
+            assert!(edges.pixels().all(|p| p[0] == 0), "{:?}", border);
+        }
+    }
 }
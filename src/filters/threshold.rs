@@ -0,0 +1,258 @@
+//! Binarization of grayscale images with global and adaptive thresholds.
+//!
+//! Global methods pick one threshold for the whole image (fixed, Otsu,
+//! triangle); adaptive methods compare every pixel with statistics of its
+//! local window, which copes with uneven lighting in scanned documents.
+//! Output pixels are 0 or 255, like the halftone dithering.
+use image::{ImageBuffer, Luma};
+
+use super::convolution::{box_blur_running_sum_f32, gaussian_blur_f32, BorderMode};
+
+/// How the threshold is chosen.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ThresholdMethod {
+    /// Pixels brighter than the given level become white.
+    Fixed(u8),
+    /// Otsu's method: the level that maximizes the between-class variance.
+    Otsu,
+    /// Triangle method: the level farthest from the line joining the
+    /// histogram peak and the end of its longer tail. Suited to images with
+    /// one dominant background, such as text on paper.
+    Triangle,
+    /// Mean of the `(2 * radius + 1)`² window minus `offset`.
+    AdaptiveMean { radius: u32, offset: f32 },
+    /// Gaussian-weighted local mean (sigma = `radius / 3`) minus `offset`.
+    AdaptiveGaussian { radius: u32, offset: f32 },
+    /// Sauvola: `mean * (1 + k * (stddev / range - 1))` over the window.
+    Sauvola { radius: u32, k: f32, range: f32 },
+    /// Niblack: `mean + k * stddev` over the window (`k` is usually negative).
+    Niblack { radius: u32, k: f32 },
+}
+
+/// 256-bin histogram of a grayscale image.
+pub fn histogram(img: &ImageBuffer<Luma<u8>, Vec<u8>>) -> [u32; 256] {
+    let mut hist = [0u32; 256];
+    for p in img.pixels() {
+        hist[p[0] as usize] += 1;
+    }
+    hist
+}
+
+/// Otsu's threshold: pixels `<= t` form one class, the rest the other.
+pub fn otsu_threshold(hist: &[u32; 256]) -> u8 {
+    let total: f64 = hist.iter().map(|&c| c as f64).sum();
+    let sum_all: f64 = hist
+        .iter()
+        .enumerate()
+        .map(|(i, &c)| i as f64 * c as f64)
+        .sum();
+    let (mut weight_bg, mut sum_bg) = (0.0f64, 0.0f64);
+    let (mut best, mut best_variance) = (0u8, -1.0f64);
+    for (t, &count) in hist.iter().enumerate() {
+        weight_bg += count as f64;
+        sum_bg += t as f64 * count as f64;
+        let weight_fg = total - weight_bg;
+        if weight_bg == 0.0 || weight_fg == 0.0 {
+            continue;
+        }
+        let mean_bg = sum_bg / weight_bg;
+        let mean_fg = (sum_all - sum_bg) / weight_fg;
+        let variance = weight_bg * weight_fg * (mean_bg - mean_fg).powi(2);
+        if variance > best_variance {
+            best_variance = variance;
+            best = t as u8;
+        }
+    }
+    best
+}
+
+/// Triangle threshold: the bin farthest from the line between the histogram
+/// peak and the far end of the longer tail.
+pub fn triangle_threshold(hist: &[u32; 256]) -> u8 {
+    let Some(first) = hist.iter().position(|&c| c > 0) else {
+        return 0;
+    };
+    let last = hist.iter().rposition(|&c| c > 0).unwrap_or(first);
+    let peak = (first..=last).max_by_key(|&i| hist[i]).unwrap_or(first);
+    let end = if last - peak > peak - first {
+        last
+    } else {
+        first
+    };
+    if end == peak {
+        return peak as u8;
+    }
+    let (x0, y0) = (peak as f64, hist[peak] as f64);
+    let (dx, dy) = (end as f64 - x0, hist[end] as f64 - y0);
+    let range = if end > peak { peak..=end } else { end..=peak };
+    range
+        .max_by(|&a, &b| {
+            let distance = |i: usize| (dy * (i as f64 - x0) - dx * (hist[i] as f64 - y0)).abs();
+            distance(a).total_cmp(&distance(b))
+        })
+        .unwrap_or(peak) as u8
+}
+
+/// Local window mean of a plane.
+fn local_mean(plane: &[f32], width: u32, height: u32, radius: u32, border: BorderMode) -> Vec<f32> {
+    let horizontal = box_blur_running_sum_f32(plane, width, height, radius, true, border);
+    box_blur_running_sum_f32(&horizontal, width, height, radius, false, border)
+}
+
+/// Local window mean and standard deviation of a plane.
+fn local_mean_stddev(
+    plane: &[f32],
+    width: u32,
+    height: u32,
+    radius: u32,
+    border: BorderMode,
+) -> (Vec<f32>, Vec<f32>) {
+    let mean = local_mean(plane, width, height, radius, border);
+    let squares: Vec<f32> = plane.iter().map(|v| v * v).collect();
+    let mean_sq = local_mean(&squares, width, height, radius, border);
+    let stddev = mean
+        .iter()
+        .zip(&mean_sq)
+        .map(|(m, sq)| (sq - m * m).max(0.0).sqrt())
+        .collect();
+    (mean, stddev)
+}
+
+/// Binarize a grayscale image.
+///
+/// # Arguments
+/// * `img` - The input grayscale image
+/// * `method` - Global or adaptive threshold
+/// * `invert` - Output black where the pixel exceeds the threshold instead
+/// * `border` - How pixels outside the image are synthesized (adaptive methods)
+///
+/// # Returns
+/// Binary image: 255 where the pixel is brighter than its threshold, 0 elsewhere
+pub fn threshold(
+    img: &ImageBuffer<Luma<u8>, Vec<u8>>,
+    method: ThresholdMethod,
+    invert: bool,
+    border: BorderMode,
+) -> ImageBuffer<Luma<u8>, Vec<u8>> {
+    let (w, h) = img.dimensions();
+    let plane: Vec<f32> = img.pixels().map(|p| p[0] as f32).collect();
+    let levels: Vec<f32> = match method {
+        ThresholdMethod::Fixed(t) => vec![t as f32; plane.len()],
+        ThresholdMethod::Otsu => vec![otsu_threshold(&histogram(img)) as f32; plane.len()],
+        ThresholdMethod::Triangle => {
+            vec![triangle_threshold(&histogram(img)) as f32; plane.len()]
+        }
+        ThresholdMethod::AdaptiveMean { radius, offset } => {
+            local_mean(&plane, w, h, radius, border)
+                .into_iter()
+                .map(|m| m - offset)
+                .collect()
+        }
+        ThresholdMethod::AdaptiveGaussian { radius, offset } => {
+            let sigma = (radius as f32 / 3.0).max(f32::EPSILON);
+            gaussian_blur_f32(&plane, w, h, sigma, border)
+                .into_iter()
+                .map(|m| m - offset)
+                .collect()
+        }
+        ThresholdMethod::Sauvola { radius, k, range } => {
+            let (mean, stddev) = local_mean_stddev(&plane, w, h, radius, border);
+            mean.iter()
+                .zip(&stddev)
+                .map(|(m, s)| m * (1.0 + k * (s / range - 1.0)))
+                .collect()
+        }
+        ThresholdMethod::Niblack { radius, k } => {
+            let (mean, stddev) = local_mean_stddev(&plane, w, h, radius, border);
+            mean.iter().zip(&stddev).map(|(m, s)| m + k * s).collect()
+        }
+    };
+    ImageBuffer::from_fn(w, h, |x, y| {
+        let i = (y * w + x) as usize;
+        let white = (plane[i] > levels[i]) != invert;
+        Luma([if white { 255 } else { 0 }])
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bimodal() -> ImageBuffer<Luma<u8>, Vec<u8>> {
+        ImageBuffer::from_fn(8, 4, |x, y| {
+            Luma([if x < 4 { 40 + y as u8 } else { 200 - y as u8 }])
+        })
+    }
+
+    #[test]
+    fn test_otsu_separates_two_modes() {
+        let t = otsu_threshold(&histogram(&bimodal()));
+        assert!((43..197).contains(&t), "{}", t);
+        let out = threshold(
+            &bimodal(),
+            ThresholdMethod::Otsu,
+            false,
+            BorderMode::Reflect101,
+        );
+        assert_eq!(out.get_pixel(0, 0)[0], 0);
+        assert_eq!(out.get_pixel(7, 3)[0], 255);
+    }
+
+    #[test]
+    fn test_triangle_threshold_on_skewed_histogram() {
+        // Bright background peak with a long dark tail
+        let mut hist = [0u32; 256];
+        hist[220] = 1000;
+        for (i, h) in hist.iter_mut().enumerate().take(220).skip(20) {
+            *h = (i as u32 - 19) / 4;
+        }
+        let t = triangle_threshold(&hist);
+        assert!(t > 20 && t < 220, "{}", t);
+        assert_eq!(triangle_threshold(&[0; 256]), 0);
+    }
+
+    #[test]
+    fn test_adaptive_handles_gradient_lighting() {
+        // Dark strokes every fourth column on a background that brightens to the right
+        let img: ImageBuffer<Luma<u8>, Vec<u8>> = ImageBuffer::from_fn(32, 8, |x, _| {
+            let background = 60 + x as u8 * 6;
+            Luma([if x % 4 == 0 {
+                background / 3
+            } else {
+                background
+            }])
+        });
+        for method in [
+            ThresholdMethod::AdaptiveMean {
+                radius: 2,
+                offset: 5.0,
+            },
+            ThresholdMethod::AdaptiveGaussian {
+                radius: 3,
+                offset: 5.0,
+            },
+            ThresholdMethod::Sauvola {
+                radius: 2,
+                k: 0.2,
+                range: 128.0,
+            },
+            ThresholdMethod::Niblack { radius: 2, k: -0.2 },
+        ] {
+            let out = threshold(&img, method, false, BorderMode::Reflect101);
+            for x in 4..28 {
+                let expected = if x % 4 == 0 { 0 } else { 255 };
+                assert_eq!(out.get_pixel(x, 4)[0], expected, "{:?} x={}", method, x);
+            }
+        }
+    }
+
+    #[test]
+    fn test_fixed_and_invert() {
+        let img: ImageBuffer<Luma<u8>, Vec<u8>> =
+            ImageBuffer::from_fn(3, 1, |x, _| Luma([x as u8 * 100]));
+        let out = threshold(&img, ThresholdMethod::Fixed(100), false, BorderMode::Zero);
+        assert_eq!(out.into_raw(), vec![0, 0, 255]);
+        let out = threshold(&img, ThresholdMethod::Fixed(100), true, BorderMode::Zero);
+        assert_eq!(out.into_raw(), vec![255, 255, 0]);
+    }
+}
@@ -0,0 +1,230 @@
+//! Tonal adjustments built on per-channel lookup tables: levels, auto-levels,
+//! brightness/contrast and exposure.
+//!
+//! Every adjustment is compiled into a 256-entry table per channel, so the
+//! cost of applying it does not depend on how it was defined.
+use image::{ImageBuffer, Rgb};
+
+use crate::color::{linear_to_srgb, srgb_to_linear};
+
+/// A 256-entry tone curve for one 8-bit channel, unlike the color lookup
+/// tables in [`crate::color`] that map whole RGB triplets.
+pub type ToneCurve8 = [u8; 256];
+
+const MAX_PIXEL: f64 = 255.0;
+
+/// Build a lookup table from a function on `0.0..=255.0`, rounding and
+/// clamping its result.
+pub fn lut_from_fn(f: impl Fn(f64) -> f64) -> ToneCurve8 {
+    std::array::from_fn(|i| f(i as f64).round().clamp(0.0, MAX_PIXEL) as u8)
+}
+
+/// Apply one lookup table per channel (R, G, B).
+pub fn apply_luts(
+    img: &ImageBuffer<Rgb<u8>, Vec<u8>>,
+    luts: &[ToneCurve8; 3],
+) -> ImageBuffer<Rgb<u8>, Vec<u8>> {
+    let (width, height) = img.dimensions();
+    ImageBuffer::from_fn(width, height, |x, y| {
+        let p = img.get_pixel(x, y);
+        Rgb([0, 1, 2].map(|c| luts[c][p[c] as usize]))
+    })
+}
+
+/// Input and output ranges with a midtone gamma, as in a levels dialog.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Levels {
+    /// Input level mapped to `output_black`; darker pixels are clipped.
+    pub black: u8,
+    /// Input level mapped to `output_white`; brighter pixels are clipped.
+    pub white: u8,
+    /// Midtone gamma (> 0.0); values above 1.0 brighten the midtones.
+    pub gamma: f64,
+    /// Darkest output level.
+    pub output_black: u8,
+    /// Brightest output level.
+    pub output_white: u8,
+}
+
+impl Default for Levels {
+    fn default() -> Self {
+        Self::new(0, 255)
+    }
+}
+
+impl Levels {
+    /// Stretch `black..=white` to the full output range with a linear midtone.
+    pub fn new(black: u8, white: u8) -> Self {
+        Self {
+            black,
+            white,
+            gamma: 1.0,
+            output_black: 0,
+            output_white: 255,
+        }
+    }
+
+    /// Set the midtone gamma, which must be positive and finite.
+    pub fn with_gamma(mut self, gamma: f64) -> Self {
+        assert!(
+            gamma > 0.0 && gamma.is_finite(),
+            "Gamma must be positive and finite"
+        );
+        self.gamma = gamma;
+        self
+    }
+
+    /// Set the output range (`black > white` inverts the image).
+    pub fn with_output(mut self, black: u8, white: u8) -> Self {
+        self.output_black = black;
+        self.output_white = white;
+        self
+    }
+
+    /// Compile into a lookup table.
+    pub fn lut(&self) -> ToneCurve8 {
+        let (black, white) = (self.black as f64, self.white as f64);
+        let (out_black, out_white) = (self.output_black as f64, self.output_white as f64);
+        lut_from_fn(|v| {
+            let t = if white > black {
+                ((v - black) / (white - black)).clamp(0.0, 1.0)
+            } else if v >= black {
+                1.0
+            } else {
+                0.0
+            };
+            out_black + (out_white - out_black) * t.powf(1.0 / self.gamma)
+        })
+    }
+}
+
+/// Levels whose black and white points clip `clip` percent (0..50) of the
+/// pixels at each end of the histogram. A histogram with a single level
+/// yields the identity range, so flat channels are left unchanged.
+fn clipped_range(hist: &[u64; 256], clip: f64) -> (u8, u8) {
+    let total: u64 = hist.iter().sum();
+    let limit = (total as f64 * clip.clamp(0.0, 50.0) / 100.0).floor() as u64;
+    let mut cumulative = 0u64;
+    let black = hist
+        .iter()
+        .position(|&c| {
+            cumulative += c;
+            cumulative > limit
+        })
+        .unwrap_or(0);
+    cumulative = 0;
+    let white = hist
+        .iter()
+        .rposition(|&c| {
+            cumulative += c;
+            cumulative > limit
+        })
+        .unwrap_or(255);
+    if white <= black {
+        return (0, 255);
+    }
+    (black as u8, white as u8)
+}
+
+/// Black and white points for auto-levels.
+///
+/// With `per_channel` each channel is stretched separately, which also removes
+/// color casts; otherwise the pooled histogram of all channels is used so the
+/// color balance is kept.
+pub fn auto_levels(
+    img: &ImageBuffer<Rgb<u8>, Vec<u8>>,
+    clip: f64,
+    per_channel: bool,
+) -> [(u8, u8); 3] {
+    let mut hists = [[0u64; 256]; 3];
+    for p in img.pixels() {
+        for c in 0..3 {
+            hists[c][p[c] as usize] += 1;
+        }
+    }
+    if per_channel {
+        hists.map(|h| clipped_range(&h, clip))
+    } else {
+        let pooled: [u64; 256] = std::array::from_fn(|i| hists[0][i] + hists[1][i] + hists[2][i]);
+        [clipped_range(&pooled, clip); 3]
+    }
+}
+
+/// Lookup table adding `brightness` gray levels and scaling the distance
+/// from mid-gray by `contrast` (1.0 leaves the image unchanged).
+pub fn brightness_contrast_lut(brightness: f64, contrast: f64) -> ToneCurve8 {
+    lut_from_fn(|v| (v - 127.5) * contrast + 127.5 + brightness)
+}
+
+/// Lookup table scaling linear light by `2^stops`, like changing the
+/// exposure of a camera.
+pub fn exposure_lut(stops: f64) -> ToneCurve8 {
+    let gain = 2f64.powf(stops) as f32;
+    lut_from_fn(|v| {
+        let linear = srgb_to_linear((v / MAX_PIXEL) as f32) * gain;
+        linear_to_srgb(linear.min(1.0)) as f64 * MAX_PIXEL
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn identity_lut() -> ToneCurve8 {
+        std::array::from_fn(|i| i as u8)
+    }
+
+    #[test]
+    fn test_levels_lut() {
+        assert_eq!(Levels::default().lut(), identity_lut());
+        let lut = Levels::new(50, 150).lut();
+        assert_eq!(
+            (lut[0], lut[50], lut[100], lut[150], lut[255]),
+            (0, 0, 128, 255, 255)
+        );
+        let lut = Levels::new(0, 255).with_gamma(2.0).lut();
+        assert!(lut[64] > 64);
+        let lut = Levels::default().with_output(255, 0).lut();
+        assert_eq!((lut[0], lut[255]), (255, 0));
+        let lut = Levels::default().with_output(16, 235).lut();
+        assert_eq!((lut[0], lut[255]), (16, 235));
+    }
+
+    #[test]
+    fn test_auto_levels_clips_outliers() {
+        // Mostly 100..=150 with a single black and a single white outlier
+        let img: ImageBuffer<Rgb<u8>, Vec<u8>> = ImageBuffer::from_fn(51, 4, |x, y| match (x, y) {
+            (0, 0) => Rgb([0, 0, 0]),
+            (1, 0) => Rgb([255, 255, 255]),
+            _ => Rgb([100 + x as u8, 100 + x as u8, 120]),
+        });
+        assert_eq!(auto_levels(&img, 0.0, false)[0], (0, 255));
+        let [red, _, blue] = auto_levels(&img, 1.0, true);
+        assert_eq!(red, (100, 150));
+        assert_eq!(blue, (0, 255));
+        let pooled = auto_levels(&img, 1.0, false);
+        assert_eq!(pooled, [(100, 150); 3]);
+    }
+
+    #[test]
+    fn test_brightness_contrast_and_exposure() {
+        assert_eq!(brightness_contrast_lut(0.0, 1.0), identity_lut());
+        let lut = brightness_contrast_lut(20.0, 1.0);
+        assert_eq!((lut[0], lut[100], lut[250]), (20, 120, 255));
+        let lut = brightness_contrast_lut(0.0, 2.0);
+        assert_eq!((lut[63], lut[191]), (0, 255));
+        assert_eq!(exposure_lut(0.0), identity_lut());
+        // One stop doubles linear light: sRGB 128 (~21.6% linear) -> ~43.2% linear
+        let lut = exposure_lut(1.0);
+        assert_eq!(lut[128], 176);
+        assert_eq!(exposure_lut(-1.0)[176], 128);
+    }
+
+    #[test]
+    fn test_apply_luts() {
+        let img: ImageBuffer<Rgb<u8>, Vec<u8>> = ImageBuffer::from_pixel(2, 2, Rgb([10, 20, 30]));
+        let invert = lut_from_fn(|v| 255.0 - v);
+        let out = apply_luts(&img, &[invert, identity_lut(), invert]);
+        assert_eq!(*out.get_pixel(1, 1), Rgb([245, 20, 225]));
+    }
+}
@@ -0,0 +1,210 @@
+//! Toning "looks": the sepia matrix and gradient maps (duotone, tritone and
+//! arbitrary color stops applied to the luminance).
+use std::str::FromStr;
+
+use image::{ImageBuffer, Luma, Rgb};
+
+use crate::color::parse_hex_rgb;
+
+/// Classic sepia matrix (rows give the output R, G and B).
+const SEPIA: [[f32; 3]; 3] = [
+    [0.393, 0.769, 0.189],
+    [0.349, 0.686, 0.168],
+    [0.272, 0.534, 0.131],
+];
+
+/// Apply the sepia matrix, blended with the original by `strength`.
+///
+/// # Arguments
+/// * `img` - The input RGB image
+/// * `strength` - 0.0 keeps the original colors, 1.0 is full sepia
+///
+/// # Returns
+/// Sepia-toned RGB image
+pub fn sepia(img: &ImageBuffer<Rgb<u8>, Vec<u8>>, strength: f32) -> ImageBuffer<Rgb<u8>, Vec<u8>> {
+    let mut out = img.clone();
+    for pixel in out.pixels_mut() {
+        let [r, g, b] = pixel.0.map(|v| v as f32);
+        pixel.0 = std::array::from_fn(|c| {
+            let [wr, wg, wb] = SEPIA[c];
+            let toned = r * wr + g * wg + b * wb;
+            let original = pixel.0[c] as f32;
+            (original + (toned - original) * strength)
+                .round()
+                .clamp(0.0, 255.0) as u8
+        });
+    }
+    out
+}
+
+/// Colors at positions along the luminance axis, blended in sRGB between
+/// neighbouring stops.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GradientMap {
+    stops: Vec<(f32, Rgb<u8>)>,
+}
+
+impl GradientMap {
+    /// Create a gradient from `(position, color)` stops with positions in
+    /// `0.0..=1.0` (0.0 = black input, 1.0 = white input), in any order.
+    /// Inputs before the first or after the last stop take its color.
+    pub fn new(stops: &[(f32, Rgb<u8>)]) -> Self {
+        let mut stops: Vec<(f32, Rgb<u8>)> = stops
+            .iter()
+            .map(|&(pos, color)| (pos.clamp(0.0, 1.0), color))
+            .collect();
+        stops.sort_by(|a, b| a.0.total_cmp(&b.0));
+        Self { stops }
+    }
+
+    /// Spread colors evenly from black to white.
+    pub fn evenly(colors: &[Rgb<u8>]) -> Self {
+        let last = colors.len().saturating_sub(1).max(1) as f32;
+        let stops: Vec<(f32, Rgb<u8>)> = colors
+            .iter()
+            .enumerate()
+            .map(|(i, &c)| (i as f32 / last, c))
+            .collect();
+        Self::new(&stops)
+    }
+
+    /// Sorted stops.
+    pub fn stops(&self) -> &[(f32, Rgb<u8>)] {
+        &self.stops
+    }
+
+    /// Color for a position in `0.0..=1.0`.
+    pub fn color_at(&self, t: f32) -> Rgb<u8> {
+        let Some(&(first_pos, first)) = self.stops.first() else {
+            let v = (t * 255.0).round().clamp(0.0, 255.0) as u8;
+            return Rgb([v; 3]);
+        };
+        if t <= first_pos {
+            return first;
+        }
+        let k = self.stops.partition_point(|s| s.0 <= t);
+        if k == self.stops.len() {
+            return self.stops[k - 1].1;
+        }
+        let (p0, c0) = self.stops[k - 1];
+        let (p1, c1) = self.stops[k];
+        let f = (t - p0) / (p1 - p0);
+        Rgb(std::array::from_fn(|c| {
+            (c0[c] as f32 + (c1[c] as f32 - c0[c] as f32) * f).round() as u8
+        }))
+    }
+}
+
+impl FromStr for GradientMap {
+    type Err = String;
+
+    /// Parse comma-separated stops: `POS:HEX` pairs such as
+    /// `"0:#1b1b3a,0.6:#c0392b,1:#f7dc6f"`, or bare hex colors, which are
+    /// spread evenly.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let items: Vec<&str> = s
+            .split(',')
+            .map(str::trim)
+            .filter(|i| !i.is_empty())
+            .collect();
+        if items.len() < 2 {
+            return Err("a gradient needs at least 2 stops".into());
+        }
+        let color = |hex: &str| parse_hex_rgb(hex).ok_or_else(|| format!("invalid color: {hex}"));
+        if items.iter().all(|i| !i.contains(':')) {
+            let colors = items
+                .iter()
+                .map(|i| color(i))
+                .collect::<Result<Vec<_>, _>>()?;
+            return Ok(Self::evenly(&colors));
+        }
+        let stops = items
+            .iter()
+            .map(|item| {
+                let (pos, hex) = item
+                    .split_once(':')
+                    .ok_or_else(|| format!("gradient stops must all be POS:HEX: {item}"))?;
+                let pos = pos
+                    .trim()
+                    .parse::<f32>()
+                    .ok()
+                    .filter(|p| (0.0..=1.0).contains(p))
+                    .ok_or_else(|| format!("stop position must be in 0..1: {item}"))?;
+                Ok((pos, color(hex)?))
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        Ok(Self::new(&stops))
+    }
+}
+
+/// Map a luminance image through a gradient.
+///
+/// # Arguments
+/// * `gray` - Luminance of the input image
+/// * `gradient` - Colors along the luminance axis
+///
+/// # Returns
+/// RGB image colored by the gradient
+pub fn gradient_map(
+    gray: &ImageBuffer<Luma<u8>, Vec<u8>>,
+    gradient: &GradientMap,
+) -> ImageBuffer<Rgb<u8>, Vec<u8>> {
+    let lut: Vec<Rgb<u8>> = (0..=255)
+        .map(|v| gradient.color_at(v as f32 / 255.0))
+        .collect();
+    let (width, height) = gray.dimensions();
+    ImageBuffer::from_fn(width, height, |x, y| lut[gray.get_pixel(x, y)[0] as usize])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sepia() {
+        let img: ImageBuffer<Rgb<u8>, Vec<u8>> =
+            ImageBuffer::from_pixel(1, 1, Rgb([100, 100, 100]));
+        assert_eq!(*sepia(&img, 1.0).get_pixel(0, 0), Rgb([135, 120, 94]));
+        assert_eq!(*sepia(&img, 0.0).get_pixel(0, 0), Rgb([100, 100, 100]));
+        let white: ImageBuffer<Rgb<u8>, Vec<u8>> = ImageBuffer::from_pixel(1, 1, Rgb([255; 3]));
+        assert_eq!(sepia(&white, 1.0).get_pixel(0, 0)[0], 255);
+    }
+
+    #[test]
+    fn test_gradient_stops() {
+        let black = Rgb([0, 0, 0]);
+        let red = Rgb([255, 0, 0]);
+        let white = Rgb([255, 255, 255]);
+        let tritone = GradientMap::evenly(&[black, red, white]);
+        assert_eq!(tritone.color_at(0.0), black);
+        assert_eq!(tritone.color_at(0.5), red);
+        assert_eq!(tritone.color_at(0.75), Rgb([255, 128, 128]));
+        let narrow = GradientMap::new(&[(0.8, white), (0.2, black)]);
+        assert_eq!(narrow.color_at(0.1), black);
+        assert_eq!(narrow.color_at(0.5), Rgb([128; 3]));
+        assert_eq!(narrow.color_at(0.9), white);
+    }
+
+    #[test]
+    fn test_parse_gradient() {
+        let even: GradientMap = "#000,#f00,#fff".parse().unwrap();
+        assert_eq!(even.stops()[1], (0.5, Rgb([255, 0, 0])));
+        let stops: GradientMap = "1:#ffffff, 0:#000000, 0.25:#00ff00".parse().unwrap();
+        assert_eq!(stops.stops()[1], (0.25, Rgb([0, 255, 0])));
+        assert!("#000".parse::<GradientMap>().is_err());
+        assert!("0:#000,#fff".parse::<GradientMap>().is_err());
+        assert!("0:#000,2:#fff".parse::<GradientMap>().is_err());
+        assert!("#000,#ggg".parse::<GradientMap>().is_err());
+    }
+
+    #[test]
+    fn test_gradient_map() {
+        let gray: ImageBuffer<Luma<u8>, Vec<u8>> =
+            ImageBuffer::from_fn(3, 1, |x, _| Luma([[0, 128, 255][x as usize]]));
+        let duotone = GradientMap::evenly(&[Rgb([0, 0, 64]), Rgb([255, 255, 192])]);
+        let out = gradient_map(&gray, &duotone);
+        assert_eq!(*out.get_pixel(0, 0), Rgb([0, 0, 64]));
+        assert_eq!(*out.get_pixel(1, 0), Rgb([128, 128, 128]));
+        assert_eq!(*out.get_pixel(2, 0), Rgb([255, 255, 192]));
+    }
+}
@@ -10,13 +10,15 @@
 //! use pixelate::filters::{Filter, HalftoneFilter};
 //!
 //! let img = image::open("input.jpg").unwrap().to_rgb8();
-//! let out = HalftoneFilter.apply(&img);
+//! let out = HalftoneFilter::default().apply(&img);
 //! out.save("out.jpg").unwrap();
 //! ```
 
-/// Shared color-related constants (e.g., sRGB luminance weights).
+/// Shared color types and helpers (color spaces, palettes, LUTs, colormaps).
 pub mod color;
 /// Filter definitions and built-in filters.
 pub mod filters;
 /// Simple, typed filter composition utilities.
 pub mod pipeline;
+/// Read-only image statistics and histograms.
+pub mod stats;
@@ -1,19 +1,40 @@
-use clap::{Parser, Subcommand, ValueEnum};
-use std::{path::PathBuf, time};
+use clap::{
+    builder::PossibleValuesParser, error::ErrorKind, ArgGroup, Args, CommandFactory, Parser,
+    Subcommand, ValueEnum,
+};
+use std::{
+    error::Error,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    time,
+};
 
+use pixelate::color::{
+    parse_hex_rgb, ColorLut, ColorMetric, Colormap, LutInterpolation, Palette, PaletteFormat,
+    COLORMAP_NAMES, PALETTE_NAMES, SRGB_LUMA_B, SRGB_LUMA_G, SRGB_LUMA_R,
+};
 use pixelate::filters::{
-    Filter, GammaFilter, GrayscaleFilter, HalftoneFilter, InvertFilter, SobelFilter,
+    BlockColor, BlurFilter, BlurMethod, BorderMode, BrightnessContrastFilter, CannyFilter,
+    ChannelCombine, ChannelEdgeFilter, ColormapFilter, ConvolveChannels, ConvolveFilter,
+    CurveInterpolation, CurvesFilter, DitherFilter, DitherMethod, DitherSpace, DitherTarget,
+    EdgeOperator, EdgeSpace, EqualizeFilter, EqualizeMethod, ExposureFilter, Filter, GammaFilter,
+    GradientEdgeFilter, GradientFilter, GradientMapFilter, GrayscaleFilter, HalftoneFilter,
+    InvertFilter, LaplacianFilter, Levels, LevelsFilter, LutFilter, MagnitudeScale, Normalization,
+    PaletteMapFilter, PixelateFilter, QuantizeFilter, QuantizeMethod, SecondDerivative,
+    SepiaFilter, ThresholdFilter, ThresholdMap, ThresholdMethod,
 };
+use pixelate::pipeline::parse::parse_chain;
+use pixelate::stats::{ascii_histogram, render_histogram, ImageStats};
 
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
 struct Cli {
-    /// Path to the image file to be processed
+    /// Path to the image file to be processed (bake: only read for --preview)
     #[arg(short, long, value_name = "FILE")]
-    input: PathBuf,
-    /// Output path for the processed image file
+    input: Option<PathBuf>,
+    /// Output path for the processed image file (stats: optional histogram PNG)
     #[arg(short, long, value_name = "FILE")]
-    output: PathBuf,
+    output: Option<PathBuf>,
 
     #[command(subcommand)]
     command: Commands,
@@ -23,10 +44,770 @@ struct Cli {
 enum EdgeMethod {
     /// Sobel operator (gradient magnitude)
     Sobel,
+    /// Prewitt operator (uniformly weighted gradient)
+    Prewitt,
+    /// Scharr operator (rotation-invariant gradient)
+    Scharr,
+    /// Roberts cross (2x2 diagonal differences)
+    Roberts,
+    /// Kirsch compass masks (strongest of eight directions)
+    Kirsch,
+    /// Frei-Chen basis (projection onto the edge subspace)
+    FreiChen,
+    /// Canny detector (thin binary edges; uses --sigma, --low and --high)
+    Canny,
+    /// Laplacian second derivative (uses --neighbours)
+    Laplacian,
+    /// Laplacian of Gaussian (uses --sigma)
+    Log,
+    /// Difference of Gaussians (uses --sigma and --sigma2)
+    Dog,
+}
+
+impl EdgeMethod {
+    /// The mask operator of the first-derivative methods; `None` for canny and
+    /// the second-derivative methods.
+    fn operator(self) -> Option<EdgeOperator> {
+        match self {
+            EdgeMethod::Sobel => Some(EdgeOperator::Sobel),
+            EdgeMethod::Prewitt => Some(EdgeOperator::Prewitt),
+            EdgeMethod::Scharr => Some(EdgeOperator::Scharr),
+            EdgeMethod::Roberts => Some(EdgeOperator::Roberts),
+            EdgeMethod::Kirsch => Some(EdgeOperator::Kirsch),
+            EdgeMethod::FreiChen => Some(EdgeOperator::FreiChen),
+            EdgeMethod::Canny | EdgeMethod::Laplacian | EdgeMethod::Log | EdgeMethod::Dog => None,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, ValueEnum)]
+enum BlockMode {
+    /// Per-channel average of the block
+    Average,
+    /// Per-channel median of the block
+    Median,
+    /// Color of the block's center pixel
+    Center,
+}
+
+impl From<BlockMode> for BlockColor {
+    fn from(mode: BlockMode) -> Self {
+        match mode {
+            BlockMode::Average => BlockColor::Average,
+            BlockMode::Median => BlockColor::Median,
+            BlockMode::Center => BlockColor::Center,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, ValueEnum)]
+enum DitherKind {
+    /// Ordered dithering with a threshold matrix (see --matrix)
+    #[value(alias = "bayer")]
+    Ordered,
+    /// Floyd–Steinberg error diffusion
+    FloydSteinberg,
+    /// Jarvis–Judice–Ninke error diffusion
+    JarvisJudiceNinke,
+    /// Stucki error diffusion
+    Stucki,
+    /// Atkinson error diffusion
+    Atkinson,
+    /// Burkes error diffusion
+    Burkes,
+    /// Sierra (three-row) error diffusion
+    Sierra,
+    /// Two-row Sierra error diffusion
+    SierraTwoRow,
+    /// Sierra Lite error diffusion
+    SierraLite,
+}
+
+impl From<DitherKind> for DitherMethod {
+    fn from(method: DitherKind) -> Self {
+        match method {
+            DitherKind::Ordered => DitherMethod::Ordered,
+            DitherKind::FloydSteinberg => DitherMethod::FloydSteinberg,
+            DitherKind::JarvisJudiceNinke => DitherMethod::JarvisJudiceNinke,
+            DitherKind::Stucki => DitherMethod::Stucki,
+            DitherKind::Atkinson => DitherMethod::Atkinson,
+            DitherKind::Burkes => DitherMethod::Burkes,
+            DitherKind::Sierra => DitherMethod::Sierra,
+            DitherKind::SierraTwoRow => DitherMethod::SierraTwoRow,
+            DitherKind::SierraLite => DitherMethod::SierraLite,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, ValueEnum)]
+enum MatrixKind {
+    /// Recursive Bayer matrix (size must be a power of two)
+    Bayer,
+    /// Clustered-dot matrix (print-style round dots)
+    ClusterDot,
+    /// Void-and-cluster blue noise
+    BlueNoise,
+}
+
+#[derive(Copy, Clone, Debug, ValueEnum)]
+enum ColorSpace {
+    /// Gamma-encoded sRGB
+    Rgb,
+    /// Perceptually uniform OKLab
+    Oklab,
+}
+
+impl From<ColorSpace> for DitherSpace {
+    fn from(space: ColorSpace) -> Self {
+        match space {
+            ColorSpace::Rgb => DitherSpace::Rgb,
+            ColorSpace::Oklab => DitherSpace::Oklab,
+        }
+    }
+}
+
+impl From<ColorSpace> for EdgeSpace {
+    fn from(space: ColorSpace) -> Self {
+        match space {
+            ColorSpace::Rgb => EdgeSpace::Rgb,
+            ColorSpace::Oklab => EdgeSpace::Oklab,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, ValueEnum)]
+enum PaletteMethod {
+    /// Median cut
+    MedianCut,
+    /// Octree reduction
+    Octree,
+    /// K-means clustering (seedable)
+    Kmeans,
+    /// Wu's variance-minimizing quantizer
+    Wu,
+}
+
+impl From<PaletteMethod> for QuantizeMethod {
+    fn from(method: PaletteMethod) -> Self {
+        match method {
+            PaletteMethod::MedianCut => QuantizeMethod::MedianCut,
+            PaletteMethod::Octree => QuantizeMethod::Octree,
+            PaletteMethod::Kmeans => QuantizeMethod::KMeans,
+            PaletteMethod::Wu => QuantizeMethod::Wu,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, ValueEnum)]
+enum Metric {
+    /// Euclidean distance in sRGB
+    Rgb,
+    /// Weighted "redmean" RGB distance
+    Redmean,
+    /// Euclidean distance in OKLab
+    Oklab,
+}
+
+impl From<Metric> for ColorMetric {
+    fn from(metric: Metric) -> Self {
+        match metric {
+            Metric::Rgb => ColorMetric::Rgb,
+            Metric::Redmean => ColorMetric::Redmean,
+            Metric::Oklab => ColorMetric::Oklab,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, ValueEnum)]
+enum BlurKind {
+    /// Gaussian blur (uses --sigma)
+    Gaussian,
+    /// Box (mean) blur (uses --radius)
+    Box,
+    /// Repeated running-sum box blur (uses --radius and --passes)
+    FastBox,
+}
+
+#[derive(Copy, Clone, Debug, ValueEnum)]
+enum ThresholdKind {
+    /// Fixed level (uses --value)
+    Fixed,
+    /// Otsu's global threshold (bimodal images)
+    Otsu,
+    /// Triangle global threshold (one dominant background)
+    Triangle,
+    /// Local window mean minus --offset (uses --radius)
+    AdaptiveMean,
+    /// Gaussian-weighted local mean minus --offset (uses --radius)
+    AdaptiveGaussian,
+    /// Sauvola local threshold (uses --radius, --k and --range)
+    Sauvola,
+    /// Niblack local threshold (uses --radius and --k)
+    Niblack,
+}
+
+#[derive(Copy, Clone, Debug, ValueEnum)]
+enum EqualizeKind {
+    /// Global histogram equalization
+    Global,
+    /// Contrast-limited adaptive equalization (uses --tiles-x, --tiles-y and --clip-limit)
+    Clahe,
+}
+
+#[derive(Copy, Clone, Debug, ValueEnum)]
+enum CurveKind {
+    /// Monotone cubic: never overshoots the control points
+    Monotone,
+    /// Catmull-Rom spline: smoother, may overshoot between points
+    CatmullRom,
+}
+
+impl From<CurveKind> for CurveInterpolation {
+    fn from(kind: CurveKind) -> Self {
+        match kind {
+            CurveKind::Monotone => CurveInterpolation::MonotoneCubic,
+            CurveKind::CatmullRom => CurveInterpolation::CatmullRom,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, ValueEnum)]
+enum LutKind {
+    /// Blend the 8 surrounding lattice points
+    Trilinear,
+    /// Blend the 4 points of the enclosing tetrahedron (keeps grays neutral)
+    Tetrahedral,
+}
+
+impl From<LutKind> for LutInterpolation {
+    fn from(kind: LutKind) -> Self {
+        match kind {
+            LutKind::Trilinear => LutInterpolation::Trilinear,
+            LutKind::Tetrahedral => LutInterpolation::Tetrahedral,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, ValueEnum)]
+enum BorderKind {
+    /// Pad with black
+    Zero,
+    /// Pad with --border-value
+    Constant,
+    /// Repeat the edge pixel (aaa|abc)
+    Clamp,
+    /// Mirror including the edge pixel (cba|abc)
+    Reflect,
+    /// Mirror around the edge pixel (dcb|abcd)
+    #[value(name = "reflect-101")]
+    Reflect101,
+    /// Tile the image (xyz|abc...xyz)
+    Wrap,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+enum EdgeColor {
+    /// Differentiate the luminance only
+    Luma,
+    /// Strongest channel gradient
+    Max,
+    /// Sum of channel gradients
+    Sum,
+    /// Di Zenzo structure tensor (dominant multi-channel gradient)
+    DiZenzo,
+    /// Colored output: each channel keeps its own edges
+    Channels,
+}
+
+#[derive(Copy, Clone, Debug, ValueEnum)]
+enum EdgeRender {
+    /// Normalized gradient magnitude (grayscale)
+    Magnitude,
+    /// Direction as hue, magnitude as brightness (RGB)
+    Direction,
+    /// gx/gy as a two-channel 16-bit image (value = 32768 + scale * derivative)
+    Raw16,
+    /// gx, gy and magnitude as a 32-bit float image (use .exr or .tiff)
+    RawFloat,
+}
+
+#[derive(Copy, Clone, Debug, ValueEnum)]
+enum NormalizeKind {
+    /// Divide by the strongest magnitude in the image
+    Max,
+    /// Divide by the --percentile magnitude; stronger edges saturate
+    Percentile,
+    /// Divide by the fixed --scale (consistent across frames)
+    Fixed,
+    /// Logarithmic compression relative to the strongest magnitude
+    Log,
+}
+
+/// Magnitude normalization and thresholding for the edge command.
+#[derive(Args)]
+struct NormalizeArgs {
+    /// How edge magnitudes are mapped to brightness (default: max)
+    #[arg(long, value_enum)]
+    normalize: Option<NormalizeKind>,
+    /// Percentile used by --normalize percentile (0..=100; default: 99.0)
+    #[arg(long)]
+    percentile: Option<f32>,
+    /// Magnitude shown as full white by --normalize fixed (> 0.0)
+    #[arg(long)]
+    scale: Option<f32>,
+    /// Output a binary map: white where the scaled magnitude reaches this level (0..=255)
+    #[arg(long)]
+    threshold: Option<u8>,
+}
+
+impl NormalizeArgs {
+    /// Build the library normalization, validating its parameters.
+    fn normalization(&self) -> Result<Normalization, Box<dyn Error>> {
+        let kind = self.normalize.unwrap_or(NormalizeKind::Max);
+        if self.percentile.is_some() && !matches!(kind, NormalizeKind::Percentile) {
+            return Err("--percentile requires --normalize percentile".into());
+        }
+        if self.scale.is_some() && !matches!(kind, NormalizeKind::Fixed) {
+            return Err("--scale requires --normalize fixed".into());
+        }
+        Ok(match kind {
+            NormalizeKind::Max => Normalization::Max,
+            NormalizeKind::Percentile => {
+                let percentile = self.percentile.unwrap_or(99.0);
+                if !(0.0..=100.0).contains(&percentile) {
+                    return Err("Percentile must be between 0.0 and 100.0".into());
+                }
+                Normalization::Percentile(percentile)
+            }
+            NormalizeKind::Fixed => match self.scale {
+                Some(scale) if scale > 0.0 => Normalization::Fixed(scale),
+                Some(_) => return Err("Scale must be greater than 0.0".into()),
+                None => return Err("--normalize fixed requires --scale".into()),
+            },
+            NormalizeKind::Log => Normalization::Log,
+        })
+    }
+
+    /// The first of these options given on the command line, if any.
+    fn given(&self) -> Option<&'static str> {
+        [
+            ("--normalize", self.normalize.is_some()),
+            ("--percentile", self.percentile.is_some()),
+            ("--scale", self.scale.is_some()),
+            ("--threshold", self.threshold.is_some()),
+        ]
+        .into_iter()
+        .find(|(_, given)| *given)
+        .map(|(flag, _)| flag)
+    }
+}
+
+/// Options of the edge command.
+#[derive(Args)]
+struct EdgeArgs {
+    /// Edge detection method
+    #[arg(long, value_enum, default_value_t = EdgeMethod::Sobel)]
+    method: EdgeMethod,
+    /// Intensity multiplier applied after normalization (>= 0.0; default: 1.0)
+    #[arg(long)]
+    intensity: Option<f64>,
+    /// Output for gradient methods (sobel, prewitt, scharr, roberts; default: magnitude)
+    #[arg(long, value_enum)]
+    render: Option<EdgeRender>,
+    /// Per-channel edges for gradient methods (catches edges between equally bright colors; default: luma)
+    #[arg(long, value_enum)]
+    color: Option<EdgeColor>,
+    /// Color space differentiated by --color (default: rgb)
+    #[arg(long, value_enum)]
+    space: Option<ColorSpace>,
+    /// Gaussian scale for canny (0.0 disables smoothing), log and dog (default: 1.4)
+    #[arg(long)]
+    sigma: Option<f32>,
+    /// Wider Gaussian for dog (default: 1.6 x --sigma)
+    #[arg(long)]
+    sigma2: Option<f32>,
+    /// Neighbourhood of the Laplacian (4 or 8; default: 4)
+    #[arg(long)]
+    neighbours: Option<u32>,
+    /// Output zero crossings of laplacian, log or dog as a binary edge map
+    #[arg(long)]
+    zero_crossings: bool,
+    /// Minimum step across a zero crossing, as a fraction of the strongest response
+    #[arg(long, default_value = "0.05", requires = "zero_crossings")]
+    zero_threshold: f32,
+    /// Canny low threshold on the gradient magnitude (default: from the magnitude percentiles)
+    #[arg(long, requires = "high")]
+    low: Option<f32>,
+    /// Canny high threshold on the gradient magnitude (default: from the magnitude percentiles)
+    #[arg(long, requires = "low")]
+    high: Option<f32>,
+    #[command(flatten)]
+    normalize: NormalizeArgs,
+    #[command(flatten)]
+    border: BorderArgs,
+    #[command(flatten)]
+    colormap: ColormapArgs,
+}
+
+impl EdgeArgs {
+    /// Reject options the chosen method or output would silently ignore.
+    fn check(&self) -> Result<(), Box<dyn Error>> {
+        use EdgeMethod::{Canny, Dog, Laplacian, Log};
+        let method = self.method;
+        let gradient = method.operator().and_then(EdgeOperator::gradient).is_some();
+        let ignored = [
+            (
+                "--sigma",
+                self.sigma.is_some() && !matches!(method, Canny | Log | Dog),
+            ),
+            ("--sigma2", self.sigma2.is_some() && !matches!(method, Dog)),
+            (
+                "--neighbours",
+                self.neighbours.is_some() && !matches!(method, Laplacian),
+            ),
+            (
+                "--zero-crossings",
+                self.zero_crossings && !matches!(method, Laplacian | Log | Dog),
+            ),
+            (
+                "--low/--high",
+                self.low.is_some() && !matches!(method, Canny),
+            ),
+            ("--render", self.render.is_some() && !gradient),
+            ("--color", self.color.is_some() && !gradient),
+        ];
+        if let Some((flag, _)) = ignored.iter().find(|(_, ignored)| *ignored) {
+            let name = method.to_possible_value().map(|v| v.get_name().to_owned());
+            return Err(format!(
+                "{flag} does not apply to --method {}",
+                name.unwrap_or_default()
+            )
+            .into());
+        }
+        if self.space.is_some() && matches!(self.color, None | Some(EdgeColor::Luma)) {
+            return Err("--space requires --color channels, max, sum or di-zenzo".into());
+        }
+        // Binary maps and raw derivatives are written without magnitude scaling
+        let unscaled = if matches!(method, Canny) {
+            Some("--method canny")
+        } else if self.zero_crossings {
+            Some("--zero-crossings")
+        } else if matches!(self.render, Some(EdgeRender::Raw16 | EdgeRender::RawFloat)) {
+            Some("the raw renders")
+        } else {
+            None
+        };
+        let scaling = self
+            .intensity
+            .is_some()
+            .then_some("--intensity")
+            .or(self.normalize.given());
+        if let (Some(output), Some(flag)) = (unscaled, scaling) {
+            return Err(format!("{flag} does not apply to {output}").into());
+        }
+        Ok(())
+    }
+}
+
+#[derive(Copy, Clone, Debug, ValueEnum)]
+enum StatsFormat {
+    /// Summary table and an ASCII histogram
+    Text,
+    /// All statistics and histograms as JSON
+    Json,
+    /// 256-row histogram table (level, red, green, blue, luma)
+    Csv,
+}
+
+#[derive(Copy, Clone, Debug, ValueEnum)]
+enum StatsChannel {
+    Red,
+    Green,
+    Blue,
+    Luma,
+}
+
+/// Options of the read-only stats command.
+#[derive(Args)]
+struct StatsArgs {
+    /// Report format printed to stdout
+    #[arg(long, value_enum, default_value_t = StatsFormat::Text)]
+    format: StatsFormat,
+    /// Comma-separated percentiles to report (0..=100)
+    #[arg(long, value_delimiter = ',', default_value = "1,5,25,50,75,95,99")]
+    percentiles: Vec<f64>,
+    /// Channel drawn by the ASCII histogram
+    #[arg(long, value_enum, default_value_t = StatsChannel::Luma)]
+    channel: StatsChannel,
+    /// Number of rows in the ASCII histogram (1..=256)
+    #[arg(long, default_value = "32")]
+    bins: usize,
+}
+
+/// Print image statistics to stdout and optionally save the histogram as an image.
+fn print_stats(
+    img: &image::ImageBuffer<image::Rgb<u8>, Vec<u8>>,
+    args: &StatsArgs,
+    output: Option<&Path>,
+) -> Result<(), Box<dyn Error>> {
+    if args.percentiles.iter().any(|p| !(0.0..=100.0).contains(p)) {
+        return Err("Percentiles must be between 0 and 100".into());
+    }
+    if !(1..=256).contains(&args.bins) {
+        return Err("Bins must be between 1 and 256".into());
+    }
+    let stats = ImageStats::compute(img, &args.percentiles);
+    let report = match args.format {
+        StatsFormat::Text => {
+            let channel = match args.channel {
+                StatsChannel::Red => 0,
+                StatsChannel::Green => 1,
+                StatsChannel::Blue => 2,
+                StatsChannel::Luma => 3,
+            };
+            let channel = &stats.channels[channel];
+            format!(
+                "{}\n{} histogram:\n{}",
+                stats.summary(),
+                channel.name,
+                ascii_histogram(&channel.histogram, args.bins, 60)
+            )
+        }
+        StatsFormat::Json => stats.to_json() + "\n",
+        StatsFormat::Csv => stats.histogram_csv(),
+    };
+    // Report a closed pipe (e.g. `| head`) as an error instead of panicking
+    io::stdout().write_all(report.as_bytes())?;
+    if let Some(path) = output {
+        render_histogram(&stats, 2, 200).save(path)?;
+    }
+    Ok(())
+}
+
+/// Border handling shared by the convolution-based commands.
+#[derive(Args)]
+struct BorderArgs {
+    /// How pixels outside the image are synthesized
+    #[arg(long, value_enum, default_value_t = BorderKind::Reflect101)]
+    border: BorderKind,
+    /// Padding value for --border constant (0..=255; default: 0)
+    #[arg(long)]
+    border_value: Option<u8>,
+}
+
+impl BorderArgs {
+    /// Build the library border mode, rejecting a --border-value it would ignore.
+    fn mode(&self) -> Result<BorderMode, Box<dyn Error>> {
+        if self.border_value.is_some() && !matches!(self.border, BorderKind::Constant) {
+            return Err("--border-value requires --border constant".into());
+        }
+        Ok(match self.border {
+            BorderKind::Zero => BorderMode::Zero,
+            BorderKind::Constant => BorderMode::Constant(self.border_value.unwrap_or(0) as f32),
+            BorderKind::Clamp => BorderMode::Clamp,
+            BorderKind::Reflect => BorderMode::Reflect,
+            BorderKind::Reflect101 => BorderMode::Reflect101,
+            BorderKind::Wrap => BorderMode::Wrap,
+        })
+    }
+}
+
+#[derive(Copy, Clone, Debug, ValueEnum)]
+enum ChannelMode {
+    /// Convolve R, G and B independently
+    Rgb,
+    /// Convolve luminance only (keeps colors from fringing)
+    Luma,
+}
+
+impl From<ChannelMode> for ConvolveChannels {
+    fn from(mode: ChannelMode) -> Self {
+        match mode {
+            ChannelMode::Rgb => ConvolveChannels::Rgb,
+            ChannelMode::Luma => ConvolveChannels::Luma,
+        }
+    }
+}
+
+/// Load a kernel from a text file, or a JSON file with optional divisor and
+/// bias (see [`ConvolveFilter::from_json`]).
+fn load_kernel_file(path: &Path) -> Result<ConvolveFilter, Box<dyn Error>> {
+    let text = std::fs::read_to_string(path)?;
+    let is_json = path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("json"))
+        || text.trim_start().starts_with(['{', '[']);
+    if is_json {
+        return Ok(ConvolveFilter::from_json(&text)?);
+    }
+    Ok(ConvolveFilter::new(text.parse()?))
+}
+
+/// Palette sources shared by `dither` and `palette`.
+#[derive(Args)]
+struct PaletteArgs {
+    /// Comma-separated hex palette (e.g. "#000,#f00,#fff")
+    #[arg(long, value_name = "HEX,...", value_delimiter = ',', value_parser = parse_color)]
+    palette: Vec<image::Rgb<u8>>,
+    /// Built-in palette
+    #[arg(long, value_name = "NAME", value_parser = PossibleValuesParser::new(PALETTE_NAMES))]
+    palette_name: Option<String>,
+    /// Palette file (.gpl, .act, .pal or a list of hex colors)
+    #[arg(long, value_name = "FILE")]
+    palette_file: Option<PathBuf>,
+}
+
+impl PaletteArgs {
+    /// Load the selected palette, if any source was given.
+    fn load(&self) -> Result<Option<Palette>, Box<dyn Error>> {
+        if let Some(name) = &self.palette_name {
+            return Ok(Palette::named(name));
+        }
+        if let Some(path) = &self.palette_file {
+            let bytes = std::fs::read(path)?;
+            let format = path
+                .extension()
+                .and_then(|ext| PaletteFormat::from_extension(&ext.to_string_lossy()))
+                .unwrap_or_else(|| PaletteFormat::detect(&bytes));
+            return Ok(Some(Palette::parse(&bytes, format)?));
+        }
+        Ok((!self.palette.is_empty()).then(|| Palette::new(self.palette.clone())))
+    }
+}
+
+/// Colormap sources shared by `colormap` and `edge`.
+#[derive(Args)]
+struct ColormapArgs {
+    /// Built-in colormap
+    #[arg(long, value_name = "NAME", value_parser = PossibleValuesParser::new(COLORMAP_NAMES))]
+    colormap: Option<String>,
+    /// Colormap CSV file with one r,g,b row per stop (0..1 or 0..255), from black to white
+    #[arg(long, value_name = "FILE", conflicts_with = "colormap")]
+    colormap_file: Option<PathBuf>,
+    /// Run the colormap from white to black
+    #[arg(long)]
+    reverse_colormap: bool,
+}
+
+impl ColormapArgs {
+    /// Load the selected colormap, if any source was given.
+    fn load(&self) -> Result<Option<Colormap>, Box<dyn Error>> {
+        let colormap = match (&self.colormap, &self.colormap_file) {
+            (Some(name), _) => Colormap::named(name),
+            (None, Some(path)) => Some(Colormap::from_csv(&std::fs::read_to_string(path)?)?),
+            (None, None) => None,
+        };
+        if colormap.is_none() && self.reverse_colormap {
+            return Err("--reverse-colormap requires --colormap or --colormap-file".into());
+        }
+        Ok(colormap.map(|c| {
+            if self.reverse_colormap {
+                c.reversed()
+            } else {
+                c
+            }
+        }))
+    }
+
+    /// Save a grayscale result, pseudo-colored if a colormap was selected.
+    fn save(
+        &self,
+        img: &image::ImageBuffer<image::Luma<u8>, Vec<u8>>,
+        output: &Path,
+    ) -> Result<(), Box<dyn Error>> {
+        match self.load()? {
+            Some(colormap) => ColormapFilter::new(colormap).apply(img).save(output)?,
+            None => img.save(output)?,
+        }
+        Ok(())
+    }
+}
+
+/// Dithering options shared by `halftone` and `dither`.
+#[derive(Args)]
+struct DitherArgs {
+    /// Dithering method
+    #[arg(long, value_enum, default_value_t = DitherKind::Ordered)]
+    method: DitherKind,
+    /// Threshold matrix for ordered dithering
+    #[arg(long, value_enum, default_value_t = MatrixKind::Bayer)]
+    matrix: MatrixKind,
+    /// Threshold matrix size (2..=64; Bayer: power of two up to 16)
+    #[arg(long, default_value = "4")]
+    matrix_size: u32,
+    /// Load a custom threshold matrix of ranks from a text file (overrides --matrix)
+    #[arg(long, value_name = "FILE")]
+    matrix_file: Option<PathBuf>,
+    /// Alternate scan direction on every other row (error diffusion only)
+    #[arg(long)]
+    serpentine: bool,
+    /// Fraction of the quantization error to diffuse (or ordered noise to add)
+    #[arg(long, default_value = "1.0")]
+    strength: f32,
+}
+
+impl DitherArgs {
+    /// Validate the options and build the requested threshold matrix.
+    fn threshold_map(&self) -> Result<ThresholdMap, Box<dyn Error>> {
+        if !(0.0..=1.0).contains(&self.strength) {
+            return Err("Strength must be between 0.0 and 1.0".into());
+        }
+        let size = self.matrix_size;
+        let map = match (&self.matrix_file, self.matrix) {
+            (Some(path), _) => std::fs::read_to_string(path)?.parse()?,
+            (None, MatrixKind::Bayer) => {
+                if !(2..=16).contains(&size) || !size.is_power_of_two() {
+                    return Err("Bayer matrix size must be 2, 4, 8 or 16".into());
+                }
+                ThresholdMap::bayer(size)
+            }
+            (None, MatrixKind::ClusterDot | MatrixKind::BlueNoise) if !(2..=64).contains(&size) => {
+                return Err("Matrix size must be between 2 and 64".into());
+            }
+            (None, MatrixKind::ClusterDot) => ThresholdMap::cluster_dot(size),
+            (None, MatrixKind::BlueNoise) => ThresholdMap::blue_noise(size),
+        };
+        Ok(map)
+    }
+}
+
+/// Parse a `#RRGGBB` / `#RGB` command-line value into an RGB color.
+fn parse_color(s: &str) -> Result<image::Rgb<u8>, String> {
+    parse_hex_rgb(s).ok_or_else(|| format!("invalid hex color: {s}"))
+}
+
+/// Options of the bake command.
+#[derive(Args)]
+struct BakeArgs {
+    /// Filter steps applied in order: invert, gamma:G, exposure:STOPS,
+    /// contrast:C, brightness:B, levels:BLACK,WHITE[,GAMMA[,OUT_BLACK,OUT_WHITE]],
+    /// curves:FILE.json, lut:FILE, sepia[:STRENGTH], gradient-map:STOPS
+    #[arg(required = true, value_name = "STEP")]
+    steps: Vec<String>,
+    /// Lattice points per axis (2..=256)
+    #[arg(long, default_value = "33")]
+    size: usize,
+    /// Title stored in the .cube file
+    #[arg(long)]
+    title: Option<String>,
+    /// Also save the input image graded through the baked LUT
+    #[arg(long, value_name = "FILE")]
+    preview: Option<PathBuf>,
 }
 
 #[derive(Subcommand)]
 enum Commands {
+    #[command(flatten)]
+    Image(ImageCommand),
+    /// Print image statistics and histograms (read-only; --output is optional)
+    Stats(StatsArgs),
+    /// Bake a chain of color filters into a .cube LUT written to --output
+    Bake(BakeArgs),
+}
+
+// Commands that turn the --input image into an --output image
+#[derive(Subcommand)]
+enum ImageCommand {
     /// Convert the image to grayscale
     Grayscale {
         /// Red channel weight
@@ -40,72 +821,898 @@ enum Commands {
         blue: f64,
     },
     /// Apply halftoning using the dithering method
-    Halftone,
+    Halftone {
+        #[command(flatten)]
+        dither: DitherArgs,
+    },
+    /// Binarize the image with a global or adaptive threshold
+    Threshold {
+        /// Threshold method
+        #[arg(long, value_enum, default_value_t = ThresholdKind::Otsu)]
+        method: ThresholdKind,
+        /// Level for --method fixed; brighter pixels become white
+        #[arg(long, default_value = "128")]
+        value: u8,
+        /// Radius of the local window for adaptive methods (>= 1)
+        #[arg(long, default_value = "15")]
+        radius: u32,
+        /// Subtracted from the local mean by adaptive-mean and adaptive-gaussian
+        #[arg(long, default_value = "5.0", allow_hyphen_values = true)]
+        offset: f32,
+        /// Standard deviation weight (default: 0.2 for sauvola, -0.2 for niblack)
+        #[arg(long, allow_hyphen_values = true)]
+        k: Option<f32>,
+        /// Dynamic range of the standard deviation for sauvola (> 0.0)
+        #[arg(long, default_value = "128.0")]
+        range: f32,
+        /// Output white for dark pixels (e.g. to get white text on black)
+        #[arg(long)]
+        invert: bool,
+        #[command(flatten)]
+        border: BorderArgs,
+    },
+    /// Dither to gray levels, per-channel levels or a palette (RGB output)
+    #[command(group(
+        ArgGroup::new("target")
+            .required(true)
+            .args(["gray", "levels", "palette", "palette_name", "palette_file"])
+    ))]
+    Dither {
+        /// Number of gray levels (>= 2)
+        #[arg(long)]
+        gray: Option<u32>,
+        /// Number of levels per RGB channel (>= 2)
+        #[arg(long)]
+        levels: Option<u32>,
+        #[command(flatten)]
+        palette: PaletteArgs,
+        /// Color space for palette matching and error diffusion
+        #[arg(long, value_enum, default_value_t = ColorSpace::Rgb)]
+        space: ColorSpace,
+        #[command(flatten)]
+        dither: DitherArgs,
+    },
+    /// Reduce the image to an optimized N-color palette
+    Quantize {
+        /// Number of palette colors (1..=256)
+        #[arg(short, long, default_value = "16")]
+        colors: usize,
+        /// Palette construction method
+        #[arg(long, value_enum, default_value_t = PaletteMethod::Wu)]
+        method: PaletteMethod,
+        /// Random seed for k-means
+        #[arg(long, default_value = "0")]
+        seed: u64,
+        /// Dither when remapping to the palette instead of snapping each pixel
+        #[arg(long, value_enum, value_name = "METHOD")]
+        dither: Option<DitherKind>,
+        /// Print the generated palette as hex colors
+        #[arg(long)]
+        print_palette: bool,
+    },
+    /// Snap every pixel to the nearest color of a palette
+    #[command(group(
+        ArgGroup::new("source")
+            .required(true)
+            .args(["palette", "palette_name", "palette_file"])
+    ))]
+    Palette {
+        #[command(flatten)]
+        palette: PaletteArgs,
+        /// Distance metric used to find the nearest color
+        #[arg(long, value_enum, default_value_t = Metric::Rgb)]
+        metric: Metric,
+    },
+    /// Blur the image (Gaussian or box)
+    Blur {
+        /// Blur method
+        #[arg(long, value_enum, default_value_t = BlurKind::Gaussian)]
+        method: BlurKind,
+        /// Standard deviation of the Gaussian (> 0.0)
+        #[arg(long, default_value = "2.0")]
+        sigma: f32,
+        /// Box radius in pixels
+        #[arg(long, default_value = "2")]
+        radius: u32,
+        /// Number of box passes for fast-box (3 approximates a Gaussian)
+        #[arg(long, default_value = "3")]
+        passes: u32,
+        #[command(flatten)]
+        border: BorderArgs,
+    },
+    /// Convolve the image with a custom kernel
+    #[command(group(
+        ArgGroup::new("kernel_source")
+            .required(true)
+            .args(["kernel", "kernel_file"])
+    ))]
+    Convolve {
+        /// Kernel rows separated by ';', values by ',' (e.g. "0,-1,0;-1,5,-1;0,-1,0")
+        #[arg(long, allow_hyphen_values = true)]
+        kernel: Option<String>,
+        /// Load the kernel from a text or JSON file
+        #[arg(long, value_name = "FILE")]
+        kernel_file: Option<PathBuf>,
+        /// Divide the weighted sum by this value (default: kernel sum, or 1 if it is 0)
+        #[arg(long, allow_hyphen_values = true)]
+        divisor: Option<f32>,
+        /// Add this value after dividing (default: 0)
+        #[arg(long, allow_hyphen_values = true)]
+        bias: Option<f32>,
+        /// Channels to convolve
+        #[arg(long, value_enum, default_value_t = ChannelMode::Rgb)]
+        channels: ChannelMode,
+        #[command(flatten)]
+        border: BorderArgs,
+    },
     /// Perform gamma correction
     Gamma {
         /// Gamma value
         #[arg(short, long)]
         gamma: f64,
     },
+    /// Adjust levels: input black/white points, midtone gamma and output range
+    Levels {
+        /// Input black point; darker pixels become the output black
+        #[arg(long, default_value = "0")]
+        black: u8,
+        /// Input white point; brighter pixels become the output white
+        #[arg(long, default_value = "255")]
+        white: u8,
+        /// Midtone gamma (> 0.0; above 1.0 brightens the midtones)
+        #[arg(long, default_value = "1.0")]
+        gamma: f64,
+        /// Darkest output level
+        #[arg(long, default_value = "0")]
+        out_black: u8,
+        /// Brightest output level
+        #[arg(long, default_value = "255")]
+        out_white: u8,
+        /// Pick the black and white points from the histogram (overrides --black/--white)
+        #[arg(long)]
+        auto: bool,
+        /// Percentage of pixels clipped at each end by --auto (0.0..50.0)
+        #[arg(long, default_value = "0.5")]
+        clip: f64,
+        /// Measure --auto on each channel separately (also removes color casts)
+        #[arg(long)]
+        per_channel: bool,
+    },
+    /// Adjust exposure (in stops), contrast and brightness
+    Adjust {
+        /// Exposure change in stops, applied in linear light (e.g. -1.0 halves the light)
+        #[arg(long, default_value = "0.0", allow_hyphen_values = true)]
+        exposure: f64,
+        /// Contrast multiplier around mid-gray (>= 0.0; 1.0 = unchanged)
+        #[arg(long, default_value = "1.0")]
+        contrast: f64,
+        /// Gray levels added after the contrast change (-255.0..255.0)
+        #[arg(long, default_value = "0.0", allow_hyphen_values = true)]
+        brightness: f64,
+    },
+    /// Apply tone curves through control points (master and per channel)
+    Curves {
+        /// Master curve points applied to all channels (e.g. "0,0 64,50 192,210 255,255")
+        #[arg(long, value_name = "POINTS")]
+        master: Option<String>,
+        /// Red channel curve points
+        #[arg(long, value_name = "POINTS")]
+        red: Option<String>,
+        /// Green channel curve points
+        #[arg(long, value_name = "POINTS")]
+        green: Option<String>,
+        /// Blue channel curve points
+        #[arg(long, value_name = "POINTS")]
+        blue: Option<String>,
+        /// Load curves from a JSON file (inline curves take precedence)
+        #[arg(long, value_name = "FILE")]
+        curves_file: Option<PathBuf>,
+        /// Interpolation between points (default: from the file, else monotone)
+        #[arg(long, value_enum)]
+        interpolation: Option<CurveKind>,
+    },
+    /// Grade the image through a .cube or HaldCLUT color lookup table
+    Lut {
+        /// LUT file: .cube (1D and/or 3D) or a HaldCLUT image
+        #[arg(long, value_name = "FILE")]
+        lut_file: PathBuf,
+        /// Interpolation of the 3D lattice
+        #[arg(long, value_enum, default_value_t = LutKind::Trilinear)]
+        interpolation: LutKind,
+    },
+    /// Tone the image with the classic sepia matrix
+    Sepia {
+        /// Blend with the original (0.0 = unchanged, 1.0 = full sepia)
+        #[arg(long, default_value = "1.0")]
+        strength: f64,
+    },
+    /// Map the luminance to a blend of two colors (three with --midtones)
+    Duotone {
+        /// Color of black input
+        #[arg(long, value_name = "HEX", value_parser = parse_color)]
+        shadows: image::Rgb<u8>,
+        /// Color of mid-gray input (makes a tritone)
+        #[arg(long, value_name = "HEX", value_parser = parse_color)]
+        midtones: Option<image::Rgb<u8>>,
+        /// Color of white input
+        #[arg(long, value_name = "HEX", value_parser = parse_color)]
+        highlights: image::Rgb<u8>,
+    },
+    /// Map the luminance through a gradient of color stops
+    GradientMap {
+        /// Comma-separated POS:HEX stops with POS in 0..1 (e.g. "0:#1b1b3a,0.6:#c0392b,1:#f7dc6f"),
+        /// or bare hex colors spread evenly
+        #[arg(long, value_name = "STOPS")]
+        stops: String,
+    },
+    /// Pseudo-color the luminance through a colormap (viridis, turbo, ...)
+    #[command(group(
+        ArgGroup::new("source")
+            .required(true)
+            .args(["colormap", "colormap_file"])
+    ))]
+    Colormap {
+        #[command(flatten)]
+        colormap: ColormapArgs,
+    },
+    /// Equalize the luminance histogram (global or CLAHE)
+    Equalize {
+        /// Equalization method
+        #[arg(long, value_enum, default_value_t = EqualizeKind::Global)]
+        method: EqualizeKind,
+        /// Number of CLAHE tiles across (>= 1)
+        #[arg(long, default_value = "8")]
+        tiles_x: u32,
+        /// Number of CLAHE tiles down (>= 1)
+        #[arg(long, default_value = "8")]
+        tiles_y: u32,
+        /// CLAHE clip limit as a multiple of the average bin count (>= 1.0)
+        #[arg(long, default_value = "2.0")]
+        clip_limit: f32,
+        /// Output the equalized luminance as a grayscale image
+        #[arg(long)]
+        gray: bool,
+    },
     /// Apply negative-positive inversion
     Invert,
-    /// Detect edges (default: Sobel operator)
-    Edge {
-        /// Edge detection method
-        #[arg(long, value_enum, default_value_t = EdgeMethod::Sobel)]
-        method: EdgeMethod,
-        /// Intensity multiplier applied after normalization (>= 0.0)
-        #[arg(long, default_value = "1.0")]
-        intensity: f64,
+    /// Pixelate the image into uniform blocks (mosaic)
+    Pixelate {
+        /// Block width in pixels
+        #[arg(long, default_value = "8")]
+        block_width: u32,
+        /// Block height in pixels (default: same as block width)
+        #[arg(long)]
+        block_height: Option<u32>,
+        /// How each block's color is chosen
+        #[arg(long, value_enum, default_value_t = BlockMode::Average)]
+        mode: BlockMode,
+        /// Draw grid lines between blocks in the given hex color (e.g. "#000000")
+        #[arg(long, value_name = "HEX", value_parser = parse_color)]
+        grid: Option<image::Rgb<u8>>,
     },
+    /// Detect edges (default: Sobel operator)
+    Edge(EdgeArgs),
+}
+
+/// Bake `args.steps` into a `.cube` file at `output`; the input image is only
+/// decoded to save the `--preview`.
+fn bake(args: &BakeArgs, input: Option<&Path>, output: &Path) -> Result<(), Box<dyn Error>> {
+    if !(2..=256).contains(&args.size) {
+        return Err("LUT size must be between 2 and 256".into());
+    }
+    let chain = parse_chain(&args.steps)?;
+    let mut lut = ColorLut::bake(args.size, |img| chain.apply(img));
+    lut.title = args.title.clone();
+    std::fs::write(output, lut.to_cube())?;
+    if let Some(preview) = &args.preview {
+        let img = LutFilter::new(lut).apply(&read_input(input)?);
+        img.save(preview)?;
+    }
+    Ok(())
+}
+
+/// Unwrap an argument only some commands require, exiting with clap's usage
+/// error when it is missing.
+fn required<'a>(value: Option<&'a Path>, arg: &str) -> &'a Path {
+    value.unwrap_or_else(|| {
+        Cli::command()
+            .error(
+                ErrorKind::MissingRequiredArgument,
+                format!("the following required arguments were not provided:\n  {arg}"),
+            )
+            .exit()
+    })
+}
+
+/// Decode the `--input` image.
+fn read_input(input: Option<&Path>) -> Result<image::RgbImage, Box<dyn Error>> {
+    Ok(image::open(required(input, "--input <FILE>"))?.to_rgb8())
 }
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
+fn main() -> Result<(), Box<dyn Error>> {
     let start = time::Instant::now();
     let cli = Cli::parse();
+    let command = match &cli.command {
+        Commands::Image(command) => command,
+        // The report goes to stdout without the timing line so scripts can parse it
+        Commands::Stats(args) => {
+            return print_stats(
+                &read_input(cli.input.as_deref())?,
+                args,
+                cli.output.as_deref(),
+            )
+        }
+        Commands::Bake(args) => {
+            let output = required(cli.output.as_deref(), "--output <FILE>");
+            bake(args, cli.input.as_deref(), output)?;
+            println!("Compute time: {:?}", start.elapsed());
+            return Ok(());
+        }
+    };
+    let output = required(cli.output.as_deref(), "--output <FILE>");
 
     // Read the image file
-    let dynamic_img: image::DynamicImage = image::open(&cli.input)?;
-    let rgb_img: image::ImageBuffer<image::Rgb<u8>, Vec<u8>> = dynamic_img.to_rgb8();
+    let rgb_img = read_input(cli.input.as_deref())?;
 
-    match &cli.command {
-        Commands::Grayscale { red, green, blue } => {
+    match command {
+        ImageCommand::Grayscale { red, green, blue } => {
             if red + green + blue > 1.0 {
                 return Err("The sum of the RGB weights must be less than or equal to 1.0".into());
             }
             let filter: GrayscaleFilter = GrayscaleFilter::new(*red, *green, *blue);
             let img = filter.apply(&rgb_img);
-            img.save(&cli.output)?;
+            img.save(output)?;
+        }
+        ImageCommand::Halftone { dither } => {
+            let filter: HalftoneFilter = HalftoneFilter::new(dither.method.into())
+                .with_matrix(dither.threshold_map()?)
+                .with_serpentine(dither.serpentine)
+                .with_strength(dither.strength);
+            let img = filter.apply(&rgb_img);
+            img.save(output)?;
         }
-        Commands::Halftone => {
-            let filter: HalftoneFilter = HalftoneFilter;
+        ImageCommand::Threshold {
+            method,
+            value,
+            radius,
+            offset,
+            k,
+            range,
+            invert,
+            border,
+        } => {
+            if *radius == 0
+                && !matches!(
+                    method,
+                    ThresholdKind::Fixed | ThresholdKind::Otsu | ThresholdKind::Triangle
+                )
+            {
+                return Err("Radius must be at least 1".into());
+            }
+            let method = match method {
+                ThresholdKind::Fixed => ThresholdMethod::Fixed(*value),
+                ThresholdKind::Otsu => ThresholdMethod::Otsu,
+                ThresholdKind::Triangle => ThresholdMethod::Triangle,
+                ThresholdKind::AdaptiveMean => ThresholdMethod::AdaptiveMean {
+                    radius: *radius,
+                    offset: *offset,
+                },
+                ThresholdKind::AdaptiveGaussian => ThresholdMethod::AdaptiveGaussian {
+                    radius: *radius,
+                    offset: *offset,
+                },
+                ThresholdKind::Sauvola => {
+                    if *range <= 0.0 {
+                        return Err("Range must be greater than 0.0".into());
+                    }
+                    ThresholdMethod::Sauvola {
+                        radius: *radius,
+                        k: k.unwrap_or(0.2),
+                        range: *range,
+                    }
+                }
+                ThresholdKind::Niblack => ThresholdMethod::Niblack {
+                    radius: *radius,
+                    k: k.unwrap_or(-0.2),
+                },
+            };
+            let filter: ThresholdFilter = ThresholdFilter::new(method)
+                .with_invert(*invert)
+                .with_border(border.mode()?);
+            let img = filter.apply(&rgb_img);
+            img.save(output)?;
+        }
+        ImageCommand::Dither {
+            gray,
+            levels,
+            palette,
+            space,
+            dither,
+        } => {
+            let target = match (gray, levels) {
+                (Some(n), _) | (_, Some(n)) if *n < 2 => {
+                    return Err("The number of levels must be at least 2".into());
+                }
+                (Some(n), _) => DitherTarget::Gray(*n),
+                (_, Some(n)) => DitherTarget::Levels(*n),
+                (None, None) => match palette.load()? {
+                    Some(palette) => DitherTarget::Palette(palette),
+                    None => return Err("No dither target was given".into()),
+                },
+            };
+            let filter: DitherFilter = DitherFilter::new(target)
+                .with_space((*space).into())
+                .with_method(dither.method.into())
+                .with_matrix(dither.threshold_map()?)
+                .with_serpentine(dither.serpentine)
+                .with_strength(dither.strength);
+            let img = filter.apply(&rgb_img);
+            img.save(output)?;
+        }
+        ImageCommand::Quantize {
+            colors,
+            method,
+            seed,
+            dither,
+            print_palette,
+        } => {
+            if !(1..=256).contains(colors) {
+                return Err("The number of colors must be between 1 and 256".into());
+            }
+            let filter: QuantizeFilter =
+                QuantizeFilter::new(*colors, (*method).into()).with_seed(*seed);
+            let quantized = filter.quantize(&rgb_img);
+            if *print_palette {
+                for c in quantized.palette.colors() {
+                    println!("#{:02x}{:02x}{:02x}", c[0], c[1], c[2]);
+                }
+            }
+            let img = match dither {
+                Some(kind) => DitherFilter::palette(quantized.palette)
+                    .with_method((*kind).into())
+                    .apply(&rgb_img),
+                None => quantized.image,
+            };
+            img.save(output)?;
+        }
+        ImageCommand::Palette { palette, metric } => {
+            let Some(palette) = palette.load()? else {
+                return Err("No palette was given".into());
+            };
+            let filter: PaletteMapFilter = PaletteMapFilter::new(palette, (*metric).into());
+            let img = filter.apply(&rgb_img);
+            img.save(output)?;
+        }
+        ImageCommand::Blur {
+            method,
+            sigma,
+            radius,
+            passes,
+            border,
+        } => {
+            let method = match method {
+                BlurKind::Gaussian => {
+                    if *sigma <= 0.0 {
+                        return Err("Sigma must be greater than 0.0".into());
+                    }
+                    BlurMethod::Gaussian { sigma: *sigma }
+                }
+                BlurKind::Box => BlurMethod::Box { radius: *radius },
+                BlurKind::FastBox => BlurMethod::FastBox {
+                    radius: *radius,
+                    passes: *passes,
+                },
+            };
+            let filter: BlurFilter = BlurFilter::new(method).with_border(border.mode()?);
             let img = filter.apply(&rgb_img);
-            img.save(&cli.output)?;
+            img.save(output)?;
         }
-        Commands::Gamma { gamma } => {
+        ImageCommand::Convolve {
+            kernel,
+            kernel_file,
+            divisor,
+            bias,
+            channels,
+            border,
+        } => {
+            let mut filter: ConvolveFilter = match (kernel, kernel_file) {
+                (Some(text), _) => ConvolveFilter::new(text.parse()?),
+                (None, Some(path)) => load_kernel_file(path)?,
+                (None, None) => return Err("No kernel was given".into()),
+            }
+            .with_channels((*channels).into())
+            .with_border(border.mode()?);
+            if let Some(bias) = bias {
+                filter = filter.with_bias(*bias);
+            }
+            if let Some(divisor) = divisor {
+                if *divisor == 0.0 {
+                    return Err("Divisor must not be 0".into());
+                }
+                filter = filter.with_divisor(*divisor);
+            }
+            let img = filter.apply(&rgb_img);
+            img.save(output)?;
+        }
+        ImageCommand::Gamma { gamma } => {
             if *gamma <= 0.0 {
                 return Err("Gamma value must be greater than 0.0".into());
             }
             let filter: GammaFilter = GammaFilter::new(*gamma);
             let img = filter.apply(&rgb_img);
-            img.save(&cli.output)?;
+            img.save(output)?;
+        }
+        ImageCommand::Levels {
+            black,
+            white,
+            gamma,
+            out_black,
+            out_white,
+            auto,
+            clip,
+            per_channel,
+        } => {
+            if !(*gamma > 0.0 && gamma.is_finite()) {
+                return Err("Gamma value must be positive and finite".into());
+            }
+            let mut filter: LevelsFilter = LevelsFilter::new(
+                Levels::new(*black, *white)
+                    .with_gamma(*gamma)
+                    .with_output(*out_black, *out_white),
+            );
+            if *auto {
+                if !(0.0..50.0).contains(clip) {
+                    return Err("Clip percentage must be in 0.0..50.0".into());
+                }
+                filter = filter.with_auto(*clip).with_per_channel(*per_channel);
+            } else if black >= white {
+                return Err("Black point must be below the white point".into());
+            }
+            let img = filter.apply(&rgb_img);
+            img.save(output)?;
+        }
+        ImageCommand::Adjust {
+            exposure,
+            contrast,
+            brightness,
+        } => {
+            if *contrast < 0.0 {
+                return Err("Contrast must be at least 0.0".into());
+            }
+            let exposed = ExposureFilter::new(*exposure).apply(&rgb_img);
+            let filter: BrightnessContrastFilter =
+                BrightnessContrastFilter::new(*brightness, *contrast);
+            let img = filter.apply(&exposed);
+            img.save(output)?;
+        }
+        ImageCommand::Curves {
+            master,
+            red,
+            green,
+            blue,
+            curves_file,
+            interpolation,
+        } => {
+            let mut filter: CurvesFilter = match curves_file {
+                Some(path) => CurvesFilter::from_json(&std::fs::read_to_string(path)?)?,
+                None => CurvesFilter::default(),
+            };
+            let curves = [
+                (master, &mut filter.master),
+                (red, &mut filter.red),
+                (green, &mut filter.green),
+                (blue, &mut filter.blue),
+            ];
+            for (inline, curve) in curves {
+                if let Some(points) = inline {
+                    *curve = points.parse()?;
+                }
+            }
+            if let Some(interpolation) = interpolation {
+                filter.interpolation = (*interpolation).into();
+            }
+            let img = filter.apply(&rgb_img);
+            img.save(output)?;
+        }
+        ImageCommand::Lut {
+            lut_file,
+            interpolation,
+        } => {
+            let filter: LutFilter = LutFilter::new(ColorLut::open(lut_file)?)
+                .with_interpolation((*interpolation).into());
+            let img = filter.apply(&rgb_img);
+            img.save(output)?;
+        }
+        ImageCommand::Sepia { strength } => {
+            if !(0.0..=1.0).contains(strength) {
+                return Err("Strength must be between 0.0 and 1.0".into());
+            }
+            let filter: SepiaFilter = SepiaFilter::new(*strength);
+            let img = filter.apply(&rgb_img);
+            img.save(output)?;
+        }
+        ImageCommand::Duotone {
+            shadows,
+            midtones,
+            highlights,
+        } => {
+            let filter: GradientMapFilter = match midtones {
+                Some(midtones) => GradientMapFilter::tritone(*shadows, *midtones, *highlights),
+                None => GradientMapFilter::duotone(*shadows, *highlights),
+            };
+            let img = filter.apply(&rgb_img);
+            img.save(output)?;
         }
-        Commands::Invert => {
+        ImageCommand::GradientMap { stops } => {
+            let filter: GradientMapFilter = GradientMapFilter::new(stops.parse()?);
+            let img = filter.apply(&rgb_img);
+            img.save(output)?;
+        }
+        ImageCommand::Colormap { colormap } => {
+            let gray_img: image::ImageBuffer<image::Luma<u8>, Vec<u8>> =
+                GrayscaleFilter::new(SRGB_LUMA_R, SRGB_LUMA_G, SRGB_LUMA_B).apply(&rgb_img);
+            colormap.save(&gray_img, output)?;
+        }
+        ImageCommand::Equalize {
+            method,
+            tiles_x,
+            tiles_y,
+            clip_limit,
+            gray,
+        } => {
+            let method = match method {
+                EqualizeKind::Global => EqualizeMethod::Global,
+                EqualizeKind::Clahe => {
+                    if *tiles_x == 0 || *tiles_y == 0 {
+                        return Err("The number of tiles must be at least 1".into());
+                    }
+                    if *clip_limit < 1.0 {
+                        return Err("Clip limit must be at least 1.0".into());
+                    }
+                    EqualizeMethod::Clahe {
+                        tiles_x: *tiles_x,
+                        tiles_y: *tiles_y,
+                        clip_limit: *clip_limit,
+                    }
+                }
+            };
+            let filter: EqualizeFilter = EqualizeFilter::new(method);
+            if *gray {
+                let gray_img: image::ImageBuffer<image::Luma<u8>, Vec<u8>> =
+                    GrayscaleFilter::new(SRGB_LUMA_R, SRGB_LUMA_G, SRGB_LUMA_B).apply(&rgb_img);
+                let img: image::ImageBuffer<image::Luma<u8>, Vec<u8>> = filter.apply(&gray_img);
+                img.save(output)?;
+            } else {
+                let img: image::ImageBuffer<image::Rgb<u8>, Vec<u8>> = filter.apply(&rgb_img);
+                img.save(output)?;
+            }
+        }
+        ImageCommand::Invert => {
             let filter: InvertFilter = InvertFilter;
             let img = filter.apply(&rgb_img);
-            img.save(&cli.output)?;
+            img.save(output)?;
         }
-        Commands::Edge { method, intensity } => match method {
-            EdgeMethod::Sobel => {
-                if *intensity < 0.0 {
-                    return Err("Intensity must be >= 0.0".into());
+        ImageCommand::Pixelate {
+            block_width,
+            block_height,
+            mode,
+            grid,
+        } => {
+            let block_height = block_height.unwrap_or(*block_width);
+            if *block_width == 0 || block_height == 0 {
+                return Err("Block size must be greater than 0".into());
+            }
+            let mut filter: PixelateFilter =
+                PixelateFilter::new(*block_width, block_height, (*mode).into());
+            if let Some(color) = grid {
+                filter = filter.with_grid(*color);
+            }
+            let img = filter.apply(&rgb_img);
+            img.save(output)?;
+        }
+        ImageCommand::Edge(args) => {
+            args.check()?;
+            let EdgeArgs {
+                method,
+                intensity,
+                render,
+                color,
+                space,
+                sigma,
+                sigma2,
+                neighbours,
+                zero_crossings,
+                zero_threshold,
+                low,
+                high,
+                normalize,
+                border,
+                colormap,
+            } = args;
+            let intensity = intensity.unwrap_or(1.0);
+            if intensity < 0.0 {
+                return Err("Intensity must be >= 0.0".into());
+            }
+            let render = render.unwrap_or(EdgeRender::Magnitude);
+            let color = color.unwrap_or(EdgeColor::Luma);
+            let space = space.unwrap_or(ColorSpace::Rgb);
+            let sigma = sigma.unwrap_or(1.4);
+            let neighbours = neighbours.unwrap_or(4);
+            let scale = MagnitudeScale::new(intensity as f32)
+                .with_normalization(normalize.normalization()?)
+                .with_threshold(normalize.threshold);
+            let border = border.mode()?;
+            let operator = method.operator();
+            // EdgeArgs::check only lets --render and --color through for gradient operators
+            let gradient = operator
+                .and_then(EdgeOperator::gradient)
+                .filter(|_| !matches!(render, EdgeRender::Magnitude) || color != EdgeColor::Luma);
+            if let Some(operator) = gradient {
+                let colormapped = colormap.colormap.is_some() || colormap.colormap_file.is_some();
+                if colormapped
+                    && (color == EdgeColor::Channels || !matches!(render, EdgeRender::Magnitude))
+                {
+                    return Err("--colormap requires a grayscale --render magnitude output".into());
+                }
+                if color == EdgeColor::Channels {
+                    if !matches!(render, EdgeRender::Magnitude) {
+                        return Err("--color channels only supports --render magnitude".into());
+                    }
+                    let filter: ChannelEdgeFilter = ChannelEdgeFilter::new(operator, intensity)
+                        .with_space(space.into())
+                        .with_scale(scale)
+                        .with_border(border);
+                    let img = filter.apply(&rgb_img);
+                    img.save(output)?;
+                } else {
+                    let mut filter: GradientFilter =
+                        GradientFilter::new(operator).with_border(border);
+                    let combine = match color {
+                        EdgeColor::Max => Some(ChannelCombine::Max),
+                        EdgeColor::Sum => Some(ChannelCombine::Sum),
+                        EdgeColor::DiZenzo => Some(ChannelCombine::DiZenzo),
+                        _ => None,
+                    };
+                    if let Some(combine) = combine {
+                        filter = filter.with_color(combine, space.into());
+                    }
+                    let gradient = filter.apply(&rgb_img);
+                    match render {
+                        EdgeRender::Magnitude => colormap.save(&gradient.to_luma(scale), output)?,
+                        EdgeRender::Direction => gradient.to_direction_rgb(scale).save(output)?,
+                        EdgeRender::Raw16 => {
+                            let raw_scale = filter.raw_u16_scale();
+                            eprintln!("raw16: value = 32768 + {raw_scale} * derivative");
+                            gradient.to_raw_u16(raw_scale).save(output)?
+                        }
+                        EdgeRender::RawFloat => gradient.to_raw_f32().save(output)?,
+                    }
                 }
-                let filter: SobelFilter = SobelFilter::new(*intensity);
-                let img = filter.apply(&rgb_img);
-                img.save(&cli.output)?;
+            } else {
+                let img = match (operator, method) {
+                    (Some(operator), _) => GradientEdgeFilter::new(operator, intensity)
+                        .with_scale(scale)
+                        .with_border(border)
+                        .apply(&rgb_img),
+                    (None, EdgeMethod::Canny) => {
+                        if sigma < 0.0 {
+                            return Err("Sigma must be >= 0.0".into());
+                        }
+                        let mut filter: CannyFilter = CannyFilter::new(sigma).with_border(border);
+                        if let (Some(low), Some(high)) = (low, high) {
+                            if *low < 0.0 || low > high {
+                                return Err("Thresholds must satisfy 0.0 <= low <= high".into());
+                            }
+                            filter = filter.with_thresholds(*low, *high);
+                        }
+                        filter.apply(&rgb_img)
+                    }
+                    (None, _) => {
+                        let operator = match method {
+                            EdgeMethod::Laplacian => match neighbours {
+                                4 => SecondDerivative::Laplacian4,
+                                8 => SecondDerivative::Laplacian8,
+                                _ => return Err("Neighbours must be 4 or 8".into()),
+                            },
+                            _ if sigma <= 0.0 => {
+                                return Err("Sigma must be greater than 0.0".into());
+                            }
+                            EdgeMethod::Log => SecondDerivative::LoG { sigma },
+                            _ => {
+                                let sigma2 = sigma2.unwrap_or(1.6 * sigma);
+                                if sigma2 <= 0.0 {
+                                    return Err("Sigma2 must be greater than 0.0".into());
+                                }
+                                SecondDerivative::DoG {
+                                    sigma1: sigma,
+                                    sigma2,
+                                }
+                            }
+                        };
+                        let mut filter: LaplacianFilter = LaplacianFilter::new(operator, intensity)
+                            .with_scale(scale)
+                            .with_border(border);
+                        if *zero_crossings {
+                            if !(0.0..=1.0).contains(zero_threshold) {
+                                return Err("Zero threshold must be between 0.0 and 1.0".into());
+                            }
+                            filter = filter.with_zero_crossings(*zero_threshold);
+                        }
+                        filter.apply(&rgb_img)
+                    }
+                };
+                colormap.save(&img, output)?;
             }
-        },
+        }
     }
     println!("Compute time: {:?}", start.elapsed());
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn edge_args(args: &[&str]) -> EdgeArgs {
+        let argv = ["pixelate", "-o", "edges.png", "edge"].iter().chain(args);
+        let Commands::Image(ImageCommand::Edge(args)) = Cli::try_parse_from(argv).unwrap().command
+        else {
+            panic!("not an edge command");
+        };
+        args
+    }
+
+    fn edge_error(args: &[&str]) -> String {
+        edge_args(args).check().unwrap_err().to_string()
+    }
+
+    #[test]
+    fn test_edge_rejects_ignored_options() {
+        assert_eq!(
+            edge_error(&["--method", "canny", "--normalize", "fixed", "--scale", "10"]),
+            "--normalize does not apply to --method canny"
+        );
+        assert_eq!(
+            edge_error(&["--method", "canny", "--intensity", "2"]),
+            "--intensity does not apply to --method canny"
+        );
+        assert_eq!(
+            edge_error(&["--method", "log", "--zero-crossings", "--threshold", "128"]),
+            "--threshold does not apply to --zero-crossings"
+        );
+        assert_eq!(
+            edge_error(&["--render", "raw16", "--percentile", "90"]),
+            "--percentile does not apply to the raw renders"
+        );
+        assert_eq!(
+            edge_error(&["--sigma", "2"]),
+            "--sigma does not apply to --method sobel"
+        );
+        assert_eq!(
+            edge_error(&["--method", "kirsch", "--color", "max"]),
+            "--color does not apply to --method kirsch"
+        );
+        for args in [
+            &[
+                "--method", "canny", "--sigma", "2", "--low", "10", "--high", "20",
+            ][..],
+            &["--method", "dog", "--sigma2", "3", "--intensity", "2"],
+            &["--render", "direction", "--normalize", "log"],
+            &["--color", "max", "--space", "oklab"],
+        ] {
+            assert!(edge_args(args).check().is_ok(), "{:?}", args);
+        }
+    }
+
+    #[test]
+    fn test_scale_options_require_their_mode() {
+        let args = edge_args(&["--normalize", "percentile", "--scale", "10"]);
+        assert!(args.normalize.normalization().is_err());
+        let args = edge_args(&["--percentile", "90"]);
+        assert!(args.normalize.normalization().is_err());
+        let args = edge_args(&["--border-value", "128"]);
+        assert!(args.border.mode().is_err());
+        let args = edge_args(&["--border", "constant", "--border-value", "128"]);
+        assert_eq!(args.border.mode().unwrap(), BorderMode::Constant(128.0));
+    }
+}
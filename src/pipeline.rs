@@ -5,11 +5,16 @@
 //! use pixelate::filters::{Filter, InvertFilter, HalftoneFilter};
 //! use pixelate::pipeline::Pipeline;
 //! # let img = image::RgbImage::new(1,1);
-//! let pipe = Pipeline::new(InvertFilter).then(HalftoneFilter);
+//! let pipe = Pipeline::new(InvertFilter).then(HalftoneFilter::default());
 //! let out = pipe.apply(&img);
 //! ```
+//!
+//! [`FilterChain`] is the dynamic counterpart for chains chosen at runtime,
+//! and [`parse`] builds one from text steps such as `gamma:0.45`.
 use crate::filters::Filter;
 
+pub mod parse;
+
 /// A pipeline wrapping a filter `F: Filter<I, O>`.
 pub struct Pipeline<I, O, F>
 where
@@ -77,3 +82,41 @@ where
         self.b.apply(&mid)
     }
 }
+
+/// Filters of one image type applied one after another, chosen at runtime.
+///
+/// Unlike [`Pipeline`], the filters are boxed, so a chain can be built from
+/// user input (see [`parse::parse_chain`]).
+pub struct FilterChain<T> {
+    /// Filters in the order they are applied.
+    pub filters: Vec<Box<dyn Filter<T, T>>>,
+}
+
+impl<T> FilterChain<T> {
+    /// Create a chain from boxed filters.
+    pub fn new(filters: Vec<Box<dyn Filter<T, T>>>) -> Self {
+        Self { filters }
+    }
+
+    /// Append a filter to the end of the chain.
+    pub fn then(mut self, filter: impl Filter<T, T> + 'static) -> Self {
+        self.filters.push(Box::new(filter));
+        self
+    }
+}
+
+impl<T> Default for FilterChain<T> {
+    fn default() -> Self {
+        Self::new(Vec::new())
+    }
+}
+
+impl<T: Clone> Filter<T, T> for FilterChain<T> {
+    /// Apply each filter to the output of the previous one; an empty chain
+    /// returns a copy of the input.
+    fn apply(&self, input: &T) -> T {
+        self.filters
+            .iter()
+            .fold(input.clone(), |img, filter| filter.apply(&img))
+    }
+}
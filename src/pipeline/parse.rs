@@ -0,0 +1,205 @@
+//! Text form of RGB filter chains, as used by `pixelate bake`.
+//!
+//! A step is a filter name, optionally followed by `:` and its arguments:
+//! `invert`, `gamma:0.45`, `levels:16,235`, `curves:grade.json`. Only
+//! filters that treat each pixel on its own are available, so a parsed chain
+//! can always be baked into a [`ColorLut`].
+use std::path::Path;
+
+use image::RgbImage;
+
+use super::FilterChain;
+use crate::color::ColorLut;
+use crate::filters::{
+    BrightnessContrastFilter, CurvesFilter, ExposureFilter, Filter, GammaFilter, GradientMapFilter,
+    InvertFilter, Levels, LevelsFilter, LutFilter, SepiaFilter,
+};
+
+/// Parse one step such as `invert`, `gamma:0.45` or `levels:16,235`.
+///
+/// The text after the first `:` holds the step's arguments; `curves` and
+/// `lut` read their file when parsed.
+pub fn parse_step(step: &str) -> Result<Box<dyn Filter<RgbImage, RgbImage>>, String> {
+    let (name, args) = step.split_once(':').unwrap_or((step, ""));
+    let numbers = || -> Result<Vec<f64>, String> {
+        args.split(',')
+            .filter(|v| !v.is_empty())
+            .map(|v| match v.trim().parse::<f64>() {
+                Ok(v) if v.is_finite() => Ok(v),
+                _ => Err(format!("invalid number in step {step}")),
+            })
+            .collect()
+    };
+    let number = || -> Result<f64, String> {
+        match numbers()?[..] {
+            [v] => Ok(v),
+            _ => Err(format!("step {step} expects one number ({name}:VALUE)")),
+        }
+    };
+    let level = |v: f64| -> Result<u8, String> {
+        if (0.0..=255.0).contains(&v) && v.fract() == 0.0 {
+            Ok(v as u8)
+        } else {
+            Err(format!("levels in step {step} must be integers in 0..255"))
+        }
+    };
+    let gamma = |v: f64| -> Result<f64, String> {
+        if v > 0.0 && v.is_finite() {
+            Ok(v)
+        } else {
+            Err("Gamma value must be positive and finite".into())
+        }
+    };
+    Ok(match name {
+        "invert" if args.is_empty() => Box::new(InvertFilter),
+        "gamma" => Box::new(GammaFilter::new(gamma(number()?)?)),
+        "exposure" => Box::new(ExposureFilter::new(number()?)),
+        "brightness" => Box::new(BrightnessContrastFilter::new(number()?, 1.0)),
+        "contrast" => {
+            let contrast = number()?;
+            if contrast < 0.0 {
+                return Err("Contrast must be at least 0.0".into());
+            }
+            Box::new(BrightnessContrastFilter::new(0.0, contrast))
+        }
+        "levels" => {
+            let values = numbers()?;
+            let levels = match values[..] {
+                [black, white] => Levels::new(level(black)?, level(white)?),
+                [black, white, g] => {
+                    Levels::new(level(black)?, level(white)?).with_gamma(gamma(g)?)
+                }
+                [black, white, g, out_black, out_white] => {
+                    Levels::new(level(black)?, level(white)?)
+                        .with_gamma(gamma(g)?)
+                        .with_output(level(out_black)?, level(out_white)?)
+                }
+                _ => return Err("levels expects BLACK,WHITE[,GAMMA[,OUT_BLACK,OUT_WHITE]]".into()),
+            };
+            if levels.black >= levels.white {
+                return Err("Black point must be below the white point".into());
+            }
+            Box::new(LevelsFilter::new(levels))
+        }
+        "curves" if !args.is_empty() => {
+            let text = std::fs::read_to_string(args).map_err(|e| format!("{args}: {e}"))?;
+            Box::new(CurvesFilter::from_json(&text).map_err(|e| format!("{args}: {e}"))?)
+        }
+        "sepia" => {
+            let strength = if args.is_empty() { 1.0 } else { number()? };
+            if !(0.0..=1.0).contains(&strength) {
+                return Err("Strength must be between 0.0 and 1.0".into());
+            }
+            Box::new(SepiaFilter::new(strength))
+        }
+        "gradient-map" => Box::new(GradientMapFilter::new(args.parse()?)),
+        "lut" if !args.is_empty() => Box::new(LutFilter::new(ColorLut::open(Path::new(args))?)),
+        _ => return Err(format!("unknown step: {step}")),
+    })
+}
+
+/// Parse steps into a chain that applies them in order.
+pub fn parse_chain<S: AsRef<str>>(steps: &[S]) -> Result<FilterChain<RgbImage>, String> {
+    steps
+        .iter()
+        .map(|step| parse_step(step.as_ref()))
+        .collect::<Result<_, _>>()
+        .map(FilterChain::new)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Rgb;
+
+    fn gray_ramp() -> RgbImage {
+        RgbImage::from_fn(16, 1, |x, _| Rgb([(x * 17) as u8; 3]))
+    }
+
+    #[test]
+    fn test_parse_valid_steps() {
+        let img = gray_ramp();
+        let invert = parse_step("invert").unwrap();
+        assert_eq!(invert.apply(&img), InvertFilter.apply(&img));
+        let chain = parse_chain(&["invert", "levels:0,255,1,10,245", "sepia:0"]).unwrap();
+        assert_eq!(chain.filters.len(), 3);
+        let out = chain.apply(&img);
+        assert_eq!(*out.get_pixel(0, 0), Rgb([245; 3]));
+        assert_eq!(*out.get_pixel(15, 0), Rgb([10; 3]));
+        for step in [
+            "gamma:0.45",
+            "exposure:-0.5",
+            "brightness:10",
+            "contrast:1.2",
+            "levels:16,235",
+            "levels:16,235,1.1",
+            "sepia",
+            "gradient-map:0:#000000,1:#ff8800",
+        ] {
+            assert!(parse_step(step).is_ok(), "{}", step);
+        }
+        assert_eq!(parse_chain::<&str>(&[]).unwrap().apply(&img), img);
+    }
+
+    #[test]
+    fn test_parse_curves_and_lut_files() {
+        let dir = std::env::temp_dir().join(format!("pixelate-parse-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let curves = dir.join("negative.json");
+        std::fs::write(&curves, r#"{"master": [[0, 255], [255, 0]]}"#).unwrap();
+        let lut = dir.join("negative.cube");
+        std::fs::write(
+            &lut,
+            ColorLut::bake(2, |img| InvertFilter.apply(img)).to_cube(),
+        )
+        .unwrap();
+        let img = gray_ramp();
+        for step in [
+            format!("curves:{}", curves.display()),
+            format!("lut:{}", lut.display()),
+        ] {
+            assert_eq!(
+                parse_step(&step).unwrap().apply(&img),
+                InvertFilter.apply(&img)
+            );
+        }
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert!(parse_step(&format!("curves:{}", curves.display())).is_err());
+    }
+
+    #[test]
+    fn test_parse_invalid_steps() {
+        for step in [
+            "",
+            "blur",
+            "invert:1",
+            "gamma",
+            "gamma:0",
+            "gamma:NaN",
+            "gamma:inf",
+            "exposure:NaN",
+            "exposure:inf",
+            "brightness:NaN",
+            "contrast:inf",
+            "gamma:x",
+            "gamma:1,2",
+            "contrast:-1",
+            "levels:16",
+            "levels:200,100",
+            "levels:0,256",
+            "levels:0.5,255",
+            "levels:0,255,1,10",
+            "levels:0,255,0",
+            "levels:0,255,-1",
+            "levels:0,255,inf",
+            "levels:0,255,NaN",
+            "sepia:2",
+            "curves",
+            "lut",
+            "gradient-map:nope",
+        ] {
+            assert!(parse_step(step).is_err(), "{}", step);
+        }
+        assert!(parse_chain(&["invert", "blur"]).is_err());
+    }
+}
@@ -0,0 +1,341 @@
+//! Read-only image statistics: per-channel summaries and 256-bin histograms.
+//!
+//! Example: print the mean luminance of an image.
+//! ```no_run
+//! use pixelate::stats::ImageStats;
+//! let img = image::open("input.jpg").unwrap().to_rgb8();
+//! let stats = ImageStats::compute(&img, &[50.0]);
+//! println!("{}", stats.channel("luma").unwrap().mean);
+//! ```
+use image::{ImageBuffer, Rgb};
+use serde_json::json;
+
+use crate::color::srgb_luma;
+
+/// Percentiles reported when the caller does not choose any.
+pub const DEFAULT_PERCENTILES: [f64; 7] = [1.0, 5.0, 25.0, 50.0, 75.0, 95.0, 99.0];
+
+/// Summary of one 8-bit channel.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ChannelStats {
+    /// Channel name (`red`, `green`, `blue` or `luma`).
+    pub name: &'static str,
+    /// Darkest level present (0 for an empty histogram).
+    pub min: u8,
+    /// Brightest level present (0 for an empty histogram).
+    pub max: u8,
+    /// Mean level.
+    pub mean: f64,
+    /// Population standard deviation.
+    pub stddev: f64,
+    /// `(percentile, level)` pairs, nearest-rank.
+    pub percentiles: Vec<(f64, u8)>,
+    /// Shannon entropy of the histogram in bits (0..=8).
+    pub entropy: f64,
+    /// Number of pixels at each level.
+    pub histogram: [u32; 256],
+}
+
+impl ChannelStats {
+    /// Summarize a channel from its histogram.
+    pub fn from_histogram(name: &'static str, histogram: [u32; 256], percentiles: &[f64]) -> Self {
+        let total: u64 = histogram.iter().map(|&c| c as u64).sum();
+        let occupied = || histogram.iter().enumerate().filter(|(_, &c)| c > 0);
+        let min = occupied().map(|(i, _)| i as u8).next().unwrap_or(0);
+        let max = occupied().map(|(i, _)| i as u8).next_back().unwrap_or(0);
+        let (mut mean, mut stddev, mut entropy) = (0.0, 0.0, 0.0);
+        if total > 0 {
+            let n = total as f64;
+            mean = occupied().map(|(i, &c)| i as f64 * c as f64).sum::<f64>() / n;
+            stddev = (occupied()
+                .map(|(i, &c)| (i as f64 - mean).powi(2) * c as f64)
+                .sum::<f64>()
+                / n)
+                .sqrt();
+            entropy = -occupied()
+                .map(|(_, &c)| {
+                    let p = c as f64 / n;
+                    p * p.log2()
+                })
+                .sum::<f64>();
+        }
+        let percentiles = percentiles
+            .iter()
+            .map(|&p| (p, percentile_level(&histogram, total, p)))
+            .collect();
+        Self {
+            name,
+            min,
+            max,
+            mean,
+            stddev,
+            percentiles,
+            entropy,
+            histogram,
+        }
+    }
+}
+
+/// Smallest level whose cumulative count reaches `p` percent of `total`.
+fn percentile_level(histogram: &[u32; 256], total: u64, p: f64) -> u8 {
+    let rank = ((p.clamp(0.0, 100.0) / 100.0 * total as f64).ceil() as u64).max(1);
+    let mut cumulative = 0u64;
+    for (level, &count) in histogram.iter().enumerate() {
+        cumulative += count as u64;
+        if cumulative >= rank {
+            return level as u8;
+        }
+    }
+    0
+}
+
+/// Statistics of an RGB image: one summary per channel plus the luminance.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ImageStats {
+    /// Image width in pixels.
+    pub width: u32,
+    /// Image height in pixels.
+    pub height: u32,
+    /// Red, green, blue and luma (Rec. 709 weights), in that order.
+    pub channels: Vec<ChannelStats>,
+    /// Number of distinct RGB colors.
+    pub unique_colors: usize,
+}
+
+impl ImageStats {
+    /// Compute statistics, reporting the given percentiles (0..=100).
+    pub fn compute(img: &ImageBuffer<Rgb<u8>, Vec<u8>>, percentiles: &[f64]) -> Self {
+        let mut histograms = [[0u32; 256]; 4];
+        // One bit per 24-bit color
+        let mut seen = vec![0u64; (1 << 24) / 64];
+        let mut unique_colors = 0;
+        for p in img.pixels() {
+            for c in 0..3 {
+                histograms[c][p[c] as usize] += 1;
+            }
+            let luma = srgb_luma(p).round().min(255.0) as usize;
+            histograms[3][luma] += 1;
+            let color = (p[0] as usize) << 16 | (p[1] as usize) << 8 | p[2] as usize;
+            let (word, bit) = (color / 64, 1u64 << (color % 64));
+            if seen[word] & bit == 0 {
+                seen[word] |= bit;
+                unique_colors += 1;
+            }
+        }
+        let names = ["red", "green", "blue", "luma"];
+        let channels = names
+            .iter()
+            .zip(histograms)
+            .map(|(name, hist)| ChannelStats::from_histogram(name, hist, percentiles))
+            .collect();
+        Self {
+            width: img.width(),
+            height: img.height(),
+            channels,
+            unique_colors,
+        }
+    }
+
+    /// Look up a channel by name.
+    pub fn channel(&self, name: &str) -> Option<&ChannelStats> {
+        self.channels.iter().find(|c| c.name == name)
+    }
+
+    /// All statistics, including the histograms, as pretty-printed JSON.
+    pub fn to_json(&self) -> String {
+        let channels: serde_json::Map<String, serde_json::Value> = self
+            .channels
+            .iter()
+            .map(|c| {
+                let percentiles: serde_json::Map<String, serde_json::Value> = c
+                    .percentiles
+                    .iter()
+                    .map(|(p, level)| (format!("p{}", p), json!(level)))
+                    .collect();
+                let value = json!({
+                    "min": c.min,
+                    "max": c.max,
+                    "mean": c.mean,
+                    "stddev": c.stddev,
+                    "percentiles": percentiles,
+                    "entropy": c.entropy,
+                    "histogram": c.histogram.to_vec(),
+                });
+                (c.name.to_string(), value)
+            })
+            .collect();
+        let value = json!({
+            "width": self.width,
+            "height": self.height,
+            "pixels": self.width as u64 * self.height as u64,
+            "unique_colors": self.unique_colors,
+            "channels": channels,
+        });
+        serde_json::to_string_pretty(&value).unwrap_or_default()
+    }
+
+    /// The histograms as CSV: a `level` column followed by one column per channel.
+    pub fn histogram_csv(&self) -> String {
+        let mut out = String::from("level");
+        for c in &self.channels {
+            out.push(',');
+            out.push_str(c.name);
+        }
+        out.push('\n');
+        for level in 0..256 {
+            out.push_str(&level.to_string());
+            for c in &self.channels {
+                out.push(',');
+                out.push_str(&c.histogram[level].to_string());
+            }
+            out.push('\n');
+        }
+        out
+    }
+
+    /// Human-readable summary table.
+    pub fn summary(&self) -> String {
+        let mut out = format!(
+            "{}x{} pixels, {} unique colors\n\n",
+            self.width, self.height, self.unique_colors
+        );
+        out.push_str("channel   min  max     mean   stddev  entropy");
+        if let Some(c) = self.channels.first() {
+            for (p, _) in &c.percentiles {
+                out.push_str(&format!(" {:>5}", format!("p{}", p)));
+            }
+        }
+        out.push('\n');
+        for c in &self.channels {
+            out.push_str(&format!(
+                "{:<8} {:>4} {:>4} {:>8.2} {:>8.2} {:>8.3}",
+                c.name, c.min, c.max, c.mean, c.stddev, c.entropy
+            ));
+            for (_, level) in &c.percentiles {
+                out.push_str(&format!(" {:>5}", level));
+            }
+            out.push('\n');
+        }
+        out
+    }
+}
+
+/// ASCII bar chart of a histogram grouped into `bins` rows (1..=256), with
+/// bars up to `width` characters long.
+pub fn ascii_histogram(histogram: &[u32; 256], bins: usize, width: usize) -> String {
+    let bins = bins.clamp(1, 256);
+    let groups: Vec<(usize, usize, u64)> = (0..bins)
+        .map(|b| {
+            let (start, end) = (b * 256 / bins, (b + 1) * 256 / bins);
+            let count = histogram[start..end].iter().map(|&c| c as u64).sum();
+            (start, end - 1, count)
+        })
+        .collect();
+    let peak = groups.iter().map(|g| g.2).max().unwrap_or(0).max(1);
+    let mut out = String::new();
+    for (start, end, count) in groups {
+        let len = (count as f64 / peak as f64 * width as f64).round() as usize;
+        out.push_str(&format!(
+            "{:>3}-{:>3} |{:<width$} {}\n",
+            start,
+            end,
+            "#".repeat(len),
+            count,
+            width = width
+        ));
+    }
+    out
+}
+
+/// Render the red, green and blue histograms as an additive overlay on black
+/// (overlaps turn yellow, cyan, magenta or white). Each of the 256 levels is
+/// `column_width` pixels wide; bars are scaled to the tallest bin.
+pub fn render_histogram(
+    stats: &ImageStats,
+    column_width: u32,
+    height: u32,
+) -> ImageBuffer<Rgb<u8>, Vec<u8>> {
+    let column_width = column_width.max(1);
+    let peak = stats
+        .channels
+        .iter()
+        .take(3)
+        .flat_map(|c| c.histogram.iter())
+        .copied()
+        .max()
+        .unwrap_or(0)
+        .max(1);
+    ImageBuffer::from_fn(256 * column_width, height, |x, y| {
+        let level = (x / column_width) as usize;
+        let from_bottom = (height - y) as f64;
+        Rgb([0, 1, 2].map(|c| {
+            let bar = stats.channels[c].histogram[level] as f64 / peak as f64 * height as f64;
+            if from_bottom <= bar.ceil() && bar > 0.0 {
+                255
+            } else {
+                0
+            }
+        }))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn two_tone() -> ImageBuffer<Rgb<u8>, Vec<u8>> {
+        ImageBuffer::from_fn(4, 2, |x, _| {
+            if x < 2 {
+                Rgb([0, 0, 0])
+            } else {
+                Rgb([200, 100, 50])
+            }
+        })
+    }
+
+    #[test]
+    fn test_channel_stats() {
+        let stats = ImageStats::compute(&two_tone(), &DEFAULT_PERCENTILES);
+        assert_eq!(stats.unique_colors, 2);
+        let red = stats.channel("red").unwrap();
+        assert_eq!((red.min, red.max), (0, 200));
+        assert_eq!(red.mean, 100.0);
+        assert_eq!(red.stddev, 100.0);
+        assert!((red.entropy - 1.0).abs() < 1e-12);
+        assert_eq!(red.percentiles[3], (50.0, 0));
+        assert_eq!(red.percentiles[4], (75.0, 200));
+        assert_eq!(red.histogram[200], 4);
+        let luma = stats.channel("luma").unwrap();
+        assert_eq!(luma.max, 118);
+    }
+
+    #[test]
+    fn test_empty_channel() {
+        let stats = ChannelStats::from_histogram("red", [0; 256], &[50.0]);
+        assert_eq!(
+            (stats.min, stats.max, stats.mean, stats.entropy),
+            (0, 0, 0.0, 0.0)
+        );
+    }
+
+    #[test]
+    fn test_reports() {
+        let stats = ImageStats::compute(&two_tone(), &[50.0]);
+        let json: serde_json::Value = serde_json::from_str(&stats.to_json()).unwrap();
+        assert_eq!(json["unique_colors"], 2);
+        assert_eq!(json["channels"]["green"]["max"], 100);
+        assert_eq!(json["channels"]["green"]["percentiles"]["p50"], 0);
+        assert_eq!(json["channels"]["blue"]["histogram"][50], 4);
+        let csv = stats.histogram_csv();
+        assert_eq!(csv.lines().count(), 257);
+        assert_eq!(csv.lines().next(), Some("level,red,green,blue,luma"));
+        assert_eq!(csv.lines().nth(1), Some("0,4,4,4,4"));
+        let ascii = ascii_histogram(&stats.channels[0].histogram, 4, 10);
+        assert_eq!(ascii.lines().count(), 4);
+        assert!(ascii.starts_with("  0- 63 |########## 4"));
+        let png = render_histogram(&stats, 2, 50);
+        assert_eq!(png.dimensions(), (512, 50));
+        assert_eq!(*png.get_pixel(0, 0), Rgb([255, 255, 255]));
+        assert_eq!(*png.get_pixel(400, 0), Rgb([255, 0, 0]));
+    }
+}